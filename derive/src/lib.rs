@@ -160,14 +160,10 @@ fn expand_widget(input: TokenStream) -> Result<TokenStream> {
 fn parse_attributes(attributes: &[Attribute]) -> Result<(New, Event, Message)> {
   let (new, event, message) = attributes
     .iter()
-    .map(|attr| parse_attribute(attr))
-    .fold(Ok((None, None, None)), |result1, result2| {
-      match (result1, result2) {
-        (Ok((new1, event1, message1)), Ok((new2, event2, message2))) => {
-          Ok((new2.or(new1), event2.or(event1), message2.or(message1)))
-        },
-        (Err(x), _) | (_, Err(x)) => Err(x),
-      }
+    .map(parse_attribute)
+    .try_fold((None, None, None), |(new1, event1, message1), result| {
+      let (new2, event2, message2) = result?;
+      Ok::<_, Error>((new2.or(new1), event2.or(event1), message2.or(message1)))
     })?;
 
   // If no attribute is given we do not create a default implementation
@@ -279,6 +275,7 @@ fn expand_widget_input(
 // the types. Checking types is cumbersome and best-effort anyway as we
 // are working on tokens without context (a user could have a field of
 // type Id but that could map to ::foo::Id and not ::gui::Id).
+#[allow(clippy::single_element_loop)]
 fn check_struct_fields(fields: &Fields) -> Result<()> {
   let id = ("id", "::gui::Id");
 
@@ -363,6 +360,15 @@ fn expand_renderable_trait(input: &DeriveInput) -> Tokens {
       ) {
         renderer.render_done(self, cap, bbox)
       }
+
+      fn measure(
+        &self,
+        cap: &::gui::Cap,
+        renderer: &::gui::Renderer,
+        available: ::gui::Size,
+      ) -> ::gui::Size {
+        renderer.measure(self, cap, available)
+      }
     }
   }
 }
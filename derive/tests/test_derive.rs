@@ -71,6 +71,7 @@ where
 }
 
 
+#[allow(dead_code)]
 #[derive(Debug, Handleable)]
 #[gui(Event = Event)]
 struct TestHandleable {
@@ -178,16 +179,8 @@ where
 #[test]
 fn various_derive_combinations() {
   let (mut ui, r) = Ui::new(|| Box::new(()), |id, _cap| Box::new(TestWidget::new(id)));
-  let _ = ui.add_ui_widget(
-    r,
-    || Box::new(()),
-    |id, _cap| Box::new(TestWidgetCustom::new(id)),
-  );
-  let _ = ui.add_ui_widget(
-    r,
-    || Box::new(()),
-    |id, _cap| Box::new(TestWidgetT::<u32>::new(id)),
-  );
+  let _ = ui.add_ui_widget(r, || (), |id, _cap| TestWidgetCustom::new(id));
+  let _ = ui.add_ui_widget(r, || (), |id, _cap| TestWidgetT::<u32>::new(id));
 }
 
 #[tokio::test]
@@ -196,11 +189,7 @@ async fn generic_widget() {
     || Box::new(()),
     |id, _cap| Box::new(TestGeneric::<Event, Message>::new(id)),
   );
-  let _ = ui.add_ui_widget(
-    r,
-    || Box::new(()),
-    |id, _cap| Box::new(TestGeneric::<Event, Message>::new(id)),
-  );
+  let _ = ui.add_ui_widget(r, || (), |id, _cap| TestGeneric::<Event, Message>::new(id));
 
   ui.handle(()).await;
 }
@@ -213,8 +202,8 @@ async fn generic_event() {
   );
   let _ = ui.add_ui_widget(
     r,
-    || Box::new(()),
-    |id, _cap| Box::new(TestGenericEvent::<CustomEvent>::new(id)),
+    || (),
+    |id, _cap| TestGenericEvent::<CustomEvent>::new(id),
   );
   ui.focus(r);
 
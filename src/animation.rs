@@ -0,0 +1,110 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::time::Duration;
+use std::time::Instant;
+
+
+/// A function mapping the progress of an animation to the progress of
+/// the animated value.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Easing {
+  /// Progress at a constant rate.
+  Linear,
+  /// Start slowly and accelerate towards the end.
+  EaseIn,
+  /// Start quickly and decelerate towards the end.
+  EaseOut,
+  /// Start slowly, accelerate, and decelerate towards the end.
+  EaseInOut,
+}
+
+impl Easing {
+  /// Apply the easing function to the given progress, in the range
+  /// `[0.0, 1.0]`.
+  ///
+  /// Values outside of the range are clamped.
+  pub fn apply(&self, t: f64) -> f64 {
+    let t = t.clamp(0.0, 1.0);
+    match self {
+      Self::Linear => t,
+      Self::EaseIn => t * t,
+      Self::EaseOut => t * (2.0 - t),
+      Self::EaseInOut => {
+        if t < 0.5 {
+          2.0 * t * t
+        } else {
+          -1.0 + (4.0 - 2.0 * t) * t
+        }
+      },
+    }
+  }
+}
+
+impl Default for Easing {
+  fn default() -> Self {
+    Self::Linear
+  }
+}
+
+
+/// The interpolation of a single value over time.
+#[derive(Clone, Debug)]
+pub(crate) struct Tween {
+  /// The value at the start of the animation.
+  from: f64,
+  /// The value at the end of the animation.
+  to: f64,
+  /// The point in time at which the animation started.
+  start: Instant,
+  /// The duration of the animation.
+  duration: Duration,
+  /// The easing function to use.
+  easing: Easing,
+  /// The value as of the last time the tween was advanced.
+  value: f64,
+  /// Whether the animation has concluded.
+  done: bool,
+}
+
+impl Tween {
+  /// Create a new `Tween` starting at the given point in time.
+  pub(crate) fn new(from: f64, to: f64, start: Instant, duration: Duration, easing: Easing) -> Self {
+    Self {
+      from,
+      to,
+      start,
+      duration,
+      easing,
+      value: from,
+      done: false,
+    }
+  }
+
+  /// Advance the tween to the given point in time, returning whether
+  /// the animation is still in progress.
+  pub(crate) fn advance(&mut self, now: Instant) -> bool {
+    let elapsed = now.saturating_duration_since(self.start);
+    let progress = if elapsed >= self.duration {
+      1.0
+    } else {
+      elapsed.as_secs_f64() / self.duration.as_secs_f64()
+    };
+
+    self.value = self.from + (self.to - self.from) * self.easing.apply(progress);
+    self.done = progress >= 1.0;
+    !self.done
+  }
+
+  /// Retrieve the value as of the last time the tween was advanced.
+  #[inline]
+  pub(crate) fn value(&self) -> f64 {
+    self.value
+  }
+
+  /// Check whether the animation has concluded.
+  #[inline]
+  pub(crate) fn is_done(&self) -> bool {
+    self.done
+  }
+}
@@ -0,0 +1,347 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+#[cfg(feature = "terminal")]
+use std::cell::Ref;
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result;
+use std::fmt::Write as _;
+use std::rc::Rc;
+
+use crate::BBox;
+use crate::Cap;
+use crate::Point;
+use crate::Renderable;
+use crate::Renderer;
+use crate::RendererRegistry;
+use crate::Size;
+
+
+/// A color of a [`Cell`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Color {
+  /// The default color of the output device.
+  Default,
+  /// Black.
+  Black,
+  /// Red.
+  Red,
+  /// Green.
+  Green,
+  /// Yellow.
+  Yellow,
+  /// Blue.
+  Blue,
+  /// Magenta.
+  Magenta,
+  /// Cyan.
+  Cyan,
+  /// White.
+  White,
+  /// A color from a 256 color palette.
+  Indexed(u8),
+  /// A true color, specified by its red, green, and blue components.
+  Rgb(u8, u8, u8),
+}
+
+impl Default for Color {
+  fn default() -> Self {
+    Self::Default
+  }
+}
+
+
+/// Emphasis applied to the character of a [`Cell`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Emphasis {
+  /// Whether the character is rendered in bold.
+  pub bold: bool,
+  /// Whether the character is rendered in italics.
+  pub italic: bool,
+  /// Whether the character is underlined.
+  pub underline: bool,
+  /// Whether foreground and background colors are swapped.
+  pub reverse: bool,
+}
+
+
+/// The style of a [`Cell`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct CellStyle {
+  /// The foreground color.
+  pub fg: Color,
+  /// The background color.
+  pub bg: Color,
+  /// The emphasis of the character.
+  pub emphasis: Emphasis,
+}
+
+
+/// A single character cell, as managed by a [`CellBuffer`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Cell {
+  /// The character displayed in the cell.
+  pub ch: char,
+  /// The style with which to display the character.
+  pub style: CellStyle,
+}
+
+impl Default for Cell {
+  fn default() -> Self {
+    Self {
+      ch: ' ',
+      style: CellStyle::default(),
+    }
+  }
+}
+
+
+/// A grid of [`Cell`]s.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Grid {
+  /// The size of the grid.
+  size: Size,
+  /// The cells, row by row.
+  cells: Vec<Cell>,
+}
+
+impl Grid {
+  /// Create a new grid of the given size, containing default cells.
+  pub(crate) fn new(size: Size) -> Self {
+    Self {
+      size,
+      cells: vec![Cell::default(); usize::from(size.w) * usize::from(size.h)],
+    }
+  }
+
+  /// Retrieve the size of the grid.
+  #[inline]
+  pub(crate) fn size(&self) -> Size {
+    self.size
+  }
+
+  /// Retrieve the index of the cell at the given position.
+  #[inline]
+  fn index(&self, x: u16, y: u16) -> Option<usize> {
+    if x < self.size.w && y < self.size.h {
+      Some(usize::from(y) * usize::from(self.size.w) + usize::from(x))
+    } else {
+      None
+    }
+  }
+
+  /// Retrieve the cell at the given position.
+  #[inline]
+  pub(crate) fn get(&self, x: u16, y: u16) -> Option<&Cell> {
+    self.index(x, y).map(|idx| &self.cells[idx])
+  }
+
+  /// Retrieve the cell at the given position, mutably.
+  #[inline]
+  fn get_mut(&mut self, x: u16, y: u16) -> Option<&mut Cell> {
+    self.index(x, y).map(move |idx| &mut self.cells[idx])
+  }
+
+  /// Reset all cells to the default.
+  fn clear(&mut self) {
+    self.cells.iter_mut().for_each(|cell| *cell = Cell::default())
+  }
+}
+
+impl Display for Grid {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    for row in self.cells.chunks(usize::from(self.size.w).max(1)) {
+      for cell in row {
+        f.write_char(cell.ch)?;
+      }
+      f.write_char('\n')?;
+    }
+    Ok(())
+  }
+}
+
+
+/// A drawing surface restricted to a [`BBox`] of a [`CellBuffer`].
+///
+/// All coordinates are relative to the upper left corner of the
+/// bounding box and drawing outside of it has no effect.
+#[derive(Debug)]
+pub struct Canvas<'grid> {
+  /// The grid to draw on.
+  grid: &'grid mut Grid,
+  /// The bounding box to draw in, clipped to the grid.
+  bbox: BBox,
+}
+
+impl Canvas<'_> {
+  /// Retrieve the bounding box the canvas is restricted to.
+  ///
+  /// The bounding box is in absolute coordinates and clipped to the
+  /// area of the [`CellBuffer`].
+  pub fn bbox(&self) -> BBox {
+    self.bbox
+  }
+
+  /// Set the cell at the given position.
+  pub fn set(&mut self, x: u16, y: u16, ch: char, style: CellStyle) {
+    if x < self.bbox.w && y < self.bbox.h {
+      if let Some(cell) = self.grid.get_mut(self.bbox.x + x, self.bbox.y + y) {
+        *cell = Cell { ch, style };
+      }
+    }
+  }
+
+  /// Print text starting at the given position, returning the number
+  /// of cells written.
+  ///
+  /// Each character occupies a single cell and text not fitting into
+  /// the bounding box is cut off.
+  pub fn text(&mut self, x: u16, y: u16, text: &str, style: CellStyle) -> u16 {
+    if y >= self.bbox.h {
+      return 0
+    }
+
+    let mut written = 0;
+    for (ch, x) in text.chars().zip(x..self.bbox.w) {
+      let () = self.set(x, y, ch, style);
+      written += 1;
+    }
+    written
+  }
+
+  /// Fill the entire bounding box with the given character and style.
+  pub fn fill(&mut self, ch: char, style: CellStyle) {
+    for y in 0..self.bbox.h {
+      for x in 0..self.bbox.w {
+        let () = self.set(x, y, ch, style);
+      }
+    }
+  }
+
+  /// Draw a frame along the edges of the bounding box using the given
+  /// character and style.
+  pub fn frame(&mut self, ch: char, style: CellStyle) {
+    let (w, h) = (self.bbox.w, self.bbox.h);
+    for x in 0..w {
+      let () = self.set(x, 0, ch, style);
+      let () = self.set(x, h.saturating_sub(1), ch, style);
+    }
+    for y in 0..h {
+      let () = self.set(0, y, ch, style);
+      let () = self.set(w.saturating_sub(1), y, ch, style);
+    }
+  }
+}
+
+
+/// A [`Renderer`] drawing into an in-memory grid of styled character
+/// cells.
+///
+/// Widgets are drawn by functions registered for their type using
+/// [`CellBuffer::on`], which receive a [`Canvas`] restricted to the
+/// widget's bounding box. Widgets of a type without a registered
+/// function are not drawn and pass their bounding box on to their
+/// children unchanged.
+///
+/// The buffer is cleared in [`Renderer::pre_render`], but not before
+/// partial passes, in which only invalidated widgets are drawn anew on
+/// top of the existing contents. The contents can be inspected after
+/// rendering, for example, by converting the buffer into a string,
+/// which makes it well suited for testing. It may also serve as a
+/// starting point for terminal backends.
+pub struct CellBuffer {
+  /// The grid of cells, shared with the registered drawing functions.
+  grid: Rc<RefCell<Grid>>,
+  /// The registry dispatching to the registered drawing functions.
+  registry: RendererRegistry,
+}
+
+impl CellBuffer {
+  /// Create a new `CellBuffer` of the given size.
+  pub fn new(size: Size) -> Self {
+    Self {
+      grid: Rc::new(RefCell::new(Grid::new(size))),
+      registry: RendererRegistry::new().fallback(|_object, _cap, bbox| bbox),
+    }
+  }
+
+  /// Register the function for drawing widgets of type `W`.
+  ///
+  /// The function returns the bounding box available to the widget's
+  /// children, just as [`Renderer::render`] does.
+  pub fn on<W, F>(mut self, draw_fn: F) -> Self
+  where
+    W: Renderable,
+    F: Fn(&W, &dyn Cap, &mut Canvas<'_>) -> BBox + 'static,
+  {
+    let grid = Rc::clone(&self.grid);
+    self.registry = self.registry.on::<W, _>(move |widget, cap, bbox| {
+      let mut grid = grid.borrow_mut();
+      let mut canvas = Canvas {
+        bbox: bbox.intersect(&BBox::new(Point::default(), grid.size())),
+        grid: &mut grid,
+      };
+      draw_fn(widget, cap, &mut canvas)
+    });
+    self
+  }
+
+  /// Retrieve the size of the buffer.
+  pub fn size(&self) -> Size {
+    self.grid.borrow().size()
+  }
+
+  /// Resize the buffer, clearing its contents.
+  pub fn resize(&self, size: Size) {
+    *self.grid.borrow_mut() = Grid::new(size);
+  }
+
+  /// Retrieve the cell at the given position.
+  pub fn cell(&self, x: u16, y: u16) -> Option<Cell> {
+    self.grid.borrow().get(x, y).copied()
+  }
+
+  /// Retrieve the grid of cells.
+  #[cfg(feature = "terminal")]
+  pub(crate) fn grid(&self) -> Ref<'_, Grid> {
+    self.grid.borrow()
+  }
+}
+
+impl Renderer for CellBuffer {
+  fn renderable_area(&self) -> BBox {
+    BBox::new(Point::default(), self.size())
+  }
+
+  fn pre_render(&self) {
+    self.grid.borrow_mut().clear()
+  }
+
+  /// Keep the contents of the buffer, so that widgets not rendered
+  /// again are retained.
+  fn pre_render_partial(&self) {}
+
+  fn render(&self, object: &dyn Renderable, cap: &dyn Cap, bbox: BBox) -> BBox {
+    self.registry.render(object, cap, bbox)
+  }
+}
+
+impl Display for CellBuffer {
+  /// Format the characters of the buffer, row by row, with each row
+  /// being terminated by a newline.
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    Display::fmt(&*self.grid.borrow(), f)
+  }
+}
+
+impl Debug for CellBuffer {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    f.debug_struct("CellBuffer")
+      .field("size", &self.size())
+      .field("registry", &self.registry)
+      .finish()
+  }
+}
@@ -0,0 +1,72 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::cell::Cell;
+use std::fmt::Debug;
+use std::rc::Rc;
+use std::time::Duration;
+use std::time::Instant;
+
+
+/// A source of the current time.
+///
+/// A [`Ui`][crate::Ui] consults its clock whenever it needs to know
+/// the current time, for example, when scheduling timers.
+pub trait Clock: Debug {
+  /// Retrieve the current time.
+  fn now(&self) -> Instant;
+}
+
+
+/// A [`Clock`] reporting the system's monotonic time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+  fn now(&self) -> Instant {
+    Instant::now()
+  }
+}
+
+
+/// A [`Clock`] that only advances when explicitly told to.
+///
+/// Clones of a `ManualClock` share the same time, meaning that one
+/// clone can be handed to a [`Ui`][crate::Ui] while another one is used
+/// for controlling the time. This functionality is mostly useful for
+/// testing.
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+  now: Rc<Cell<Instant>>,
+}
+
+impl ManualClock {
+  /// Create a new `ManualClock` starting at the given time.
+  pub fn new(now: Instant) -> Self {
+    Self {
+      now: Rc::new(Cell::new(now)),
+    }
+  }
+
+  /// Advance the clock by the given duration.
+  pub fn advance(&self, duration: Duration) {
+    self.now.set(self.now.get() + duration)
+  }
+
+  /// Set the clock to the given time.
+  pub fn set(&self, now: Instant) {
+    self.now.set(now)
+  }
+}
+
+impl Default for ManualClock {
+  fn default() -> Self {
+    Self::new(Instant::now())
+  }
+}
+
+impl Clock for ManualClock {
+  fn now(&self) -> Instant {
+    self.now.get()
+  }
+}
@@ -0,0 +1,468 @@
+// Copyright (C) 2018-2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt::Debug;
+
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+
+/// A type usable as coordinate in the geometric primitives of this
+/// module.
+///
+/// The trait is implemented for the common integer types as well as for
+/// floating point numbers, allowing for wide, signed, and fractional
+/// coordinate spaces. Arithmetic saturates at the bounds of the type.
+pub trait Coord: Copy + Debug + Default + PartialOrd + 'static {
+  /// The value zero.
+  const ZERO: Self;
+
+  /// Add two values, saturating at the bounds of the type.
+  fn saturating_add(self, other: Self) -> Self;
+
+  /// Subtract two values, saturating at the bounds of the type.
+  fn saturating_sub(self, other: Self) -> Self;
+
+  /// Convert an `i64` into a value of this type, saturating at the
+  /// bounds of the type.
+  fn from_i64(value: i64) -> Self;
+
+  /// Convert a value of this type into an `i64`, saturating at the
+  /// bounds of `i64` and rounding to the nearest integer, if necessary.
+  fn to_i64(self) -> i64;
+
+  /// Convert an `f64` into a value of this type, saturating at the
+  /// bounds of the type and rounding to the nearest integer, if
+  /// necessary.
+  fn from_f64(value: f64) -> Self;
+
+  /// Convert a value of this type into an `f64`.
+  fn to_f64(self) -> f64;
+
+  /// Convert a value of this type into one of type `U`, saturating at
+  /// the bounds of `U`.
+  ///
+  /// Integers are converted exactly, if representable. Fractional
+  /// values are rounded to the nearest integer if `U` is an integer
+  /// type and keep their fraction otherwise.
+  fn cast<U>(self) -> U
+  where
+    U: Coord;
+}
+
+macro_rules! impl_coord_int {
+  ($($ty:ty),*) => {
+    $(
+      impl Coord for $ty {
+        const ZERO: Self = 0;
+
+        #[inline]
+        fn saturating_add(self, other: Self) -> Self {
+          <$ty>::saturating_add(self, other)
+        }
+
+        #[inline]
+        fn saturating_sub(self, other: Self) -> Self {
+          <$ty>::saturating_sub(self, other)
+        }
+
+        #[inline]
+        fn from_i64(value: i64) -> Self {
+          Self::try_from(value).unwrap_or(if value < 0 { Self::MIN } else { Self::MAX })
+        }
+
+        #[inline]
+        fn to_i64(self) -> i64 {
+          i64::try_from(self).unwrap_or(i64::MAX)
+        }
+
+        #[inline]
+        fn from_f64(value: f64) -> Self {
+          // Float to integer casts saturate.
+          value.round() as Self
+        }
+
+        #[inline]
+        fn to_f64(self) -> f64 {
+          self as f64
+        }
+
+        #[inline]
+        fn cast<U>(self) -> U
+        where
+          U: Coord,
+        {
+          U::from_i64(self.to_i64())
+        }
+      }
+    )*
+  };
+}
+
+impl_coord_int!(u16, u32, u64, i16, i32, i64);
+
+macro_rules! impl_coord_float {
+  ($($ty:ty),*) => {
+    $(
+      impl Coord for $ty {
+        const ZERO: Self = 0.0;
+
+        #[inline]
+        fn saturating_add(self, other: Self) -> Self {
+          (self + other).clamp(<$ty>::MIN, <$ty>::MAX)
+        }
+
+        #[inline]
+        fn saturating_sub(self, other: Self) -> Self {
+          (self - other).clamp(<$ty>::MIN, <$ty>::MAX)
+        }
+
+        #[inline]
+        fn from_i64(value: i64) -> Self {
+          value as $ty
+        }
+
+        #[inline]
+        fn to_i64(self) -> i64 {
+          self.round() as i64
+        }
+
+        #[inline]
+        #[allow(trivial_numeric_casts)]
+        fn from_f64(value: f64) -> Self {
+          (value as $ty).clamp(<$ty>::MIN, <$ty>::MAX)
+        }
+
+        #[inline]
+        fn to_f64(self) -> f64 {
+          f64::from(self)
+        }
+
+        #[inline]
+        fn cast<U>(self) -> U
+        where
+          U: Coord,
+        {
+          U::from_f64(self.to_f64())
+        }
+      }
+    )*
+  };
+}
+
+impl_coord_float!(f32, f64);
+
+
+/// Retrieve the lesser of two values.
+#[inline]
+fn min<T>(a: T, b: T) -> T
+where
+  T: PartialOrd,
+{
+  if b < a {
+    b
+  } else {
+    a
+  }
+}
+
+/// Retrieve the greater of two values.
+#[inline]
+fn max<T>(a: T, b: T) -> T
+where
+  T: PartialOrd,
+{
+  if b > a {
+    b
+  } else {
+    a
+  }
+}
+
+/// Offset a coordinate by a signed amount, saturating at the bounds of
+/// its type.
+#[inline]
+fn offset<T>(value: T, delta: i64) -> T
+where
+  T: Coord,
+{
+  if delta < 0 {
+    value.saturating_sub(T::from_i64(delta.saturating_neg()))
+  } else {
+    value.saturating_add(T::from_i64(delta))
+  }
+}
+
+
+/// A point in two-dimensional space.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Point<T = u16> {
+  /// The x-coordinate of the point.
+  pub x: T,
+  /// The y-coordinate of the point.
+  pub y: T,
+}
+
+
+/// The size of a rectangular area, in the same units as used by
+/// [`BBox`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Size<T = u16> {
+  /// The width.
+  pub w: T,
+  /// The height.
+  pub h: T,
+}
+
+
+/// Distances by which to shrink the edges of a [`BBox`], for example to
+/// account for a border or padding.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Insets<T = u16> {
+  /// The distance from the top edge.
+  pub top: T,
+  /// The distance from the right edge.
+  pub right: T,
+  /// The distance from the bottom edge.
+  pub bottom: T,
+  /// The distance from the left edge.
+  pub left: T,
+}
+
+impl<T> Insets<T>
+where
+  T: Coord,
+{
+  /// Create `Insets` using the same distance for all edges.
+  pub fn uniform(inset: T) -> Self {
+    Self {
+      top: inset,
+      right: inset,
+      bottom: inset,
+      left: inset,
+    }
+  }
+}
+
+
+/// A bounding box representing the area that a widget may occupy. A
+/// bounding box always describes a rectangular area. The origin [x=0,
+/// y=0] is typically assumed to reside in the upper left corner of the
+/// screen, but it is really up to the individual
+/// [`Renderer`][crate::Renderer] to make do with whatever is provided.
+///
+/// The coordinate type defaults to `u16`, which is what the
+/// [`Ui`][crate::Ui] lays out widgets in. Other [`Coord`] types can be
+/// used for calculations in wider, signed, or fractional coordinate
+/// spaces, for example, to position content partly off-screen, as well
+/// as by a [`Renderer`][crate::Renderer] working with device
+/// coordinates (see [`ScaledRenderer`][crate::ScaledRenderer]). Such
+/// bounding boxes can be converted (and clipped) using
+/// [`BBox::cast`].
+///
+/// All operations on bounding boxes saturate, meaning that results
+/// never wrap around but are clamped to the range of representable
+/// coordinates instead. A bounding box with a width or height not
+/// greater than zero is considered empty.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct BBox<T = u16> {
+  /// The x-coordinate of the bounding box.
+  pub x: T,
+  /// The y-coordinate of the bounding box.
+  pub y: T,
+  /// The width of the bounding box.
+  pub w: T,
+  /// The height of the bounding box.
+  pub h: T,
+}
+
+impl<T> BBox<T>
+where
+  T: Coord,
+{
+  /// Create a bounding box from its origin and size.
+  pub fn new(origin: Point<T>, size: Size<T>) -> Self {
+    Self {
+      x: origin.x,
+      y: origin.y,
+      w: size.w,
+      h: size.h,
+    }
+  }
+
+  /// Retrieve the upper left corner of the bounding box.
+  pub fn origin(&self) -> Point<T> {
+    Point {
+      x: self.x,
+      y: self.y,
+    }
+  }
+
+  /// Retrieve the size of the bounding box.
+  pub fn size(&self) -> Size<T> {
+    Size {
+      w: self.w,
+      h: self.h,
+    }
+  }
+
+  /// Check whether the bounding box covers no area at all.
+  pub fn is_empty(&self) -> bool {
+    !(self.w > T::ZERO && self.h > T::ZERO)
+  }
+
+  /// Retrieve the exclusive right and bottom edges of the bounding box.
+  fn edges(&self) -> (T, T) {
+    (self.x.saturating_add(self.w), self.y.saturating_add(self.h))
+  }
+
+  /// Create a bounding box from its (exclusive) edges.
+  fn from_edges(x1: T, y1: T, x2: T, y2: T) -> Self {
+    Self {
+      x: x1,
+      y: y1,
+      w: max(x2.saturating_sub(x1), T::ZERO),
+      h: max(y2.saturating_sub(y1), T::ZERO),
+    }
+  }
+
+  /// Check whether the given point lies within the bounding box.
+  pub fn contains(&self, point: Point<T>) -> bool {
+    point.x >= self.x
+      && point.y >= self.y
+      && point.x.saturating_sub(self.x) < self.w
+      && point.y.saturating_sub(self.y) < self.h
+  }
+
+  /// Check whether the bounding box shares any area with another one.
+  pub fn overlaps(&self, other: &BBox<T>) -> bool {
+    !self.intersect(other).is_empty()
+  }
+
+  /// Calculate the intersection of two bounding boxes.
+  ///
+  /// If the bounding boxes do not overlap, the result is empty.
+  pub fn intersect(&self, other: &BBox<T>) -> BBox<T> {
+    let (x2, y2) = self.edges();
+    let (other_x2, other_y2) = other.edges();
+
+    Self::from_edges(
+      max(self.x, other.x),
+      max(self.y, other.y),
+      min(x2, other_x2),
+      min(y2, other_y2),
+    )
+  }
+
+  /// Calculate the smallest bounding box containing both bounding
+  /// boxes.
+  ///
+  /// Empty bounding boxes do not contribute to the result.
+  pub fn union(&self, other: &BBox<T>) -> BBox<T> {
+    if other.is_empty() {
+      return *self
+    }
+    if self.is_empty() {
+      return *other
+    }
+
+    let (x2, y2) = self.edges();
+    let (other_x2, other_y2) = other.edges();
+    Self::from_edges(
+      min(self.x, other.x),
+      min(self.y, other.y),
+      max(x2, other_x2),
+      max(y2, other_y2),
+    )
+  }
+
+  /// Shrink the bounding box by the given insets.
+  ///
+  /// Insets exceeding the bounding box's extent result in an empty
+  /// bounding box.
+  pub fn inset(&self, insets: Insets<T>) -> BBox<T> {
+    let (x2, y2) = self.edges();
+    let x1 = min(self.x.saturating_add(insets.left), x2);
+    let y1 = min(self.y.saturating_add(insets.top), y2);
+
+    Self::from_edges(
+      x1,
+      y1,
+      max(x2.saturating_sub(insets.right), x1),
+      max(y2.saturating_sub(insets.bottom), y1),
+    )
+  }
+
+  /// Split the bounding box into a left and a right part, with the
+  /// left one having the given width.
+  ///
+  /// If the bounding box is narrower than `width`, the right part is
+  /// empty.
+  pub fn split_horizontal(&self, width: T) -> (BBox<T>, BBox<T>) {
+    let width = max(min(width, self.w), T::ZERO);
+    let left = BBox { w: width, ..*self };
+    let right = BBox {
+      x: self.x.saturating_add(width),
+      w: self.w.saturating_sub(width),
+      ..*self
+    };
+    (left, right)
+  }
+
+  /// Split the bounding box into a top and a bottom part, with the top
+  /// one having the given height.
+  ///
+  /// If the bounding box is less high than `height`, the bottom part is
+  /// empty.
+  pub fn split_vertical(&self, height: T) -> (BBox<T>, BBox<T>) {
+    let height = max(min(height, self.h), T::ZERO);
+    let top = BBox { h: height, ..*self };
+    let bottom = BBox {
+      y: self.y.saturating_add(height),
+      h: self.h.saturating_sub(height),
+      ..*self
+    };
+    (top, bottom)
+  }
+
+  /// Move the bounding box by the given offsets.
+  ///
+  /// The bounding box keeps its size as long as it can be represented.
+  /// Parts that would be moved beyond the bounds of the coordinate
+  /// space are cut off.
+  pub fn translate(&self, dx: i64, dy: i64) -> BBox<T> {
+    let (x2, y2) = self.edges();
+    Self::from_edges(
+      offset(self.x, dx),
+      offset(self.y, dy),
+      offset(x2, dx),
+      offset(y2, dy),
+    )
+  }
+
+  /// Convert the bounding box into one using a different coordinate
+  /// type.
+  ///
+  /// Parts of the bounding box that cannot be represented using the
+  /// target type are cut off. That makes this method suitable for
+  /// clipping a bounding box in a signed or wide coordinate space to
+  /// the one used by a [`Renderer`][crate::Renderer].
+  ///
+  /// Edges are converted individually using [`Coord::cast`]. When
+  /// converting fractional coordinates to an integer type, each edge is
+  /// rounded to the nearest integer, so that adjacent bounding boxes
+  /// stay adjacent. Conversions between floating point types keep the
+  /// fraction.
+  pub fn cast<U>(&self) -> BBox<U>
+  where
+    U: Coord,
+  {
+    let (x2, y2) = self.edges();
+
+    BBox::from_edges(self.x.cast(), self.y.cast(), x2.cast(), y2.cast())
+  }
+}
@@ -5,7 +5,9 @@ use std::fmt::Debug;
 
 use async_trait::async_trait;
 
+use crate::Id;
 use crate::MutCap;
+use crate::Request;
 
 
 /// A trait representing an object capable of handling events.
@@ -40,4 +42,20 @@ pub trait Handleable<E, M>: Debug {
   async fn respond(&self, message: &mut M, cap: &mut dyn MutCap<E, M>) -> Option<M> {
     None
   }
+
+  /// Answer a query.
+  ///
+  /// This is the handler for a typed query, as sent by the `query`
+  /// method of [`MutCap`]. A widget not providing a response via the
+  /// given [`Request`] (the default behavior) causes the query to fail.
+  #[allow(unused_variables)]
+  async fn answer(&self, request: &mut Request<'_>, cap: &mut dyn MutCap<E, M>) {}
+
+  /// Observe changes to the data of watched widgets.
+  ///
+  /// This is the handler for changes to the data of widgets registered
+  /// via [`MutCap::watch`]. `changed` contains the [`Id`]s of all
+  /// watched widgets whose data changed since the last notification.
+  #[allow(unused_variables)]
+  async fn observe(&self, changed: &[Id], cap: &mut dyn MutCap<E, M>) {}
 }
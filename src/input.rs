@@ -0,0 +1,508 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::str::from_utf8;
+use std::time::Duration;
+use std::time::Instant;
+
+
+/// The escape character, introducing escape sequences.
+const ESC: u8 = 0x1b;
+/// The sequence starting a bracketed paste.
+const PASTE_START: &[u8] = b"\x1b[200~";
+/// The sequence ending a bracketed paste.
+const PASTE_END: &[u8] = b"\x1b[201~";
+
+
+/// A key on the keyboard.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Key {
+  /// A key producing a character.
+  ///
+  /// Keys pressed together with the control key are reported as the
+  /// corresponding lower case character, with [`Modifiers::ctrl`]
+  /// being set.
+  Char(char),
+  /// The enter key.
+  Enter,
+  /// The tab key.
+  Tab,
+  /// The tab key pressed together with shift.
+  BackTab,
+  /// The backspace key.
+  Backspace,
+  /// The escape key.
+  Esc,
+  /// The up arrow key.
+  Up,
+  /// The down arrow key.
+  Down,
+  /// The left arrow key.
+  Left,
+  /// The right arrow key.
+  Right,
+  /// The home key.
+  Home,
+  /// The end key.
+  End,
+  /// The page up key.
+  PageUp,
+  /// The page down key.
+  PageDown,
+  /// The insert key.
+  Insert,
+  /// The delete key.
+  Delete,
+  /// A function key, with the given number (starting at one).
+  F(u8),
+}
+
+
+/// Modifier keys held down while pressing a key or using the mouse.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Modifiers {
+  /// Whether the shift key was held down.
+  pub shift: bool,
+  /// Whether the alt (or meta) key was held down.
+  pub alt: bool,
+  /// Whether the control key was held down.
+  pub ctrl: bool,
+}
+
+impl Modifiers {
+  /// Decode modifiers from the bit mask used in the parameters of
+  /// escape sequences, i.e., 1 for shift, 2 for alt, and 4 for control.
+  fn from_bits(bits: u16) -> Self {
+    Self {
+      shift: bits & 1 != 0,
+      alt: bits & 2 != 0,
+      ctrl: bits & 4 != 0,
+    }
+  }
+}
+
+
+/// A mouse button.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum MouseButton {
+  /// The left mouse button.
+  Left,
+  /// The middle mouse button.
+  Middle,
+  /// The right mouse button.
+  Right,
+}
+
+
+/// The kind of a [`MouseEvent`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum MouseKind {
+  /// A button was pressed.
+  Press(MouseButton),
+  /// A button was released.
+  Release(MouseButton),
+  /// The mouse was moved while a button was held down.
+  Drag(MouseButton),
+  /// The mouse was moved without a button being held down.
+  Move,
+  /// The mouse wheel was scrolled up.
+  ScrollUp,
+  /// The mouse wheel was scrolled down.
+  ScrollDown,
+}
+
+
+/// An event originating from the mouse.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct MouseEvent {
+  /// What happened.
+  pub kind: MouseKind,
+  /// The zero based column the mouse pointer is located in.
+  pub x: u16,
+  /// The zero based row the mouse pointer is located in.
+  pub y: u16,
+  /// The modifier keys held down.
+  pub modifiers: Modifiers,
+}
+
+
+/// An input event decoded by an [`InputDecoder`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum InputEvent {
+  /// A key was pressed.
+  Key(Key, Modifiers),
+  /// A mouse event, as reported in SGR mode.
+  Mouse(MouseEvent),
+  /// Text was pasted, as reported in bracketed paste mode.
+  Paste(String),
+}
+
+
+/// The result of parsing input.
+#[derive(Debug, PartialEq)]
+enum Parse {
+  /// An event got decoded from the given number of bytes.
+  Event(InputEvent, usize),
+  /// The given number of bytes got consumed without producing an
+  /// event, e.g., because they form an unsupported sequence.
+  Skip(usize),
+  /// More input is required.
+  Incomplete,
+  /// A bracketed paste starts, spanning the given number of bytes.
+  PasteStart(usize),
+}
+
+
+/// Create a key event without modifiers.
+fn key(key: Key) -> InputEvent {
+  InputEvent::Key(key, Modifiers::default())
+}
+
+/// Decode a single byte that does not start an escape sequence or a
+/// multi-byte UTF-8 character.
+fn parse_byte(byte: u8) -> InputEvent {
+  let ctrl = |c: u8| {
+    let modifiers = Modifiers {
+      ctrl: true,
+      ..Default::default()
+    };
+    InputEvent::Key(Key::Char(char::from(c)), modifiers)
+  };
+
+  match byte {
+    b'\r' | b'\n' => key(Key::Enter),
+    b'\t' => key(Key::Tab),
+    0x08 | 0x7f => key(Key::Backspace),
+    ESC => key(Key::Esc),
+    0x00 => ctrl(b' '),
+    0x01..=0x1a => ctrl(byte - 1 + b'a'),
+    0x1c..=0x1f => ctrl(byte - 0x1c + b'4'),
+    _ => key(Key::Char(char::from(byte))),
+  }
+}
+
+/// Decode a (potentially multi-byte) UTF-8 character or a single
+/// control byte.
+fn parse_char(input: &[u8]) -> Parse {
+  let byte = input[0];
+  let len = match byte {
+    0x00..=0x7f => return Parse::Event(parse_byte(byte), 1),
+    0xc0..=0xdf => 2,
+    0xe0..=0xef => 3,
+    0xf0..=0xf7 => 4,
+    _ => return Parse::Skip(1),
+  };
+
+  if input.len() < len {
+    if input[1..].iter().all(|byte| byte & 0xc0 == 0x80) {
+      return Parse::Incomplete
+    }
+    return Parse::Skip(1)
+  }
+
+  match from_utf8(&input[..len]) {
+    // SANITY: The string contains exactly one character.
+    Ok(s) => Parse::Event(key(Key::Char(s.chars().next().unwrap())), len),
+    Err(..) => Parse::Skip(1),
+  }
+}
+
+/// Parse the numeric parameters of a CSI sequence.
+fn parse_params(params: &[u8]) -> Vec<u16> {
+  from_utf8(params)
+    .unwrap_or_default()
+    .split(';')
+    .map(|param| param.parse().unwrap_or(0))
+    .collect()
+}
+
+/// Decode the modifiers encoded in the given CSI parameter.
+fn param_modifiers(param: Option<&u16>) -> Modifiers {
+  Modifiers::from_bits(param.copied().unwrap_or(1).saturating_sub(1))
+}
+
+/// Map the final byte of a CSI or SS3 sequence not carrying a numeric
+/// key code to the key it represents.
+fn final_key(byte: u8) -> Option<Key> {
+  let key = match byte {
+    b'A' => Key::Up,
+    b'B' => Key::Down,
+    b'C' => Key::Right,
+    b'D' => Key::Left,
+    b'H' => Key::Home,
+    b'F' => Key::End,
+    b'Z' => Key::BackTab,
+    b'P' => Key::F(1),
+    b'Q' => Key::F(2),
+    b'R' => Key::F(3),
+    b'S' => Key::F(4),
+    _ => return None,
+  };
+  Some(key)
+}
+
+/// Map the key code of a `CSI <code> ~` sequence to the key it
+/// represents.
+fn tilde_key(code: u16) -> Option<Key> {
+  let key = match code {
+    1 | 7 => Key::Home,
+    2 => Key::Insert,
+    3 => Key::Delete,
+    4 | 8 => Key::End,
+    5 => Key::PageUp,
+    6 => Key::PageDown,
+    11..=15 => Key::F((code - 10) as u8),
+    17..=21 => Key::F((code - 11) as u8),
+    23..=24 => Key::F((code - 12) as u8),
+    _ => return None,
+  };
+  Some(key)
+}
+
+/// Decode an SGR mouse report with the given parameters and final
+/// byte.
+fn parse_mouse(params: &[u8], final_byte: u8) -> Option<InputEvent> {
+  let params = parse_params(params);
+  let (code, x, y) = match params.as_slice() {
+    [code, x, y] => (*code, *x, *y),
+    _ => return None,
+  };
+
+  let button = match code & 0b11 {
+    0 => Some(MouseButton::Left),
+    1 => Some(MouseButton::Middle),
+    2 => Some(MouseButton::Right),
+    _ => None,
+  };
+  let kind = match (code & 0b110_0000, button, final_byte) {
+    (64, Some(MouseButton::Left), _) => MouseKind::ScrollUp,
+    (64, Some(MouseButton::Middle), _) => MouseKind::ScrollDown,
+    (32, Some(button), _) => MouseKind::Drag(button),
+    (32, None, _) => MouseKind::Move,
+    (0, Some(button), b'M') => MouseKind::Press(button),
+    (0, Some(button), b'm') => MouseKind::Release(button),
+    _ => return None,
+  };
+
+  let event = MouseEvent {
+    kind,
+    x: x.saturating_sub(1),
+    y: y.saturating_sub(1),
+    modifiers: Modifiers::from_bits((code >> 2) & 0b111),
+  };
+  Some(InputEvent::Mouse(event))
+}
+
+/// Parse a CSI sequence, i.e., one starting with `ESC [`.
+fn parse_csi(input: &[u8]) -> Parse {
+  let body = &input[2..];
+  let end = match body.iter().position(|byte| (0x40..=0x7e).contains(byte)) {
+    Some(end) => end,
+    None if body.iter().all(|byte| (0x20..=0x3f).contains(byte)) => return Parse::Incomplete,
+    None => return Parse::Skip(2),
+  };
+  let len = 2 + end + 1;
+  let params = &body[..end];
+  let final_byte = body[end];
+
+  if &input[..len] == PASTE_START {
+    return Parse::PasteStart(len)
+  }
+
+  let event = if let Some(params) = params.strip_prefix(b"<") {
+    match final_byte {
+      b'M' | b'm' => parse_mouse(params, final_byte),
+      _ => None,
+    }
+  } else if !params.iter().all(|byte| byte.is_ascii_digit() || *byte == b';') {
+    None
+  } else {
+    let params = parse_params(params);
+    match final_byte {
+      b'~' => params
+        .first()
+        .and_then(|code| tilde_key(*code))
+        .map(|key| InputEvent::Key(key, param_modifiers(params.get(1)))),
+      _ => final_key(final_byte).map(|key| {
+        let modifiers = param_modifiers(params.get(1));
+        match key {
+          Key::BackTab => InputEvent::Key(
+            key,
+            Modifiers {
+              shift: true,
+              ..modifiers
+            },
+          ),
+          _ => InputEvent::Key(key, modifiers),
+        }
+      }),
+    }
+  };
+
+  match event {
+    Some(event) => Parse::Event(event, len),
+    None => Parse::Skip(len),
+  }
+}
+
+/// Parse input starting with an escape character.
+fn parse_escape(input: &[u8]) -> Parse {
+  match input.get(1) {
+    None => Parse::Incomplete,
+    Some(b'[') => parse_csi(input),
+    Some(b'O') => match input.get(2) {
+      None => Parse::Incomplete,
+      Some(byte) => match final_key(*byte) {
+        Some(found) => Parse::Event(key(found), 3),
+        None => Parse::Skip(3),
+      },
+    },
+    Some(&ESC) => Parse::Event(key(Key::Esc), 1),
+    Some(..) => match parse_char(&input[1..]) {
+      Parse::Event(InputEvent::Key(found, modifiers), len) => {
+        let modifiers = Modifiers {
+          alt: true,
+          ..modifiers
+        };
+        Parse::Event(InputEvent::Key(found, modifiers), 1 + len)
+      },
+      Parse::Skip(..) => Parse::Event(key(Key::Esc), 1),
+      parse => parse,
+    },
+  }
+}
+
+/// Parse the next input event.
+fn parse(input: &[u8]) -> Parse {
+  match input[0] {
+    ESC => parse_escape(input),
+    _ => parse_char(input),
+  }
+}
+
+
+/// A decoder turning raw bytes as read from a terminal into
+/// [`InputEvent`]s.
+///
+/// The decoder understands the escape sequences for special keys and
+/// modifiers emitted by common (xterm compatible) terminals, mouse
+/// reports in SGR mode, and bracketed paste. Enabling the latter two
+/// modes on the terminal is up to the user.
+///
+/// Input may arrive in arbitrary chunks and sequences split across
+/// chunks are decoded once complete. Because a press of the escape key
+/// is indistinguishable from the start of an escape sequence, input
+/// starting with an escape character that has not been completed
+/// within the timeout is reported as it is, once it is expired (see
+/// [`InputDecoder::expire`]).
+///
+/// Decoded events can be converted into the event type used by the
+/// [`Ui`][crate::Ui] by means of a `From` implementation.
+#[derive(Debug)]
+pub struct InputDecoder {
+  /// Bytes that could not be decoded yet.
+  buffer: Vec<u8>,
+  /// Whether we are within a bracketed paste.
+  paste: bool,
+  /// The time at which the last bytes were fed.
+  last: Option<Instant>,
+  /// The time after which incomplete input is reported as is.
+  timeout: Duration,
+}
+
+impl InputDecoder {
+  /// Create a new `InputDecoder` using the given timeout for incomplete
+  /// escape sequences.
+  pub fn new(timeout: Duration) -> Self {
+    Self {
+      buffer: Vec::new(),
+      paste: false,
+      last: None,
+      timeout,
+    }
+  }
+
+  /// Retrieve the point in time at which pending input times out, if
+  /// there is any.
+  pub fn deadline(&self) -> Option<Instant> {
+    if self.paste || self.buffer.first() != Some(&ESC) {
+      return None
+    }
+    self.last.map(|last| last + self.timeout)
+  }
+
+  /// Decode as many events as possible from the buffered input.
+  fn drain(&mut self, events: &mut Vec<InputEvent>) {
+    while !self.buffer.is_empty() {
+      if self.paste {
+        match self
+          .buffer
+          .windows(PASTE_END.len())
+          .position(|window| window == PASTE_END)
+        {
+          Some(end) => {
+            let text = String::from_utf8_lossy(&self.buffer[..end]).into_owned();
+            let _ = self.buffer.drain(..end + PASTE_END.len());
+            let () = events.push(InputEvent::Paste(text));
+            self.paste = false;
+            continue
+          },
+          None => break,
+        }
+      }
+
+      let len = match parse(&self.buffer) {
+        Parse::Event(event, len) => {
+          let () = events.push(event);
+          len
+        },
+        Parse::Skip(len) => len,
+        Parse::PasteStart(len) => {
+          self.paste = true;
+          len
+        },
+        Parse::Incomplete => break,
+      };
+      let _ = self.buffer.drain(..len);
+    }
+  }
+
+  /// Feed bytes read from the terminal to the decoder, returning all
+  /// events that could be decoded.
+  ///
+  /// Pending input that timed out by the time the bytes arrive is
+  /// expired first, as per [`InputDecoder::expire`].
+  pub fn decode(&mut self, bytes: &[u8], now: Instant) -> Vec<InputEvent> {
+    let mut events = self.expire(now);
+    let () = self.buffer.extend_from_slice(bytes);
+    self.last = Some(now);
+
+    let () = self.drain(&mut events);
+    events
+  }
+
+  /// Expire pending input, if it timed out at the given point in time.
+  ///
+  /// An escape character at the start of the input is reported as
+  /// [`Key::Esc`] and decoding continues after it.
+  pub fn expire(&mut self, now: Instant) -> Vec<InputEvent> {
+    let mut events = Vec::new();
+    while let Some(deadline) = self.deadline() {
+      if deadline > now {
+        break
+      }
+
+      let _ = self.buffer.remove(0);
+      let () = events.push(key(Key::Esc));
+      let () = self.drain(&mut events);
+    }
+    events
+  }
+}
+
+impl Default for InputDecoder {
+  /// Create an `InputDecoder` with a timeout of 50ms.
+  fn default() -> Self {
+    Self::new(Duration::from_millis(50))
+  }
+}
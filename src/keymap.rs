@@ -0,0 +1,346 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::Cap;
+use crate::Id;
+use crate::MutCap;
+
+
+/// The context in which a key binding is active.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum KeyContext {
+  /// The binding is active when the widget with the given [`Id`] is
+  /// part of the focus chain.
+  Widget(Id),
+  /// The binding is active when a widget of the given type is part of
+  /// the focus chain.
+  Type(TypeId),
+  /// The binding is always active. Global bindings have the lowest
+  /// precedence and their messages are sent to the root widget.
+  Global,
+}
+
+impl KeyContext {
+  /// Create a [`KeyContext::Type`] for widgets of type `W`.
+  pub fn of<W>() -> Self
+  where
+    W: 'static,
+  {
+    Self::Type(TypeId::of::<W>())
+  }
+}
+
+
+/// The outcome of feeding a key to a [`Keymap`].
+#[derive(Debug, PartialEq)]
+pub enum Resolution<K, M> {
+  /// A binding matched and its message got sent to the given widget.
+  Dispatched {
+    /// The widget the message was sent to.
+    widget: Id,
+    /// The widget's response to the message.
+    response: Option<M>,
+  },
+  /// The keys fed so far form the prefix of at least one binding and
+  /// more keys are required.
+  Pending,
+  /// The keys fed so far do not match any binding. They are returned
+  /// and the sequence is reset.
+  Unbound(Vec<K>),
+}
+
+
+/// The match state of a key sequence within a single context.
+struct Match<M> {
+  /// The message of the binding matching the sequence exactly.
+  exact: Option<M>,
+  /// Whether the sequence is a strict prefix of another binding.
+  prefix: bool,
+}
+
+
+/// A keymap mapping sequences of keys to messages.
+///
+/// Bindings are registered for a [`KeyContext`]. When resolving a key,
+/// the focus chain is consulted, starting at the focused widget and
+/// walking up to the root. For each widget on the way, bindings
+/// registered for its [`Id`] take precedence over those registered for
+/// its type. Global bindings are checked last.
+///
+/// A sequence that matches a binding exactly while also being a prefix
+/// of a longer one stays pending until either more keys arrive or it
+/// times out (see [`Keymap::expire`]). If the keys arriving do not
+/// continue any binding, the longest part of the sequence that matched
+/// a binding exactly is dispatched and the remaining keys are fed
+/// anew.
+pub struct Keymap<K, M> {
+  /// The registered bindings, by context.
+  bindings: HashMap<KeyContext, Vec<(Vec<K>, M)>>,
+  /// The keys of the currently pending sequence.
+  pending: Vec<K>,
+  /// The time at which the last key of the pending sequence arrived.
+  last: Option<Instant>,
+  /// The time after which a pending sequence expires.
+  timeout: Duration,
+}
+
+impl<K, M> Keymap<K, M>
+where
+  K: Clone + PartialEq,
+  M: Clone,
+{
+  /// Create a new, empty `Keymap` using the given timeout for pending
+  /// key sequences.
+  pub fn new(timeout: Duration) -> Self {
+    Self {
+      bindings: HashMap::new(),
+      pending: Vec::new(),
+      last: None,
+      timeout,
+    }
+  }
+
+  /// Bind a sequence of keys to a message in the given context.
+  ///
+  /// A previous binding for the same sequence in the same context is
+  /// replaced.
+  ///
+  /// # Panics
+  ///
+  /// This method panics if the key sequence is empty.
+  pub fn bind<I>(&mut self, context: KeyContext, keys: I, message: M)
+  where
+    I: IntoIterator<Item = K>,
+  {
+    let keys = keys.into_iter().collect::<Vec<_>>();
+    assert!(!keys.is_empty(), "Key sequence must not be empty");
+
+    let bindings = self.bindings.entry(context).or_default();
+    match bindings.iter_mut().find(|(k, _)| *k == keys) {
+      Some(binding) => binding.1 = message,
+      None => bindings.push((keys, message)),
+    }
+  }
+
+  /// Remove the binding for the given key sequence in the given
+  /// context, returning its message.
+  pub fn unbind(&mut self, context: KeyContext, keys: &[K]) -> Option<M> {
+    let bindings = self.bindings.get_mut(&context)?;
+    let pos = bindings.iter().position(|(k, _)| k == keys)?;
+    Some(bindings.remove(pos).1)
+  }
+
+  /// Retrieve the keys of the currently pending sequence.
+  pub fn pending(&self) -> &[K] {
+    &self.pending
+  }
+
+  /// Retrieve the point in time at which the pending sequence expires,
+  /// if any.
+  pub fn deadline(&self) -> Option<Instant> {
+    self.last.map(|last| last + self.timeout)
+  }
+
+  /// Check how the given key sequence matches the bindings in the
+  /// given context.
+  fn lookup(&self, context: &KeyContext, keys: &[K]) -> Match<M> {
+    let mut result = Match {
+      exact: None,
+      prefix: false,
+    };
+
+    if let Some(bindings) = self.bindings.get(context) {
+      for (k, message) in bindings {
+        if k.len() == keys.len() {
+          if k == keys {
+            result.exact = Some(message.clone());
+          }
+        } else if k.len() > keys.len() && k.starts_with(keys) {
+          result.prefix = true;
+        }
+      }
+    }
+    result
+  }
+
+  /// Retrieve all contexts in order of precedence, along with the
+  /// widget to send messages of bindings in them to.
+  fn contexts(cap: &dyn Cap) -> Vec<(Id, KeyContext)> {
+    let mut contexts = Vec::new();
+    let mut widget = Some(cap.focused().unwrap_or_else(|| cap.root_id()));
+
+    while let Some(id) = widget {
+      contexts.push((id, KeyContext::Widget(id)));
+      contexts.push((id, KeyContext::Type(cap.widget_type(id))));
+      widget = cap.parent_id(id);
+    }
+    contexts.push((cap.root_id(), KeyContext::Global));
+    contexts
+  }
+
+  /// Find the binding for the given key sequence, following the focus
+  /// chain.
+  ///
+  /// The result is the widget to send a message to, the message of an
+  /// exactly matching binding (if any), and whether the sequence is a
+  /// prefix of a longer binding.
+  fn resolve(&self, cap: &dyn Cap, keys: &[K]) -> Option<(Id, Match<M>)> {
+    Self::contexts(cap).into_iter().find_map(|(id, context)| {
+      let m = self.lookup(&context, keys);
+      if m.exact.is_some() || m.prefix {
+        Some((id, m))
+      } else {
+        None
+      }
+    })
+  }
+
+  /// Find the binding matching the given key sequence exactly,
+  /// following the focus chain.
+  ///
+  /// In contrast to [`resolve`][Self::resolve], contexts in which the
+  /// sequence merely is a prefix of a binding are skipped.
+  fn resolve_exact(&self, cap: &dyn Cap, keys: &[K]) -> Option<(Id, M)> {
+    Self::contexts(cap)
+      .into_iter()
+      .find_map(|(id, context)| self.lookup(&context, keys).exact.map(|message| (id, message)))
+  }
+
+  /// Find the longest strict prefix of the pending key sequence that
+  /// matches a binding exactly, following the focus chain.
+  ///
+  /// The result is the length of the prefix, the widget to send a
+  /// message to, and the message.
+  fn resolve_longest_exact(&self, cap: &dyn Cap) -> Option<(usize, Id, M)> {
+    (1..self.pending.len()).rev().find_map(|len| {
+      self
+        .resolve_exact(cap, &self.pending[..len])
+        .map(|(id, message)| (len, id, message))
+    })
+  }
+
+  /// Reset the pending key sequence, returning the keys it contained.
+  fn reset(&mut self) -> Vec<K> {
+    self.last = None;
+    self.pending.drain(..).collect()
+  }
+
+  /// Feed a key to the keymap.
+  ///
+  /// If the key completes a binding, the bound message is sent to the
+  /// widget that the binding was found for, using [`MutCap::send`].
+  ///
+  /// A pending sequence that timed out by the time the key arrives is
+  /// expired first, as per [`Keymap::expire`]. The resolution of the
+  /// expired sequence, if any, is reported ahead of the one for the
+  /// given key.
+  ///
+  /// If the key causes the pending sequence to no longer match any
+  /// binding, the longest part of the sequence matching a binding
+  /// exactly is dispatched and the keys following it are fed again,
+  /// possibly leading to further resolutions. Only if no such part
+  /// exists are all keys of the sequence reported as unbound.
+  pub async fn handle<E>(
+    &mut self,
+    cap: &mut dyn MutCap<E, M>,
+    key: K,
+    now: Instant,
+  ) -> Vec<Resolution<K, M>> {
+    let mut resolutions = Vec::new();
+    if let Some(resolution) = self.expire(cap, now).await {
+      let () = resolutions.push(resolution);
+    }
+
+    let mut keys = VecDeque::new();
+    let () = keys.push_back(key);
+
+    while let Some(key) = keys.pop_front() {
+      self.pending.push(key);
+      self.last = Some(now);
+
+      let resolution = match self.resolve(&**cap, &self.pending) {
+        Some((
+          widget,
+          Match {
+            exact: Some(message),
+            prefix: false,
+          },
+        )) => {
+          let _ = self.reset();
+          let response = cap.send(widget, message).await;
+          Resolution::Dispatched { widget, response }
+        },
+        Some((_, Match { prefix: true, .. })) => Resolution::Pending,
+        _ => match self.resolve_longest_exact(&**cap) {
+          Some((len, widget, message)) => {
+            let remaining = self.reset().split_off(len);
+            for key in remaining.into_iter().rev() {
+              let () = keys.push_front(key);
+            }
+            let response = cap.send(widget, message).await;
+            Resolution::Dispatched { widget, response }
+          },
+          None => Resolution::Unbound(self.reset()),
+        },
+      };
+
+      // A sequence pending only until keys fed anew continue it is not
+      // worth reporting.
+      if !matches!(resolution, Resolution::Pending) || keys.is_empty() {
+        let () = resolutions.push(resolution);
+      }
+    }
+    resolutions
+  }
+
+  /// Expire the pending key sequence, if it timed out at the given
+  /// point in time.
+  ///
+  /// A sequence that matches a binding exactly causes the bound message
+  /// to be sent. The binding is searched for in all contexts, including
+  /// those with lower precedence than the one in which the sequence was
+  /// pending. Otherwise the keys of the sequence are reported as
+  /// unbound. `None` is returned if there is no sequence that timed
+  /// out.
+  pub async fn expire<E>(
+    &mut self,
+    cap: &mut dyn MutCap<E, M>,
+    now: Instant,
+  ) -> Option<Resolution<K, M>> {
+    match self.deadline() {
+      Some(deadline) if deadline <= now => (),
+      _ => return None,
+    }
+
+    let resolution = match self.resolve_exact(&**cap, &self.pending) {
+      Some((widget, message)) => {
+        let _ = self.reset();
+        let response = cap.send(widget, message).await;
+        Resolution::Dispatched { widget, response }
+      },
+      _ => Resolution::Unbound(self.reset()),
+    };
+    Some(resolution)
+  }
+}
+
+impl<K, M> Debug for Keymap<K, M>
+where
+  K: Debug,
+{
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    f.debug_struct("Keymap")
+      .field("pending", &self.pending)
+      .field("timeout", &self.timeout)
+      .finish()
+  }
+}
@@ -0,0 +1,333 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt::Debug;
+
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::BBox;
+use crate::Cap;
+use crate::Id;
+use crate::Point;
+use crate::Size;
+
+
+/// A layout assigning bounding boxes to the children of a widget.
+///
+/// A layout is attached to a widget using
+/// [`MutCap::set_layout`][crate::MutCap::set_layout]. When the widget
+/// is rendered, the layout is asked to split up the widget's inner
+/// bounding box (the one returned by its `render` method) among its
+/// children. Children that the layout does not assign a bounding box to
+/// receive the entire inner bounding box.
+pub trait Layout: Debug {
+  /// Assign bounding boxes to the children of the given widget.
+  ///
+  /// `bbox` is the area available to the widget's children.
+  fn layout(&self, cap: &dyn Cap, widget: Id, bbox: BBox) -> Vec<(Id, BBox)>;
+
+  /// Determine the space available to the children of the given widget
+  /// during the measure pass.
+  ///
+  /// `available` is the space available to the widget's children. As
+  /// children are measured before their parent is laid out, the sizes
+  /// reported by [`Cap::measured_size`] may be outdated or missing at
+  /// this point. Children not listed receive all of `available`. By
+  /// default, children receive the size of the bounding box that
+  /// [`layout`][Self::layout] assigns to them.
+  fn allot(&self, cap: &dyn Cap, widget: Id, available: Size) -> Vec<(Id, Size)> {
+    self
+      .layout(cap, widget, BBox::new(Point::default(), available))
+      .into_iter()
+      .map(|(id, bbox)| (id, bbox.size()))
+      .collect()
+  }
+}
+
+
+/// The direction in which a [`Flow`] arranges its children.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Direction {
+  /// Arrange children from left to right.
+  Row,
+  /// Arrange children from top to bottom.
+  Column,
+}
+
+
+/// A constraint on the size of a child along the main axis of a
+/// [`Flow`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Constraint {
+  /// The child has exactly the given size.
+  Fixed(u16),
+  /// The child has at least the given size, but grows to fill the
+  /// remaining space just as [`Constraint::Fill`] does.
+  Min(u16),
+  /// The child fills the remaining space just as [`Constraint::Fill`]
+  /// does, but grows no larger than the given size.
+  Max(u16),
+  /// The child fills the remaining space. Equivalent to a
+  /// [`Constraint::Weight`] of one.
+  Fill,
+  /// The child receives a share of the remaining space proportional to
+  /// the given weight.
+  Weight(u16),
+  /// The child has the size it reported during the measure pass (see
+  /// [`Renderable::measure`][crate::Renderable::measure]).
+  Content,
+}
+
+impl Constraint {
+  /// Retrieve the size the constraint requires at a minimum.
+  fn base(&self) -> u32 {
+    match self {
+      Self::Fixed(size) | Self::Min(size) => u32::from(*size),
+      Self::Max(..) | Self::Fill | Self::Weight(..) | Self::Content => 0,
+    }
+  }
+
+  /// Retrieve the weight with which the constraint grows.
+  fn weight(&self) -> u32 {
+    match self {
+      Self::Fixed(..) | Self::Content => 0,
+      Self::Min(..) | Self::Max(..) | Self::Fill => 1,
+      Self::Weight(weight) => u32::from(*weight),
+    }
+  }
+
+  /// Retrieve the size the constraint permits at a maximum.
+  fn limit(&self) -> u32 {
+    match self {
+      Self::Max(size) => u32::from(*size),
+      _ => u32::MAX,
+    }
+  }
+}
+
+
+/// Distribute the given length among a set of constraints.
+///
+/// Space left after satisfying all base sizes is handed out to flexible
+/// items by weight, with items hitting their limit dropping out and the
+/// remainder getting redistributed. If the base sizes exceed the
+/// available length, later items are truncated.
+fn distribute(length: u16, constraints: &[Constraint]) -> Vec<u16> {
+  let length = u32::from(length);
+  let mut sizes = constraints.iter().map(Constraint::base).collect::<Vec<_>>();
+  let mut remaining = length.saturating_sub(sizes.iter().sum());
+  let mut flexible = (0..constraints.len())
+    .filter(|i| constraints[*i].weight() > 0)
+    .collect::<Vec<_>>();
+
+  loop {
+    let total = flexible.iter().map(|i| constraints[*i].weight()).sum::<u32>();
+    if total == 0 || remaining == 0 {
+      break
+    }
+
+    let share = |i: usize| remaining * constraints[i].weight() / total;
+    let capped = flexible
+      .iter()
+      .copied()
+      .filter(|i| sizes[*i] + share(*i) > constraints[*i].limit())
+      .collect::<Vec<_>>();
+
+    if capped.is_empty() {
+      let mut distributed = 0;
+      for i in &flexible {
+        sizes[*i] += share(*i);
+        distributed += share(*i);
+      }
+
+      // Hand out what is left due to rounding, one by one.
+      let mut leftover = remaining - distributed;
+      for i in &flexible {
+        if leftover == 0 {
+          break
+        }
+        if sizes[*i] < constraints[*i].limit() {
+          sizes[*i] += 1;
+          leftover -= 1;
+        }
+      }
+      break
+    }
+
+    for i in capped {
+      let limit = constraints[i].limit();
+      remaining -= limit - sizes[i];
+      sizes[i] = limit;
+      flexible.retain(|x| *x != i);
+    }
+  }
+
+  let mut offset = 0;
+  sizes
+    .into_iter()
+    .map(|size| {
+      let size = size.min(length - offset);
+      offset += size;
+      size as u16
+    })
+    .collect()
+}
+
+
+/// A [`Layout`] arranging children in a row or column.
+///
+/// Children are laid out in the order in which they were added, along
+/// the main axis given by the [`Direction`], with their size being
+/// determined by the associated [`Constraint`]. Along the cross axis
+/// children span the entire available area. Hidden children are not
+/// allotted any space.
+#[derive(Clone, Debug)]
+pub struct Flow {
+  /// The direction in which children are arranged.
+  direction: Direction,
+  /// The children to lay out, along with their constraints.
+  children: Vec<(Id, Constraint)>,
+}
+
+impl Flow {
+  /// Create a new, empty `Flow` arranging children in the given
+  /// direction.
+  pub fn new(direction: Direction) -> Self {
+    Self {
+      direction,
+      children: Vec::new(),
+    }
+  }
+
+  /// Create a new, empty `Flow` arranging children from left to right.
+  pub fn row() -> Self {
+    Self::new(Direction::Row)
+  }
+
+  /// Create a new, empty `Flow` arranging children from top to bottom.
+  pub fn column() -> Self {
+    Self::new(Direction::Column)
+  }
+
+  /// Add a child to the flow, placing it after all previously added
+  /// ones.
+  pub fn child(mut self, child: Id, constraint: Constraint) -> Self {
+    self.children.push((child, constraint));
+    self
+  }
+}
+
+impl Layout for Flow {
+  /// Determine the space available to the children of the flow.
+  ///
+  /// Children sized by their content may use all space along the main
+  /// axis that is not required by the base sizes of their siblings.
+  /// All other children receive the size they would be laid out with.
+  fn allot(&self, cap: &dyn Cap, widget: Id, available: Size) -> Vec<(Id, Size)> {
+    let length = match self.direction {
+      Direction::Row => available.w,
+      Direction::Column => available.h,
+    };
+    let required = self
+      .children
+      .iter()
+      .filter(|(id, _)| cap.is_visible(*id))
+      .map(|(_, constraint)| constraint.base())
+      .sum::<u32>();
+    let remaining = u32::from(length).saturating_sub(required) as u16;
+
+    let laid_out = self.layout(cap, widget, BBox::new(Point::default(), available));
+    laid_out
+      .into_iter()
+      .map(|(id, bbox)| {
+        let content = self
+          .children
+          .iter()
+          .any(|(child, constraint)| *child == id && *constraint == Constraint::Content);
+        let size = match (content, self.direction) {
+          (false, _) => bbox.size(),
+          (true, Direction::Row) => Size {
+            w: remaining,
+            ..available
+          },
+          (true, Direction::Column) => Size {
+            h: remaining,
+            ..available
+          },
+        };
+        (id, size)
+      })
+      .collect()
+  }
+
+  fn layout(&self, cap: &dyn Cap, _widget: Id, bbox: BBox) -> Vec<(Id, BBox)> {
+    let children = self
+      .children
+      .iter()
+      .filter(|(id, _)| cap.is_visible(*id))
+      .collect::<Vec<_>>();
+    let length = match self.direction {
+      Direction::Row => bbox.w,
+      Direction::Column => bbox.h,
+    };
+    let constraints = children
+      .iter()
+      .map(|(id, constraint)| match constraint {
+        Constraint::Content => {
+          let size = cap.measured_size(*id).unwrap_or_default();
+          match self.direction {
+            Direction::Row => Constraint::Fixed(size.w),
+            Direction::Column => Constraint::Fixed(size.h),
+          }
+        },
+        _ => *constraint,
+      })
+      .collect::<Vec<_>>();
+    let sizes = distribute(length, &constraints);
+
+    let mut offset = 0;
+    children
+      .into_iter()
+      .zip(sizes)
+      .map(|((id, _), size)| {
+        let child_bbox = match self.direction {
+          Direction::Row => BBox {
+            x: bbox.x.saturating_add(offset),
+            w: size,
+            ..bbox
+          },
+          Direction::Column => BBox {
+            y: bbox.y.saturating_add(offset),
+            h: size,
+            ..bbox
+          },
+        };
+        offset += size;
+        (*id, child_bbox)
+      })
+      .collect()
+  }
+}
+
+
+/// A viewport through which the children of a widget are shown.
+///
+/// A widget with a viewport attached (see
+/// [`MutCap::set_viewport`][crate::MutCap::set_viewport]) lays out its
+/// children in a content area of the given size, with the origin at
+/// [x=0, y=0]. Of this content area only the part starting at the
+/// scroll offset and being as large as the widget's inner bounding box
+/// is displayed. Bounding boxes of children are translated accordingly
+/// and clipped to the widget's inner bounding box.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Viewport {
+  /// The size of the content area.
+  pub content: Size,
+  /// The position in the content area that is displayed in the upper
+  /// left corner of the widget's inner bounding box.
+  pub offset: Point,
+}
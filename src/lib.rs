@@ -25,26 +25,113 @@
 //! strives for being completely agnostic of the underlying system and
 //! its rendering machinery as well as event dispatching.
 
+mod animation;
+mod cell;
+mod clock;
+mod geometry;
 mod handleable;
+#[cfg(feature = "terminal")]
+mod input;
+mod keymap;
+mod layout;
 mod mergeable;
 mod object;
 mod placeholder;
+mod query;
+mod recording;
+mod registry;
 mod renderable;
 mod renderer;
+mod scaled;
+#[cfg(feature = "serde")]
+mod snapshot;
+mod style;
+mod svg;
+#[cfg(feature = "terminal")]
+mod terminal;
+mod trace;
+mod typed;
 mod ui;
 mod widget;
 
 use self::placeholder::Placeholder;
 
+pub use self::animation::Easing;
+pub use self::cell::Canvas;
+pub use self::cell::Cell;
+pub use self::cell::CellBuffer;
+pub use self::cell::CellStyle;
+pub use self::cell::Color;
+pub use self::cell::Emphasis;
+pub use self::clock::Clock;
+pub use self::clock::ManualClock;
+pub use self::clock::SystemClock;
+pub use self::geometry::BBox;
+pub use self::geometry::Coord;
+pub use self::geometry::Insets;
+pub use self::geometry::Point;
+pub use self::geometry::Size;
 pub use self::handleable::Handleable;
+#[cfg(feature = "terminal")]
+pub use self::input::InputDecoder;
+#[cfg(feature = "terminal")]
+pub use self::input::InputEvent;
+#[cfg(feature = "terminal")]
+pub use self::input::Key;
+#[cfg(feature = "terminal")]
+pub use self::input::Modifiers;
+#[cfg(feature = "terminal")]
+pub use self::input::MouseButton;
+#[cfg(feature = "terminal")]
+pub use self::input::MouseEvent;
+#[cfg(feature = "terminal")]
+pub use self::input::MouseKind;
+pub use self::keymap::KeyContext;
+pub use self::keymap::Keymap;
+pub use self::keymap::Resolution;
+pub use self::layout::Constraint;
+pub use self::layout::Direction;
+pub use self::layout::Flow;
+pub use self::layout::Layout;
+pub use self::layout::Viewport;
 pub use self::mergeable::Mergeable;
 pub use self::object::Object;
+pub use self::query::Query;
+pub use self::query::QueryError;
+pub use self::query::Request;
+pub use self::recording::DisplayItem;
+pub use self::recording::RecordingRenderer;
+pub use self::recording::RenderedWidget;
+pub use self::registry::RendererRegistry;
 pub use self::renderable::Renderable;
-pub use self::renderer::BBox;
 pub use self::renderer::Renderer;
+pub use self::scaled::ScaledRenderer;
+#[cfg(feature = "serde")]
+pub use self::snapshot::RestoreError;
+#[cfg(feature = "serde")]
+pub use self::snapshot::Snapshot;
+#[cfg(feature = "serde")]
+pub use self::snapshot::SnapshotRegistry;
+#[cfg(feature = "serde")]
+pub use self::snapshot::WidgetSnapshot;
+pub use self::style::Style;
+pub use self::style::StyleKey;
+pub use self::style::Theme;
+pub use self::svg::SvgRenderer;
+#[cfg(feature = "terminal")]
+pub use self::terminal::Terminal;
+pub use self::trace::Recorder;
+pub use self::trace::ReplayError;
+pub use self::trace::Trace;
+pub use self::trace::TraceEntry;
+pub use self::trace::TraceInput;
+pub use self::typed::DataError;
+pub use self::typed::TypedId;
 pub use self::ui::Cap;
 pub use self::ui::Id;
 pub use self::ui::MutCap;
+pub use self::ui::TaskFuture;
+pub use self::ui::TimerId;
 pub use self::ui::Ui;
 pub use self::widget::Widget;
 
@@ -13,6 +13,7 @@ use crate::MutCap;
 use crate::Object;
 use crate::Renderable;
 use crate::Renderer;
+use crate::Request;
 use crate::Widget;
 
 
@@ -51,6 +52,10 @@ impl<E, M> Handleable<E, M> for Placeholder {
   async fn respond(&self, _message: &mut M, _cap: &mut dyn MutCap<E, M>) -> Option<M> {
     unreachable!()
   }
+
+  async fn answer(&self, _request: &mut Request<'_>, _cap: &mut dyn MutCap<E, M>) {
+    unreachable!()
+  }
 }
 
 impl<E, M> Widget<E, M> for Placeholder {
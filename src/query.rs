@@ -0,0 +1,169 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::any::type_name;
+use std::any::Any;
+use std::error::Error;
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result;
+
+use crate::Id;
+use crate::MutCap;
+
+
+/// A trait for typed requests that can be sent to a widget via the
+/// `query` method available on [`MutCap`] trait objects as well as on
+/// [`Ui`][crate::Ui].
+///
+/// Each query type declares the type of the response that a widget
+/// answering it is expected to provide.
+pub trait Query: 'static {
+  /// The type of the response to the query.
+  type Response: 'static;
+}
+
+
+/// The internally used storage for a query and its response.
+struct Slot<Q>
+where
+  Q: Query,
+{
+  query: Q,
+  response: Option<Q::Response>,
+}
+
+
+/// A type-erased query, as passed to [`Handleable::answer`][crate::Handleable::answer].
+///
+/// A widget answering a query checks for the query type it knows about
+/// and provides a response for it.
+pub struct Request<'q> {
+  /// The actual `Slot` object.
+  slot: &'q mut dyn Any,
+  /// The name of the query type, for diagnostic purposes.
+  name: &'static str,
+}
+
+impl<'q> Request<'q> {
+  /// Create a new `Request` wrapping the given `Slot`.
+  fn new<Q>(slot: &'q mut Slot<Q>) -> Self
+  where
+    Q: Query,
+  {
+    Self {
+      slot,
+      name: type_name::<Q>(),
+    }
+  }
+
+  /// Check whether the request carries a query of type `Q`.
+  pub fn is<Q>(&self) -> bool
+  where
+    Q: Query,
+  {
+    self.slot.is::<Slot<Q>>()
+  }
+
+  /// Retrieve the query, if it is of type `Q`.
+  pub fn query<Q>(&self) -> Option<&Q>
+  where
+    Q: Query,
+  {
+    self.slot.downcast_ref::<Slot<Q>>().map(|slot| &slot.query)
+  }
+
+  /// Provide the response to a query of type `Q`.
+  ///
+  /// The method returns `false` if the request does not carry a query
+  /// of type `Q`, in which case the response is discarded.
+  pub fn respond<Q>(&mut self, response: Q::Response) -> bool
+  where
+    Q: Query,
+  {
+    if let Some(slot) = self.slot.downcast_mut::<Slot<Q>>() {
+      slot.response = Some(response);
+      true
+    } else {
+      false
+    }
+  }
+
+  /// Answer a query of type `Q` using the provided function.
+  ///
+  /// The function is only invoked if the request carries a query of
+  /// type `Q`. The method returns whether that was the case.
+  pub fn answer<Q, F>(&mut self, f: F) -> bool
+  where
+    Q: Query,
+    F: FnOnce(&Q) -> Q::Response,
+  {
+    if let Some(slot) = self.slot.downcast_mut::<Slot<Q>>() {
+      slot.response = Some(f(&slot.query));
+      true
+    } else {
+      false
+    }
+  }
+
+  /// Retrieve the name of the query type.
+  pub fn name(&self) -> &'static str {
+    self.name
+  }
+}
+
+impl Debug for Request<'_> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    f.debug_struct("Request").field("query", &self.name).finish()
+  }
+}
+
+
+/// An error as reported by a failed query.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum QueryError {
+  /// The widget did not answer the query.
+  Unanswered {
+    /// The [`Id`] of the widget the query was sent to.
+    widget: Id,
+    /// The name of the query type.
+    query: &'static str,
+  },
+}
+
+impl Display for QueryError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    match self {
+      Self::Unanswered { widget, query } => {
+        write!(f, "widget {} did not answer query {}", widget, query)
+      },
+    }
+  }
+}
+
+impl Error for QueryError {}
+
+
+/// Send a query of type `Q` to the given widget and wait for its
+/// response.
+pub(crate) async fn query<E, M, Q>(
+  cap: &mut dyn MutCap<E, M>,
+  widget: Id,
+  query: Q,
+) -> std::result::Result<Q::Response, QueryError>
+where
+  Q: Query,
+{
+  let mut slot = Slot {
+    query,
+    response: None,
+  };
+  let mut request = Request::new(&mut slot);
+  let () = cap.ask(widget, &mut request).await;
+
+  slot.response.ok_or(QueryError::Unanswered {
+    widget,
+    query: type_name::<Q>(),
+  })
+}
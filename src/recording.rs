@@ -0,0 +1,165 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::any::TypeId;
+use std::cell::RefCell;
+
+use crate::BBox;
+use crate::Cap;
+use crate::Id;
+use crate::Renderable;
+use crate::Renderer;
+
+
+/// A widget as recorded by a [`RecordingRenderer`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct RenderedWidget {
+  /// The [`TypeId`] of the widget.
+  pub type_id: TypeId,
+  /// The name of the widget's type.
+  pub type_name: &'static str,
+  /// The widget's [`Id`].
+  pub id: Id,
+  /// The bounding box the widget got rendered with.
+  pub bbox: BBox,
+}
+
+impl RenderedWidget {
+  /// Create a `RenderedWidget` from a [`Renderable`].
+  fn new(object: &dyn Renderable, bbox: BBox) -> Self {
+    Self {
+      type_id: Renderable::type_id(object),
+      type_name: object.type_name(),
+      id: object.id(),
+      bbox,
+    }
+  }
+}
+
+
+/// An entry in the display list of a [`RecordingRenderer`], recording a
+/// single invocation of a [`Renderer`] method.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DisplayItem {
+  /// [`Renderer::pre_render`] was invoked.
+  PreRender,
+  /// [`Renderer::render`] was invoked for a widget.
+  Render(RenderedWidget),
+  /// [`Renderer::render_done`] was invoked for a widget.
+  RenderDone(RenderedWidget),
+  /// [`Renderer::post_render`] was invoked.
+  PostRender,
+}
+
+
+/// A [`Renderer`] recording all calls made to it in a display list.
+///
+/// The renderer does not render anything itself and passes bounding
+/// boxes on to children unchanged. It is meant to be used in tests,
+/// which can inspect the display list or use one of the provided
+/// assertion helpers. The display list is reset at the start of each
+/// render pass, i.e., in [`Renderer::pre_render`].
+#[derive(Debug)]
+pub struct RecordingRenderer {
+  /// The renderable area to report.
+  area: BBox,
+  /// The display list of the current or last render pass.
+  items: RefCell<Vec<DisplayItem>>,
+}
+
+impl RecordingRenderer {
+  /// Create a new `RecordingRenderer` reporting the given renderable
+  /// area.
+  pub fn new(area: BBox) -> Self {
+    Self {
+      area,
+      items: RefCell::new(Vec::new()),
+    }
+  }
+
+  /// Retrieve the display list of the last render pass.
+  pub fn display_list(&self) -> Vec<DisplayItem> {
+    self.items.borrow().clone()
+  }
+
+  /// Retrieve the widgets rendered during the last render pass, in
+  /// rendering order.
+  pub fn rendered(&self) -> Vec<RenderedWidget> {
+    self
+      .items
+      .borrow()
+      .iter()
+      .filter_map(|item| match item {
+        DisplayItem::Render(widget) => Some(*widget),
+        _ => None,
+      })
+      .collect()
+  }
+
+  /// Retrieve the [`Id`]s of the widgets rendered during the last
+  /// render pass, in rendering order.
+  pub fn rendered_ids(&self) -> Vec<Id> {
+    self.rendered().into_iter().map(|widget| widget.id).collect()
+  }
+
+  /// Retrieve the bounding box the given widget was rendered with
+  /// during the last render pass, if it got rendered.
+  pub fn bbox(&self, widget: Id) -> Option<BBox> {
+    self
+      .rendered()
+      .into_iter()
+      .find(|rendered| rendered.id == widget)
+      .map(|rendered| rendered.bbox)
+  }
+
+  /// Assert that exactly the given widgets were rendered during the
+  /// last render pass, in the given order.
+  #[track_caller]
+  pub fn assert_render_order(&self, expected: &[Id]) {
+    assert_eq!(self.rendered_ids(), expected, "unexpected render order");
+  }
+
+  /// Assert that the given widget was rendered during the last render
+  /// pass, with the given bounding box.
+  #[track_caller]
+  pub fn assert_bbox(&self, widget: Id, expected: BBox) {
+    match self.bbox(widget) {
+      Some(bbox) => assert_eq!(bbox, expected, "unexpected bounding box for widget {}", widget),
+      None => panic!("widget {} was not rendered", widget),
+    }
+  }
+
+  /// Assert that the given widget was not rendered during the last
+  /// render pass.
+  #[track_caller]
+  pub fn assert_not_rendered(&self, widget: Id) {
+    assert!(self.bbox(widget).is_none(), "widget {} was rendered", widget);
+  }
+}
+
+impl Renderer for RecordingRenderer {
+  fn renderable_area(&self) -> BBox {
+    self.area
+  }
+
+  fn pre_render(&self) {
+    let mut items = self.items.borrow_mut();
+    let () = items.clear();
+    let () = items.push(DisplayItem::PreRender);
+  }
+
+  fn render(&self, object: &dyn Renderable, _cap: &dyn Cap, bbox: BBox) -> BBox {
+    let widget = RenderedWidget::new(object, bbox);
+    let () = self.items.borrow_mut().push(DisplayItem::Render(widget));
+    bbox
+  }
+
+  fn render_done(&self, object: &dyn Renderable, _cap: &dyn Cap, bbox: BBox) {
+    let widget = RenderedWidget::new(object, bbox);
+    let () = self.items.borrow_mut().push(DisplayItem::RenderDone(widget));
+  }
+
+  fn post_render(&self) {
+    let () = self.items.borrow_mut().push(DisplayItem::PostRender);
+  }
+}
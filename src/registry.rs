@@ -0,0 +1,105 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result;
+
+use crate::BBox;
+use crate::Cap;
+use crate::Renderable;
+
+
+/// The type of a function rendering a [`Renderable`].
+type RenderFn = dyn Fn(&dyn Renderable, &dyn Cap, BBox) -> BBox;
+
+
+/// The fallback used by a default constructed [`RendererRegistry`].
+fn unknown(object: &dyn Renderable, _cap: &dyn Cap, _bbox: BBox) -> BBox {
+  panic!("Renderable {:?} is unknown to the renderer", object)
+}
+
+
+/// A registry of render functions, keyed by the type of the object to
+/// render.
+///
+/// A registry is meant to be used by a [`Renderer`][crate::Renderer]
+/// implementation to dispatch rendering of an object to a function
+/// specific to the object's type, without having to try downcasting it
+/// to all the types it knows about. Lookup happens in constant time.
+///
+/// Objects of a type for which no function is registered are passed to
+/// the fallback, which by default panics.
+pub struct RendererRegistry {
+  /// The registered render functions.
+  handlers: HashMap<TypeId, Box<RenderFn>>,
+  /// The function used for rendering objects of unknown type.
+  fallback: Box<RenderFn>,
+}
+
+impl RendererRegistry {
+  /// Create a new, empty `RendererRegistry`.
+  pub fn new() -> Self {
+    Self {
+      handlers: HashMap::new(),
+      fallback: Box::new(unknown),
+    }
+  }
+
+  /// Register the function for rendering objects of type `W`.
+  ///
+  /// A function previously registered for the same type is replaced.
+  pub fn on<W, F>(mut self, render_fn: F) -> Self
+  where
+    W: Renderable,
+    F: Fn(&W, &dyn Cap, BBox) -> BBox + 'static,
+  {
+    let render_fn = move |object: &dyn Renderable, cap: &dyn Cap, bbox: BBox| {
+      // SANITY: We only ever invoke the function for objects of type
+      //         `W`.
+      let object = object.downcast_ref::<W>().unwrap();
+      render_fn(object, cap, bbox)
+    };
+    let _ = self.handlers.insert(TypeId::of::<W>(), Box::new(render_fn));
+    self
+  }
+
+  /// Set the function used for rendering objects for which no function
+  /// is registered.
+  pub fn fallback<F>(mut self, render_fn: F) -> Self
+  where
+    F: Fn(&dyn Renderable, &dyn Cap, BBox) -> BBox + 'static,
+  {
+    self.fallback = Box::new(render_fn);
+    self
+  }
+
+  /// Check whether a function is registered for the given type.
+  pub fn is_registered(&self, type_id: TypeId) -> bool {
+    self.handlers.contains_key(&type_id)
+  }
+
+  /// Render an object using the function registered for its type.
+  pub fn render(&self, object: &dyn Renderable, cap: &dyn Cap, bbox: BBox) -> BBox {
+    match self.handlers.get(&Renderable::type_id(object)) {
+      Some(render_fn) => render_fn(object, cap, bbox),
+      None => (self.fallback)(object, cap, bbox),
+    }
+  }
+}
+
+impl Default for RendererRegistry {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Debug for RendererRegistry {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    f.debug_struct("RendererRegistry")
+      .field("handlers", &self.handlers.len())
+      .finish()
+  }
+}
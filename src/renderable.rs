@@ -1,19 +1,30 @@
 // Copyright (C) 2018-2024 Daniel Mueller (deso@posteo.net)
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::any::type_name;
 use std::any::TypeId;
 use std::fmt::Debug;
 
 use crate::BBox;
 use crate::Cap;
+use crate::Object;
 use crate::Renderer;
+use crate::Size;
 
 
 /// A trait representing a renderable object.
-pub trait Renderable: 'static + Debug {
+///
+/// Renderable objects are [`Object`]s, allowing a [`Renderer`] to
+/// determine the [`Id`][crate::Id] of the widget being rendered.
+pub trait Renderable: 'static + Object + Debug {
   /// Get the [`TypeId`] of `self`.
   fn type_id(&self) -> TypeId;
 
+  /// Get the name of the type of `self`, for diagnostic purposes.
+  fn type_name(&self) -> &'static str {
+    type_name::<Self>()
+  }
+
   /// Render the renderable object.
   ///
   /// This method should just forward the call to the given
@@ -33,6 +44,23 @@ pub trait Renderable: 'static + Debug {
   /// adjustments.
   #[allow(unused_variables)]
   fn render_done(&self, cap: &dyn Cap, renderer: &dyn Renderer, bbox: BBox) {}
+
+  /// Measure the renderable object, determining the size it requires
+  /// to display its content.
+  ///
+  /// This method is invoked for all displayed widgets before any of
+  /// them is rendered. Children are measured before their parent
+  /// ("post-order"), meaning that a container can inquire the sizes of
+  /// its children using [`Cap::measured_size`] when calculating its
+  /// own. Just as [`render`][Self::render], this method should
+  /// typically just forward the call to the given [`Renderer`].
+  /// `available` is the space the object's parent can give to it, as
+  /// determined by the parent's [`Layout`][crate::Layout], if any. By
+  /// default, the object makes use of all the `available` space.
+  #[allow(unused_variables)]
+  fn measure(&self, cap: &dyn Cap, renderer: &dyn Renderer, available: Size) -> Size {
+    available
+  }
 }
 
 impl dyn Renderable {
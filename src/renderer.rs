@@ -1,49 +1,70 @@
 // Copyright (C) 2018-2024 Daniel Mueller (deso@posteo.net)
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use crate::BBox;
 use crate::Cap;
+use crate::Coord;
 use crate::Renderable;
-
-
-/// A bounding box representing the area that a widget may occupy. A
-/// bounding box always describes a rectangular area. The origin [x=0,
-/// y=0] is typically assumed to reside in the upper left corner of the
-/// screen, but it is really up to the individual [`Renderer`] to make
-/// do with whatever is provided.
-#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
-pub struct BBox {
-  /// The x-coordinate of the bounding box.
-  pub x: u16,
-  /// The y-coordinate of the bounding box.
-  pub y: u16,
-  /// The width of the bounding box.
-  pub w: u16,
-  /// The height of the bounding box.
-  pub h: u16,
-}
+use crate::Size;
 
 
 /// An abstraction for objects used for rendering widgets.
-pub trait Renderer {
+///
+/// A `Renderer` works with coordinates of type `T`, which defaults to
+/// `u16`. That is the coordinate space the [`Ui`][crate::Ui] lays out
+/// widgets in. A `Renderer` using a different coordinate type, e.g.,
+/// to address pixel displays wider than `u16::MAX` units or to scale by
+/// a fractional factor, can be driven by wrapping it in a
+/// [`ScaledRenderer`][crate::ScaledRenderer].
+pub trait Renderer<T = u16>
+where
+  T: Coord,
+{
   /// Retrieve the bounding box of the renderable area (typically the
   /// screen).
   /// Note that the units to be used are not specified. That is, the
   /// result could be in pixels, characters (in case of a terminal), or
   /// just arbitrary numbers (if virtual coordinates are being used), as
   /// long as this `Renderer` knows how to interpret them.
-  fn renderable_area(&self) -> BBox;
+  fn renderable_area(&self) -> BBox<T>;
+
+  /// Measure an object, determining the size it requires to display
+  /// its content.
+  ///
+  /// The `available` size is an upper bound for the result. By
+  /// default, an object is assumed to make use of all the available
+  /// space.
+  #[allow(unused_variables)]
+  fn measure(&self, object: &dyn Renderable, cap: &dyn Cap, available: Size<T>) -> Size<T> {
+    available
+  }
 
   /// Perform some pre-render step.
+  ///
+  /// This method is invoked before a pass rendering all widgets, as
+  /// performed by [`Ui::render`][crate::Ui::render].
   fn pre_render(&self) {}
 
+  /// Perform some pre-render step before a partial pass, rendering
+  /// only invalidated widgets, as performed by
+  /// [`Ui::render_dirty`][crate::Ui::render_dirty].
+  ///
+  /// Whatever got rendered for the remaining widgets during earlier
+  /// passes is expected to be retained. By default, this method
+  /// forwards to [`Renderer::pre_render`].
+  fn pre_render_partial(&self) {
+    self.pre_render()
+  }
+
   /// Render an object.
   ///
   /// Objects are represented as [`Renderable`] and need to be cast into
   /// the actual widget type to render by the `Renderer` itself, should
-  /// that be necessary. A simplified implementation could look as
-  /// follows:
+  /// that be necessary. That can happen by means of the `downcast_ref`
+  /// method available on `dyn Renderable` or, more conveniently, by
+  /// dispatching through a [`RendererRegistry`][crate::RendererRegistry]:
   /// ```rust
-  /// # use gui::{BBox, Cap, Id, Renderer, Renderable};
+  /// # use gui::{BBox, Cap, Id, Renderer, RendererRegistry, Renderable};
   /// # use gui::derive::{Handleable, Widget};
   /// # #[derive(Debug, Widget, Handleable)]
   /// # #[gui(Event = ())]
@@ -55,40 +76,44 @@ pub trait Renderer {
   /// # struct ConcreteWidget2 {
   /// #   id: Id,
   /// # }
-  /// # #[derive(Debug)]
-  /// # struct TestRenderer {}
-  /// # impl TestRenderer {
-  /// #   fn render_concrete_widget1(&self, widget: &ConcreteWidget1, bbox: BBox) -> BBox {
-  /// #     bbox
-  /// #   }
-  /// #   fn render_concrete_widget2(&self, widget: &ConcreteWidget1, bbox: BBox) -> BBox {
-  /// #     bbox
-  /// #   }
+  /// # fn render_concrete_widget1(widget: &ConcreteWidget1, bbox: BBox) -> BBox {
+  /// #   bbox
   /// # }
-  /// # impl Renderer for TestRenderer {
+  /// # fn render_concrete_widget2(widget: &ConcreteWidget2, bbox: BBox) -> BBox {
+  /// #   bbox
+  /// # }
+  /// #[derive(Debug)]
+  /// struct TestRenderer {
+  ///   registry: RendererRegistry,
+  /// }
+  ///
+  /// impl TestRenderer {
+  ///   fn new() -> Self {
+  ///     let registry = RendererRegistry::new()
+  ///       .on::<ConcreteWidget1, _>(|widget, _cap, bbox| render_concrete_widget1(widget, bbox))
+  ///       .on::<ConcreteWidget2, _>(|widget, _cap, bbox| render_concrete_widget2(widget, bbox));
+  ///     Self { registry }
+  ///   }
+  /// }
+  ///
+  /// impl Renderer for TestRenderer {
   /// #   fn renderable_area(&self) -> BBox {
   /// #     Default::default()
   /// #   }
-  /// fn render(&self, widget: &dyn Renderable, cap: &dyn Cap, bbox: BBox) -> BBox {
-  ///   if let Some(widget1) = widget.downcast_ref::<ConcreteWidget1>() {
-  ///     self.render_concrete_widget1(widget1, bbox)
-  ///   } else if let Some(widget2) = widget.downcast_ref::<ConcreteWidget1>() {
-  ///     self.render_concrete_widget2(widget2, bbox)
-  ///   } else {
-  ///     panic!("Renderable {:?} is unknown to the renderer", widget)
+  ///   fn render(&self, widget: &dyn Renderable, cap: &dyn Cap, bbox: BBox) -> BBox {
+  ///     self.registry.render(widget, cap, bbox)
   ///   }
   /// }
+  /// # fn main() {
+  /// #   let _ = TestRenderer::new();
   /// # }
-  /// # fn main() {}
   /// ```
-  // TODO: Ideally we would like to have a double dispatch mechanism for
-  //       determining the object to render.
-  fn render(&self, object: &dyn Renderable, cap: &dyn Cap, bbox: BBox) -> BBox;
+  fn render(&self, object: &dyn Renderable, cap: &dyn Cap, bbox: BBox<T>) -> BBox<T>;
 
   /// A method invoked once rendering of a widget and all its children
   /// concluded.
   #[allow(unused_variables)]
-  fn render_done(&self, object: &dyn Renderable, cap: &dyn Cap, bbox: BBox) {}
+  fn render_done(&self, object: &dyn Renderable, cap: &dyn Cap, bbox: BBox<T>) {}
 
   /// Perform some post-render step.
   fn post_render(&self) {}
@@ -0,0 +1,158 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result;
+
+use crate::BBox;
+use crate::Cap;
+use crate::Coord;
+use crate::Renderable;
+use crate::Renderer;
+use crate::Size;
+
+
+/// Clamp a logical coordinate, as calculated in floating point, to the
+/// range of `u16`.
+#[inline]
+fn logical(value: f64) -> u16 {
+  u16::from_f64(value)
+}
+
+
+/// A [`Renderer`] adapting the `u16` coordinate space the
+/// [`Ui`][crate::Ui] lays out widgets in to the device coordinates of
+/// another [`Renderer`].
+///
+/// Logical coordinates are multiplied by a scale factor to yield
+/// device coordinates of type `T`. The factor may be fractional, for
+/// example, to account for the DPI of a display, and the device
+/// coordinate type can be wider than `u16`: with a factor of 2.0, a
+/// display 100000 pixels wide is covered by 50000 logical units.
+///
+/// The edges of bounding boxes are scaled individually, so that
+/// adjacent bounding boxes stay adjacent. Bounding boxes and sizes
+/// reported back by the wrapped renderer are converted to the logical
+/// units they fully cover, for areas, or that are required to contain
+/// them, for measured sizes.
+pub struct ScaledRenderer<'r, T>
+where
+  T: Coord,
+{
+  /// The renderer working with device coordinates.
+  renderer: &'r dyn Renderer<T>,
+  /// The factor by which to multiply logical coordinates.
+  scale: f64,
+}
+
+impl<'r, T> ScaledRenderer<'r, T>
+where
+  T: Coord,
+{
+  /// Create a new `ScaledRenderer` wrapping the given one.
+  ///
+  /// # Panics
+  ///
+  /// This method panics if `scale` is not a finite number greater than
+  /// zero.
+  pub fn new(renderer: &'r dyn Renderer<T>, scale: f64) -> Self {
+    assert!(
+      scale.is_finite() && scale > 0.0,
+      "Scale factor must be finite and greater than zero"
+    );
+    Self { renderer, scale }
+  }
+
+  /// Retrieve the scale factor in use.
+  pub fn scale(&self) -> f64 {
+    self.scale
+  }
+
+  /// Convert a bounding box in logical coordinates into device ones.
+  fn to_device(&self, bbox: BBox) -> BBox<T> {
+    let x = T::from_f64(f64::from(bbox.x) * self.scale);
+    let y = T::from_f64(f64::from(bbox.y) * self.scale);
+    let x2 = T::from_f64((f64::from(bbox.x) + f64::from(bbox.w)) * self.scale);
+    let y2 = T::from_f64((f64::from(bbox.y) + f64::from(bbox.h)) * self.scale);
+
+    BBox {
+      x,
+      y,
+      w: x2.saturating_sub(x),
+      h: y2.saturating_sub(y),
+    }
+  }
+
+  /// Convert a bounding box in device coordinates into the largest one
+  /// in logical coordinates it fully covers.
+  fn to_logical(&self, bbox: BBox<T>) -> BBox {
+    let x = logical((bbox.x.to_f64() / self.scale).ceil());
+    let y = logical((bbox.y.to_f64() / self.scale).ceil());
+    let x2 = logical(((bbox.x.to_f64() + bbox.w.to_f64()) / self.scale).floor());
+    let y2 = logical(((bbox.y.to_f64() + bbox.h.to_f64()) / self.scale).floor());
+
+    BBox {
+      x,
+      y,
+      w: x2.saturating_sub(x),
+      h: y2.saturating_sub(y),
+    }
+  }
+}
+
+impl<T> Renderer for ScaledRenderer<'_, T>
+where
+  T: Coord,
+{
+  fn renderable_area(&self) -> BBox {
+    self.to_logical(self.renderer.renderable_area())
+  }
+
+  fn measure(&self, object: &dyn Renderable, cap: &dyn Cap, available: Size) -> Size {
+    let device = Size {
+      w: T::from_f64(f64::from(available.w) * self.scale),
+      h: T::from_f64(f64::from(available.h) * self.scale),
+    };
+    let size = self.renderer.measure(object, cap, device);
+
+    Size {
+      w: logical((size.w.to_f64() / self.scale).ceil()).min(available.w),
+      h: logical((size.h.to_f64() / self.scale).ceil()).min(available.h),
+    }
+  }
+
+  fn pre_render(&self) {
+    self.renderer.pre_render()
+  }
+
+  fn pre_render_partial(&self) {
+    self.renderer.pre_render_partial()
+  }
+
+  fn render(&self, object: &dyn Renderable, cap: &dyn Cap, bbox: BBox) -> BBox {
+    let inner = self.renderer.render(object, cap, self.to_device(bbox));
+    self.to_logical(inner)
+  }
+
+  fn render_done(&self, object: &dyn Renderable, cap: &dyn Cap, bbox: BBox) {
+    self
+      .renderer
+      .render_done(object, cap, self.to_device(bbox))
+  }
+
+  fn post_render(&self) {
+    self.renderer.post_render()
+  }
+}
+
+impl<T> Debug for ScaledRenderer<'_, T>
+where
+  T: Coord,
+{
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    f.debug_struct("ScaledRenderer")
+      .field("scale", &self.scale)
+      .finish()
+  }
+}
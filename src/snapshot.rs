@@ -0,0 +1,346 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::any::Any;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::Cap;
+use crate::Id;
+use crate::MutCap;
+use crate::Ui;
+use crate::Viewport;
+
+
+/// The type of a function capturing a widget's data.
+type SaveFn<V> = dyn Fn(&dyn Any) -> V;
+
+/// The type of a function restoring a widget's data.
+type RestoreFn<V> = dyn Fn(&mut dyn Any, &V);
+
+/// The functions for capturing and restoring data of a certain type.
+type Handlers<V> = (Box<SaveFn<V>>, Box<RestoreFn<V>>);
+
+
+/// Retrieve the children of the given widget in the order in which they
+/// were added.
+///
+/// The order of children as reported by [`Cap::children`] changes as
+/// widgets get focused and so is unsuitable for identifying widgets
+/// across `Ui` instances.
+fn children<E, M>(ui: &Ui<E, M>, widget: Id) -> Vec<Id> {
+  let mut children = ui.children(widget).copied().collect::<Vec<_>>();
+  let () = children.sort_by_key(Id::index);
+  children
+}
+
+
+/// A registry of functions converting widget data to and from values of
+/// type `V`, keyed by the type of the data.
+///
+/// Only data of types registered here are captured in a [`Snapshot`].
+/// `V` is the serializable representation of the data and is chosen by
+/// the user, e.g., `serde_json::Value`.
+pub struct SnapshotRegistry<V> {
+  /// The registered functions.
+  handlers: HashMap<TypeId, Handlers<V>>,
+}
+
+impl<V> SnapshotRegistry<V> {
+  /// Create a new, empty `SnapshotRegistry`.
+  pub fn new() -> Self {
+    Self {
+      handlers: HashMap::new(),
+    }
+  }
+
+  /// Register the functions for capturing and restoring data of type
+  /// `D`.
+  ///
+  /// Functions previously registered for the same type are replaced.
+  pub fn on<D, S, R>(mut self, save: S, restore: R) -> Self
+  where
+    D: 'static,
+    S: Fn(&D) -> V + 'static,
+    R: Fn(&mut D, &V) + 'static,
+  {
+    // SANITY: We only ever invoke the functions for data of type `D`.
+    let save = move |data: &dyn Any| save(data.downcast_ref::<D>().unwrap());
+    let restore = move |data: &mut dyn Any, value: &V| {
+      restore(data.downcast_mut::<D>().unwrap(), value)
+    };
+    let _ = self
+      .handlers
+      .insert(TypeId::of::<D>(), (Box::new(save), Box::new(restore)));
+    self
+  }
+
+  /// Check whether functions are registered for the given type.
+  pub fn is_registered(&self, type_id: TypeId) -> bool {
+    self.handlers.contains_key(&type_id)
+  }
+
+  /// Capture the given data, if their type is registered.
+  fn save(&self, data: &dyn Any) -> Option<V> {
+    self
+      .handlers
+      .get(&Any::type_id(data))
+      .map(|(save, _)| save(data))
+  }
+
+  /// Restore the given data, if their type is registered.
+  fn restore(&self, data: &mut dyn Any, value: &V) {
+    if let Some((_, restore)) = self.handlers.get(&Any::type_id(data)) {
+      restore(data, value)
+    }
+  }
+}
+
+impl<V> Default for SnapshotRegistry<V> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<V> Debug for SnapshotRegistry<V> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    f.debug_struct("SnapshotRegistry")
+      .field("handlers", &self.handlers.len())
+      .finish()
+  }
+}
+
+
+/// The captured state of a single widget.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct WidgetSnapshot<V> {
+  /// The index of the snapshot of the widget's parent in
+  /// [`Snapshot::widgets`], or `None` for the root widget.
+  pub parent: Option<usize>,
+  /// The name of the widget's type, as reported by
+  /// [`Renderable::type_name`][crate::Renderable::type_name].
+  pub type_name: String,
+  /// Whether the widget is visible.
+  pub visible: bool,
+  /// The widget's viewport, if any.
+  pub viewport: Option<Viewport>,
+  /// The widget's data, if their type is known to the
+  /// [`SnapshotRegistry`] used for capturing.
+  pub data: Option<V>,
+}
+
+
+/// The captured state of a [`Ui`], comprising its widget tree along
+/// with visibility, viewports, focus, and widget data.
+///
+/// The widget tree is stored as a flat list, in which each widget
+/// refers to its parent by index. Parents precede their children and
+/// the children of a widget appear in the order in which they were
+/// added. Widgets are identified by their path, i.e., the indices of
+/// the children to descend into, starting at the root. A snapshot can
+/// be restored into any `Ui` whose widget tree contains widgets of the
+/// same types at the same paths, such as a freshly built instance of
+/// the one it was captured from. The stacking order of widgets is not
+/// captured, except as far as it follows from the focus.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Snapshot<V> {
+  /// The snapshots of all widgets, starting with the root.
+  pub widgets: Vec<WidgetSnapshot<V>>,
+  /// The index of the snapshot of the focused widget in `widgets`, if
+  /// any.
+  pub focused: Option<usize>,
+}
+
+impl<V> Snapshot<V> {
+  /// Capture the state of the given `Ui`.
+  ///
+  /// Data are only captured for widgets whose data type is known to
+  /// the provided registry.
+  pub fn capture<E, M>(ui: &Ui<E, M>, registry: &SnapshotRegistry<V>) -> Self {
+    let mut widgets = Vec::new();
+    let mut focused = None;
+    // We walk the tree depth-first using an explicit stack, to not be
+    // limited by the depth of the call stack.
+    let mut stack = vec![(ui.root_id(), None)];
+
+    while let Some((widget, parent)) = stack.pop() {
+      let index = widgets.len();
+      let () = widgets.push(WidgetSnapshot {
+        parent,
+        type_name: ui.widget_type_name(widget).to_string(),
+        visible: ui.is_visible(widget),
+        viewport: ui.viewport(widget),
+        data: registry.save(ui.data(widget)),
+      });
+
+      if ui.is_focused(widget) {
+        focused = Some(index);
+      }
+
+      // Children are pushed in reverse, so that they are popped in the
+      // order in which they were added.
+      let children = children(ui, widget);
+      let () = stack.extend(children.into_iter().rev().map(|child| (child, Some(index))));
+    }
+
+    Self { widgets, focused }
+  }
+
+  /// Restore the captured state into the given `Ui`.
+  ///
+  /// Widgets present in the `Ui` but not in the snapshot are left
+  /// untouched. The snapshot is checked against the `Ui` before any
+  /// change is made, meaning that on error the `Ui` is unmodified.
+  pub fn restore<E, M>(
+    &self,
+    ui: &mut Ui<E, M>,
+    registry: &SnapshotRegistry<V>,
+  ) -> std::result::Result<(), RestoreError> {
+    // For each widget snapshot, the matched widget along with its
+    // position among its siblings and its children in the `Ui`.
+    let mut matched = Vec::<(Id, usize, Vec<Id>)>::with_capacity(self.widgets.len());
+    // The number of children of each widget snapshot seen so far.
+    let mut counts = vec![0; self.widgets.len()];
+
+    // Retrieve the path of the widget snapshot with the given index.
+    let path = |matched: &[(Id, usize, Vec<Id>)], mut index: usize| {
+      let mut path = Vec::new();
+      while let Some(parent) = self.widgets[index].parent {
+        let () = path.push(matched[index].1);
+        index = parent;
+      }
+      let () = path.reverse();
+      path
+    };
+
+    for (index, snapshot) in self.widgets.iter().enumerate() {
+      let (widget, position) = match snapshot.parent {
+        None if index == 0 => (ui.root_id(), 0),
+        Some(parent) if parent < index => {
+          let position = counts[parent];
+          counts[parent] += 1;
+
+          match matched[parent].2.get(position) {
+            Some(widget) => (*widget, position),
+            None => {
+              let mut path = path(&matched, parent);
+              let () = path.push(position);
+              return Err(RestoreError::UnknownWidget { path })
+            },
+          }
+        },
+        _ => return Err(RestoreError::Malformed { index }),
+      };
+      let () = matched.push((widget, position, children(ui, widget)));
+
+      let type_name = ui.widget_type_name(widget);
+      if type_name != snapshot.type_name {
+        return Err(RestoreError::TypeMismatch {
+          path: path(&matched, index),
+          expected: snapshot.type_name.clone(),
+          actual: type_name.to_string(),
+        })
+      }
+
+      if snapshot.data.is_some() && !registry.is_registered(Any::type_id(ui.data(widget))) {
+        return Err(RestoreError::UnknownData {
+          path: path(&matched, index),
+        })
+      }
+    }
+
+    let focused = match self.focused {
+      Some(index) => match matched.get(index) {
+        Some((widget, _, _)) => Some(*widget),
+        None => return Err(RestoreError::Malformed { index }),
+      },
+      None => None,
+    };
+
+    for ((widget, _, _), snapshot) in matched.into_iter().zip(&self.widgets) {
+      if snapshot.visible != ui.is_visible(widget) {
+        if snapshot.visible {
+          ui.show(widget)
+        } else {
+          ui.hide(widget)
+        }
+      }
+
+      let _ = ui.set_viewport(widget, snapshot.viewport);
+
+      if let Some(value) = &snapshot.data {
+        let () = registry.restore(ui.data_mut(widget), value);
+      }
+    }
+
+    if let Some(focused) = focused {
+      ui.focus(focused)
+    }
+    Ok(())
+  }
+}
+
+
+/// An error reported when restoring a [`Snapshot`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RestoreError {
+  /// The snapshot references a widget that does not exist.
+  UnknownWidget {
+    /// The path of the widget.
+    path: Vec<usize>,
+  },
+  /// The type of a widget differs from the captured one.
+  TypeMismatch {
+    /// The path of the widget.
+    path: Vec<usize>,
+    /// The name of the captured type.
+    expected: String,
+    /// The name of the widget's actual type.
+    actual: String,
+  },
+  /// The snapshot contains data for a widget whose data type is not
+  /// known to the [`SnapshotRegistry`] used for restoring.
+  UnknownData {
+    /// The path of the widget.
+    path: Vec<usize>,
+  },
+  /// The snapshot is malformed, e.g., because a widget snapshot does
+  /// not refer to a preceding parent.
+  Malformed {
+    /// The index of the offending widget snapshot.
+    index: usize,
+  },
+}
+
+impl Display for RestoreError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    match self {
+      Self::UnknownWidget { path } => write!(f, "widget at path {:?} does not exist", path),
+      Self::TypeMismatch {
+        path,
+        expected,
+        actual,
+      } => write!(
+        f,
+        "widget at path {:?} is of type {} and not {}",
+        path, actual, expected
+      ),
+      Self::UnknownData { path } => write!(
+        f,
+        "data type of widget at path {:?} is not registered",
+        path
+      ),
+      Self::Malformed { index } => write!(f, "snapshot is malformed at widget {}", index),
+    }
+  }
+}
+
+impl Error for RestoreError {}
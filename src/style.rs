@@ -0,0 +1,213 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::any::Any;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use crate::CellStyle;
+use crate::Color;
+use crate::Emphasis;
+use crate::Insets;
+
+
+/// A trait for custom, typed style properties.
+///
+/// The implementing type acts as the key identifying the property,
+/// while the associated type determines the type of its value.
+pub trait StyleKey: 'static {
+  /// The type of the property's value.
+  type Value: Clone + Debug + 'static;
+}
+
+
+/// A type-erased value of a custom style property.
+trait CustomValue: Debug {
+  /// Retrieve the value as `Any`, for downcasting.
+  fn as_any(&self) -> &dyn Any;
+}
+
+impl<T> CustomValue for T
+where
+  T: Debug + 'static,
+{
+  fn as_any(&self) -> &dyn Any {
+    self
+  }
+}
+
+
+/// A set of style properties.
+///
+/// Properties that are not set are inherited from the parent widget,
+/// or from the [`Theme`] in case of the root widget. The effective
+/// style of a widget can be retrieved via
+/// [`Cap::style`][crate::Cap::style].
+#[derive(Clone, Debug, Default)]
+pub struct Style {
+  /// The foreground color.
+  pub fg: Option<Color>,
+  /// The background color.
+  pub bg: Option<Color>,
+  /// The emphasis to use for text.
+  pub emphasis: Option<Emphasis>,
+  /// The padding to apply around content.
+  pub padding: Option<Insets>,
+  /// Custom properties, keyed by the [`TypeId`] of their [`StyleKey`].
+  custom: HashMap<TypeId, Rc<dyn CustomValue>>,
+}
+
+impl Style {
+  /// Create a new `Style` without any properties set.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Set the foreground color.
+  pub fn fg(mut self, fg: Color) -> Self {
+    self.fg = Some(fg);
+    self
+  }
+
+  /// Set the background color.
+  pub fn bg(mut self, bg: Color) -> Self {
+    self.bg = Some(bg);
+    self
+  }
+
+  /// Set the emphasis to use for text.
+  pub fn emphasis(mut self, emphasis: Emphasis) -> Self {
+    self.emphasis = Some(emphasis);
+    self
+  }
+
+  /// Set the padding to apply around content.
+  pub fn padding(mut self, padding: Insets) -> Self {
+    self.padding = Some(padding);
+    self
+  }
+
+  /// Set the custom property identified by `K`.
+  pub fn with<K>(mut self, value: K::Value) -> Self
+  where
+    K: StyleKey,
+  {
+    let _ = self.set::<K>(value);
+    self
+  }
+
+  /// Set the custom property identified by `K`, returning the previous
+  /// value, if any.
+  pub fn set<K>(&mut self, value: K::Value) -> Option<K::Value>
+  where
+    K: StyleKey,
+  {
+    let previous = self.get::<K>().cloned();
+    let _ = self.custom.insert(TypeId::of::<K>(), Rc::new(value));
+    previous
+  }
+
+  /// Retrieve the custom property identified by `K`, if set.
+  pub fn get<K>(&self) -> Option<&K::Value>
+  where
+    K: StyleKey,
+  {
+    // Note that `as_ref` is required to dispatch to the contained value
+    // and not the `Rc` itself.
+    self
+      .custom
+      .get(&TypeId::of::<K>())
+      .and_then(|value| value.as_ref().as_any().downcast_ref::<K::Value>())
+  }
+
+  /// Remove the custom property identified by `K`, returning its value,
+  /// if it was set.
+  pub fn remove<K>(&mut self) -> Option<K::Value>
+  where
+    K: StyleKey,
+  {
+    let previous = self.get::<K>().cloned();
+    let _ = self.custom.remove(&TypeId::of::<K>());
+    previous
+  }
+
+  /// Create a new `Style` containing the properties of `self` and, for
+  /// those not set, the ones of `parent`.
+  pub fn inherit(&self, parent: &Style) -> Style {
+    let mut custom = parent.custom.clone();
+    let () = custom.extend(
+      self
+        .custom
+        .iter()
+        .map(|(key, value)| (*key, Rc::clone(value))),
+    );
+
+    Style {
+      fg: self.fg.or(parent.fg),
+      bg: self.bg.or(parent.bg),
+      emphasis: self.emphasis.or(parent.emphasis),
+      padding: self.padding.or(parent.padding),
+      custom,
+    }
+  }
+
+  /// Convert the style into a [`CellStyle`], using defaults for unset
+  /// properties.
+  pub fn cell_style(&self) -> CellStyle {
+    CellStyle {
+      fg: self.fg.unwrap_or_default(),
+      bg: self.bg.unwrap_or_default(),
+      emphasis: self.emphasis.unwrap_or_default(),
+    }
+  }
+}
+
+
+/// A theme providing the styles that widgets inherit from.
+///
+/// A theme consists of a base [`Style`] that the root widget inherits
+/// from and, optionally, styles for individual widget types. The
+/// latter apply to all widgets of the respective type, but are
+/// overridden by a widget's own style. The theme of a
+/// [`Ui`][crate::Ui] can be swapped at runtime via
+/// [`MutCap::set_theme`][crate::MutCap::set_theme].
+#[derive(Clone, Debug, Default)]
+pub struct Theme {
+  /// The style all others inherit from.
+  base: Style,
+  /// Styles for widget types, keyed by their [`TypeId`].
+  widgets: HashMap<TypeId, Style>,
+}
+
+impl Theme {
+  /// Create a new `Theme` with the given base style.
+  pub fn new(base: Style) -> Self {
+    Self {
+      base,
+      widgets: HashMap::new(),
+    }
+  }
+
+  /// Set the style to use for widgets of type `W`.
+  pub fn with_widget_style<W>(mut self, style: Style) -> Self
+  where
+    W: 'static,
+  {
+    let _ = self.widgets.insert(TypeId::of::<W>(), style);
+    self
+  }
+
+  /// Retrieve the base style.
+  #[inline]
+  pub fn base(&self) -> &Style {
+    &self.base
+  }
+
+  /// Retrieve the style for widgets of the type identified by the given
+  /// [`TypeId`], if any.
+  pub fn widget_style(&self, widget_type: TypeId) -> Option<&Style> {
+    self.widgets.get(&widget_type)
+  }
+}
@@ -0,0 +1,290 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::fmt::Write as _;
+
+use crate::BBox;
+use crate::Cap;
+use crate::Id;
+use crate::Renderable;
+use crate::Renderer;
+use crate::Size;
+
+
+/// The colors used for filling widgets, by nesting depth.
+const FILLS: [&str; 6] = [
+  "#4e79a7", "#f28e2b", "#59a14f", "#b07aa1", "#edc948", "#76b7b2",
+];
+
+
+/// Escape a string for inclusion in XML text or attribute values.
+fn escape(s: &str) -> String {
+  let mut escaped = String::with_capacity(s.len());
+  for c in s.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      _ => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+/// Strip the module path from a type name, leaving generic arguments
+/// as they are.
+fn short_name(type_name: &str) -> &str {
+  let end = type_name.find('<').unwrap_or(type_name.len());
+  match type_name[..end].rfind("::") {
+    Some(idx) => &type_name[idx + 2..],
+    None => type_name,
+  }
+}
+
+
+/// A widget recorded by an [`SvgRenderer`].
+#[derive(Clone, Debug)]
+struct Entry {
+  /// The widget's [`Id`].
+  id: Id,
+  /// The name of the widget's type.
+  type_name: &'static str,
+  /// The bounding box the widget got rendered with.
+  bbox: BBox,
+  /// The bounding box the widget made available to its children.
+  inner: BBox,
+  /// The nesting depth of the widget.
+  depth: usize,
+  /// Whether the widget had the input focus.
+  focused: bool,
+}
+
+
+/// A [`Renderer`] recording the bounding box each widget is rendered
+/// with, for the purpose of exporting them as an SVG image.
+///
+/// The renderer wraps another [`Renderer`], which it forwards all calls
+/// to. That way the bounding boxes recorded are exactly those resulting
+/// from rendering with the wrapped renderer. If no real rendering is
+/// desired, a [`CellBuffer`][crate::CellBuffer] without any registered
+/// drawing functions can be used.
+///
+/// In the exported image each widget is represented by a labelled
+/// rectangle. Rectangles are drawn in rendering order, meaning that
+/// widgets higher in the z-order are drawn on top of lower ones. The
+/// focused widget is highlighted. As the output is deterministic, it
+/// lends itself to being used in snapshot tests.
+///
+/// A partial render pass, as performed by
+/// [`Ui::render_dirty`][crate::Ui::render_dirty], updates the recorded
+/// widgets that got rendered anew in place and keeps all others, so
+/// that the export always covers the entire `Ui`.
+#[derive(Debug)]
+pub struct SvgRenderer<R> {
+  /// The renderer to forward calls to.
+  inner: R,
+  /// The widgets rendered during the last render pass.
+  entries: RefCell<Vec<Entry>>,
+  /// The nesting depth of the widget currently being rendered.
+  depth: Cell<usize>,
+  /// Whether the current render pass is a partial one.
+  partial: Cell<bool>,
+  /// The renderable area at the time of the last render pass.
+  area: Cell<BBox>,
+}
+
+impl<R> SvgRenderer<R>
+where
+  R: Renderer,
+{
+  /// Create a new `SvgRenderer` wrapping the given renderer.
+  pub fn new(inner: R) -> Self {
+    Self {
+      inner,
+      entries: RefCell::new(Vec::new()),
+      depth: Cell::new(0),
+      partial: Cell::new(false),
+      area: Cell::new(BBox::default()),
+    }
+  }
+
+  /// Retrieve the wrapped renderer.
+  pub fn inner(&self) -> &R {
+    &self.inner
+  }
+
+  /// Destroy the `SvgRenderer`, returning the wrapped renderer.
+  pub fn into_inner(self) -> R {
+    self.inner
+  }
+
+  /// Retrieve the [`Id`]s and bounding boxes of all widgets rendered
+  /// during the last full render pass and any partial ones since, in
+  /// rendering order.
+  pub fn bboxes(&self) -> Vec<(Id, BBox)> {
+    self
+      .entries
+      .borrow()
+      .iter()
+      .map(|entry| (entry.id, entry.bbox))
+      .collect()
+  }
+
+  /// Export the widgets rendered during the last render pass as an SVG
+  /// image.
+  pub fn to_svg(&self) -> String {
+    let area = self.area.get();
+    let mut svg = String::new();
+    let _ = writeln!(
+      svg,
+      concat!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}""#,
+        r#" width="{}" height="{}">"#,
+      ),
+      area.x,
+      area.y,
+      area.w,
+      area.h,
+      u32::from(area.w) * 10,
+      u32::from(area.h) * 10,
+    );
+    let _ = writeln!(svg, r#"<style>text {{ font: 0.8px monospace; }}</style>"#);
+
+    for (z, entry) in self.entries.borrow().iter().enumerate() {
+      let (stroke, stroke_width) = if entry.focused {
+        ("#e15759", "0.3")
+      } else {
+        ("#000000", "0.1")
+      };
+      let type_name = escape(entry.type_name);
+      let bbox = entry.bbox;
+      let inner = entry.inner;
+
+      let _ = writeln!(
+        svg,
+        r#"<g data-id="{}" data-z="{}"{}>"#,
+        entry.id,
+        z,
+        if entry.focused { r#" data-focused="true""# } else { "" },
+      );
+      let _ = writeln!(
+        svg,
+        "<title>{} {} bbox=({}, {}, {}, {}) inner=({}, {}, {}, {})</title>",
+        entry.id,
+        type_name,
+        bbox.x,
+        bbox.y,
+        bbox.w,
+        bbox.h,
+        inner.x,
+        inner.y,
+        inner.w,
+        inner.h,
+      );
+      let _ = writeln!(
+        svg,
+        concat!(
+          r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" fill-opacity="0.3""#,
+          r#" stroke="{}" stroke-width="{}"/>"#,
+        ),
+        bbox.x,
+        bbox.y,
+        bbox.w,
+        bbox.h,
+        FILLS[entry.depth % FILLS.len()],
+        stroke,
+        stroke_width,
+      );
+      let _ = writeln!(
+        svg,
+        r#"<text x="{}" y="{}">{} {}</text>"#,
+        bbox.x,
+        f32::from(bbox.y) + 0.8,
+        entry.id,
+        escape(short_name(entry.type_name)),
+      );
+      let _ = writeln!(svg, "</g>");
+    }
+    let _ = writeln!(svg, "</svg>");
+    svg
+  }
+
+  /// Export the widgets rendered during the last render pass as a
+  /// standalone HTML document containing an SVG image.
+  pub fn to_html(&self) -> String {
+    format!(
+      "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>gui layout</title>\n\
+       </head>\n<body>\n{}</body>\n</html>\n",
+      self.to_svg()
+    )
+  }
+}
+
+impl<R> Renderer for SvgRenderer<R>
+where
+  R: Renderer,
+{
+  fn renderable_area(&self) -> BBox {
+    self.inner.renderable_area()
+  }
+
+  fn measure(&self, object: &dyn Renderable, cap: &dyn Cap, available: Size) -> Size {
+    self.inner.measure(object, cap, available)
+  }
+
+  fn pre_render(&self) {
+    let () = self.entries.borrow_mut().clear();
+    let () = self.depth.set(0);
+    let () = self.partial.set(false);
+    let () = self.area.set(self.inner.renderable_area());
+    self.inner.pre_render()
+  }
+
+  fn pre_render_partial(&self) {
+    // Widgets not rendered anew keep their entries from earlier passes.
+    let () = self.depth.set(0);
+    let () = self.partial.set(true);
+    self.inner.pre_render_partial()
+  }
+
+  fn render(&self, object: &dyn Renderable, cap: &dyn Cap, bbox: BBox) -> BBox {
+    let inner = self.inner.render(object, cap, bbox);
+    let id = object.id();
+    let mut entries = self.entries.borrow_mut();
+    // During a partial pass, a widget rendered anew replaces its
+    // previous entry. The latter also provides the nesting depth, as
+    // parents are not necessarily rendered.
+    let existing = if self.partial.get() {
+      entries.iter().position(|entry| entry.id == id)
+    } else {
+      None
+    };
+    let depth = existing.map_or_else(|| self.depth.get(), |idx| entries[idx].depth);
+    let entry = Entry {
+      id,
+      type_name: object.type_name(),
+      bbox,
+      inner,
+      depth,
+      focused: cap.is_focused(id),
+    };
+    match existing {
+      Some(idx) => entries[idx] = entry,
+      None => entries.push(entry),
+    }
+    let () = self.depth.set(depth + 1);
+    inner
+  }
+
+  fn render_done(&self, object: &dyn Renderable, cap: &dyn Cap, bbox: BBox) {
+    let () = self.depth.set(self.depth.get().saturating_sub(1));
+    self.inner.render_done(object, cap, bbox)
+  }
+
+  fn post_render(&self) {
+    self.inner.post_render()
+  }
+}
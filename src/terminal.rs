@@ -0,0 +1,273 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::cell::Cell;
+use std::cell::Ref;
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::io::Error;
+use std::io::Result;
+use std::io::Write;
+
+use crate::cell::Grid;
+use crate::BBox;
+use crate::Cap;
+use crate::CellBuffer;
+use crate::CellStyle;
+use crate::Color;
+use crate::Renderable;
+use crate::Renderer;
+use crate::Size;
+
+
+/// Append the SGR parameters selecting the given color.
+///
+/// `base` is the parameter for selecting the first of the eight basic
+/// colors, i.e., 30 for foreground and 40 for background colors.
+fn color_params(params: &mut Vec<String>, color: Color, base: u8) {
+  let param = match color {
+    Color::Black => base.to_string(),
+    Color::Red => (base + 1).to_string(),
+    Color::Green => (base + 2).to_string(),
+    Color::Yellow => (base + 3).to_string(),
+    Color::Blue => (base + 4).to_string(),
+    Color::Magenta => (base + 5).to_string(),
+    Color::Cyan => (base + 6).to_string(),
+    Color::White => (base + 7).to_string(),
+    Color::Default => (base + 9).to_string(),
+    Color::Indexed(index) => format!("{};5;{}", base + 8, index),
+    Color::Rgb(r, g, b) => format!("{};2;{};{};{}", base + 8, r, g, b),
+  };
+  params.push(param)
+}
+
+/// Write the SGR sequence switching to the given style.
+///
+/// The sequence always resets all attributes first, so that it does
+/// not depend on the previously active style.
+fn write_style<W>(writer: &mut W, style: &CellStyle) -> Result<()>
+where
+  W: Write,
+{
+  let mut params = vec!["0".to_string()];
+  let emphasis = &style.emphasis;
+  for (enabled, param) in [
+    (emphasis.bold, "1"),
+    (emphasis.italic, "3"),
+    (emphasis.underline, "4"),
+    (emphasis.reverse, "7"),
+  ] {
+    if enabled {
+      params.push(param.to_string());
+    }
+  }
+
+  if style.fg != Color::Default {
+    let () = color_params(&mut params, style.fg, 30);
+  }
+  if style.bg != Color::Default {
+    let () = color_params(&mut params, style.bg, 40);
+  }
+
+  write!(writer, "\x1b[{}m", params.join(";"))
+}
+
+/// Write the changes necessary to turn `front` into `back`.
+///
+/// If `front` is not available, all cells are written. If `clear` is
+/// `true`, the screen is cleared first.
+fn write_diff<W>(writer: &mut W, front: Option<&Grid>, back: &Grid, clear: bool) -> Result<()>
+where
+  W: Write,
+{
+  if clear {
+    write!(writer, "\x1b[2J")?;
+  }
+
+  let size = back.size();
+  let mut cursor = None;
+  let mut style = None;
+
+  for y in 0..size.h {
+    for x in 0..size.w {
+      // SANITY: Both coordinates are within the grid's bounds.
+      let cell = back.get(x, y).unwrap();
+      if front.and_then(|front| front.get(x, y)) == Some(cell) {
+        continue
+      }
+
+      if cursor != Some((x, y)) {
+        write!(writer, "\x1b[{};{}H", y + 1, x + 1)?;
+      }
+      if style != Some(cell.style) {
+        let () = write_style(writer, &cell.style)?;
+        style = Some(cell.style);
+      }
+
+      write!(writer, "{}", cell.ch)?;
+      cursor = Some((x + 1, y));
+    }
+  }
+
+  if style.is_some() {
+    let () = write_style(writer, &CellStyle::default())?;
+  }
+  writer.flush()
+}
+
+
+/// A [`Renderer`] driving an ANSI/VT100 compatible terminal.
+///
+/// The terminal draws widgets into a [`CellBuffer`] (the back buffer)
+/// and, once rendering concluded in [`Renderer::post_render`], compares
+/// it to what was last sent to the terminal (the front buffer). Only
+/// cells that changed are written, using cursor positioning and SGR
+/// sequences for colors and emphasis, to the provided
+/// [`Write`][std::io::Write] implementation.
+///
+/// Each character is assumed to occupy a single terminal cell. Because
+/// [`Renderer`] methods cannot report failure, the first error
+/// encountered while writing is stored and can be retrieved using
+/// [`Terminal::take_error`].
+pub struct Terminal<W> {
+  /// The writer to emit terminal sequences to.
+  writer: RefCell<W>,
+  /// The buffer widgets are drawn into.
+  back: CellBuffer,
+  /// The contents last sent to the terminal, if known.
+  front: RefCell<Option<Grid>>,
+  /// Whether to clear the screen before writing the next frame.
+  clear: Cell<bool>,
+  /// The first error encountered while writing.
+  error: RefCell<Option<Error>>,
+}
+
+impl<W> Terminal<W>
+where
+  W: Write,
+{
+  /// Create a new `Terminal` writing to `writer` and drawing widgets
+  /// using the given [`CellBuffer`].
+  ///
+  /// The buffer's size should match that of the terminal.
+  pub fn new(writer: W, buffer: CellBuffer) -> Self {
+    Self {
+      writer: RefCell::new(writer),
+      back: buffer,
+      front: RefCell::new(None),
+      clear: Cell::new(false),
+      error: RefCell::new(None),
+    }
+  }
+
+  /// Retrieve the buffer widgets are drawn into.
+  pub fn buffer(&self) -> &CellBuffer {
+    &self.back
+  }
+
+  /// Adjust the size of the terminal.
+  ///
+  /// The next render clears the screen, so that no stale contents
+  /// remain outside of the new area, and writes all cells. Because
+  /// [`Ui::render_dirty`][crate::Ui::render_dirty] does nothing if no
+  /// widget got invalidated, the `Ui` should be rendered using
+  /// [`Ui::render`][crate::Ui::render] afterwards.
+  pub fn resize(&self, size: Size) {
+    let () = self.back.resize(size);
+    let () = self.invalidate();
+    let () = self.clear.set(true);
+  }
+
+  /// Forget about what was sent to the terminal, causing the next
+  /// render to write all cells.
+  ///
+  /// That is useful when the terminal's contents were changed
+  /// externally.
+  pub fn invalidate(&self) {
+    *self.front.borrow_mut() = None;
+  }
+
+  /// Retrieve and clear the first error that occurred while writing to
+  /// the terminal since the last invocation.
+  pub fn take_error(&self) -> Option<Error> {
+    self.error.borrow_mut().take()
+  }
+
+  /// Retrieve a reference to the underlying writer.
+  pub fn get_ref(&self) -> Ref<'_, W> {
+    self.writer.borrow()
+  }
+
+  /// Retrieve a mutable reference to the underlying writer.
+  pub fn get_mut(&mut self) -> &mut W {
+    self.writer.get_mut()
+  }
+
+  /// Destroy the `Terminal`, returning the underlying writer.
+  pub fn into_inner(self) -> W {
+    self.writer.into_inner()
+  }
+}
+
+impl<W> Renderer for Terminal<W>
+where
+  W: Write,
+{
+  fn renderable_area(&self) -> BBox {
+    self.back.renderable_area()
+  }
+
+  fn measure(&self, object: &dyn Renderable, cap: &dyn Cap, available: Size) -> Size {
+    self.back.measure(object, cap, available)
+  }
+
+  fn pre_render(&self) {
+    self.back.pre_render()
+  }
+
+  fn pre_render_partial(&self) {
+    self.back.pre_render_partial()
+  }
+
+  fn render(&self, object: &dyn Renderable, cap: &dyn Cap, bbox: BBox) -> BBox {
+    self.back.render(object, cap, bbox)
+  }
+
+  fn render_done(&self, object: &dyn Renderable, cap: &dyn Cap, bbox: BBox) {
+    self.back.render_done(object, cap, bbox)
+  }
+
+  fn post_render(&self) {
+    let () = self.back.post_render();
+
+    let back = self.back.grid();
+    let mut front = self.front.borrow_mut();
+    let clear = self.clear.get();
+    let result = write_diff(&mut *self.writer.borrow_mut(), front.as_ref(), &back, clear);
+    match result {
+      Ok(()) => {
+        *front = Some(back.clone());
+        let () = self.clear.set(false);
+      },
+      Err(err) => {
+        // We don't know what made it to the terminal, so redraw
+        // everything next time around.
+        *front = None;
+        let mut error = self.error.borrow_mut();
+        if error.is_none() {
+          *error = Some(err);
+        }
+      },
+    }
+  }
+}
+
+impl<W> Debug for Terminal<W> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+    f.debug_struct("Terminal")
+      .field("buffer", &self.back)
+      .finish()
+  }
+}
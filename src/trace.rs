@@ -0,0 +1,320 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::error::Error;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result;
+use std::time::Duration;
+use std::time::Instant;
+
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::Cap;
+use crate::Id;
+use crate::ManualClock;
+use crate::MutCap;
+use crate::Ui;
+
+
+/// An input to a [`Ui`] as recorded in a [`Trace`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum TraceInput<E, M> {
+  /// An event passed to [`Ui::handle`].
+  Event(E),
+  /// A message sent to a widget via [`MutCap::send`].
+  Message {
+    /// The `Ui` independent index of the receiving widget.
+    widget: usize,
+    /// The message sent.
+    message: M,
+  },
+  /// Timers fired via [`Ui::fire_due`], at the time of the entry.
+  Timers,
+  /// Tasks completed while driven by [`Ui::run_tasks`].
+  Tasks {
+    /// The sequence numbers of the completed tasks, in the order in
+    /// which they were spawned, along with their outputs.
+    completed: Vec<(usize, M)>,
+  },
+}
+
+
+/// A single entry in a [`Trace`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TraceEntry<E, M> {
+  /// The time the input was provided at, relative to the start of the
+  /// recording.
+  pub time: Duration,
+  /// The `Ui` independent index of the widget that had the focus when
+  /// the input was provided.
+  pub focused: Option<usize>,
+  /// The input itself.
+  pub input: TraceInput<E, M>,
+}
+
+
+/// A trace of all external inputs to a [`Ui`], as created by a
+/// [`Recorder`].
+///
+/// If the `serde` feature is enabled, a trace can be serialized and
+/// deserialized, provided that the event and message types support
+/// that.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Trace<E, M> {
+  /// The recorded entries, in order.
+  pub entries: Vec<TraceEntry<E, M>>,
+  /// The dump of the widget tree after the last input, as produced by
+  /// [`Ui::dump`].
+  pub dump: String,
+}
+
+impl<E, M> Trace<E, M>
+where
+  E: Clone,
+  M: Clone,
+{
+  /// Replay the trace by feeding its inputs to the given `Ui`.
+  ///
+  /// The `Ui` is expected to be in the same state as the one the
+  /// recording started with. Before each input is provided, the focus
+  /// is compared to the recorded one and, eventually, the resulting
+  /// widget tree is as well. A divergence is reported as an error.
+  ///
+  /// For the replay to be deterministic, the `Ui`'s
+  /// [`Clock`][crate::Clock] is replaced with a [`ManualClock`]
+  /// starting at the current time, which is advanced to the time of
+  /// each entry before its input is provided. Tasks are not actually
+  /// run: the recorded outputs of completed tasks are delivered
+  /// instead and the corresponding tasks are dropped.
+  pub async fn replay(&self, ui: &mut Ui<E, M>) -> std::result::Result<(), ReplayError> {
+    let start = ui.now();
+    let clock = ManualClock::new(start);
+    let () = ui.set_clock(Box::new(clock.clone()));
+
+    for (i, entry) in self.entries.iter().enumerate() {
+      let () = clock.set(start + entry.time);
+
+      let focused = ui.focused().map(|id| id.index());
+      if focused != entry.focused {
+        return Err(ReplayError::FocusMismatch {
+          entry: i,
+          expected: entry.focused,
+          actual: focused,
+        })
+      }
+
+      match &entry.input {
+        TraceInput::Event(event) => {
+          let _ = ui.handle(event.clone()).await;
+        },
+        TraceInput::Message { widget, message } => {
+          let widget = ui
+            .id_from_index(*widget)
+            .ok_or(ReplayError::UnknownWidget {
+              entry: i,
+              widget: *widget,
+            })?;
+          let _ = ui.send(widget, message.clone()).await;
+        },
+        TraceInput::Timers => {
+          let _ = ui.fire_due(ui.now()).await;
+        },
+        TraceInput::Tasks { completed } => {
+          let completed = completed
+            .iter()
+            .map(|(task, message)| {
+              let widget = ui.take_task(*task).ok_or(ReplayError::UnknownTask {
+                entry: i,
+                task: *task,
+              })?;
+              Ok((widget, message.clone()))
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+          let () = ui.complete_tasks(completed).await;
+        },
+      }
+    }
+
+    let dump = ui.dump();
+    if dump != self.dump {
+      return Err(ReplayError::TreeMismatch {
+        expected: self.dump.clone(),
+        actual: dump,
+      })
+    }
+    Ok(())
+  }
+}
+
+
+/// An error reported when replaying a [`Trace`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReplayError {
+  /// The focused widget differs from the recorded one.
+  FocusMismatch {
+    /// The index of the trace entry at which the mismatch occurred.
+    entry: usize,
+    /// The index of the widget that was focused during recording.
+    expected: Option<usize>,
+    /// The index of the widget focused during replay.
+    actual: Option<usize>,
+  },
+  /// A message was sent to a widget that does not exist.
+  UnknownWidget {
+    /// The index of the trace entry that referenced the widget.
+    entry: usize,
+    /// The index of the widget.
+    widget: usize,
+  },
+  /// A completed task does not exist.
+  UnknownTask {
+    /// The index of the trace entry that referenced the task.
+    entry: usize,
+    /// The sequence number of the task.
+    task: usize,
+  },
+  /// The widget tree resulting from the replay differs from the
+  /// recorded one.
+  TreeMismatch {
+    /// The recorded tree dump.
+    expected: String,
+    /// The tree dump produced by the replay.
+    actual: String,
+  },
+}
+
+impl Display for ReplayError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    match self {
+      Self::FocusMismatch {
+        entry,
+        expected,
+        actual,
+      } => write!(
+        f,
+        "focus mismatch at entry {}: expected {:?}, got {:?}",
+        entry, expected, actual
+      ),
+      Self::UnknownWidget { entry, widget } => {
+        write!(f, "entry {} references unknown widget {}", entry, widget)
+      },
+      Self::UnknownTask { entry, task } => {
+        write!(f, "entry {} references unknown task {}", entry, task)
+      },
+      Self::TreeMismatch { expected, actual } => write!(
+        f,
+        "widget tree mismatch:\nexpected:\n{}actual:\n{}",
+        expected, actual
+      ),
+    }
+  }
+}
+
+impl Error for ReplayError {}
+
+
+/// A recorder of all external inputs to a [`Ui`].
+///
+/// The recorder wraps [`Ui::handle`], [`MutCap::send`],
+/// [`Ui::fire_due`], and [`Ui::run_tasks`], logging each input along
+/// with the time and the focused widget before forwarding it. The
+/// result is a [`Trace`] that can be replayed later on.
+#[derive(Debug)]
+pub struct Recorder<E, M> {
+  /// The time the recording started at.
+  start: Instant,
+  /// The entries recorded so far.
+  entries: Vec<TraceEntry<E, M>>,
+}
+
+impl<E, M> Recorder<E, M>
+where
+  E: Clone,
+  M: Clone,
+{
+  /// Start recording the inputs to the given `Ui`.
+  ///
+  /// Time stamps are taken from the `Ui`'s [`Clock`][crate::Clock].
+  pub fn new(ui: &Ui<E, M>) -> Self {
+    Self {
+      start: ui.now(),
+      entries: Vec::new(),
+    }
+  }
+
+  /// Record an input provided at the given time.
+  fn record(&mut self, ui: &Ui<E, M>, now: Instant, input: TraceInput<E, M>) {
+    let entry = TraceEntry {
+      time: now.saturating_duration_since(self.start),
+      focused: ui.focused().map(|id| id.index()),
+      input,
+    };
+    self.entries.push(entry)
+  }
+
+  /// Record an event and pass it to [`Ui::handle`].
+  pub async fn handle<T>(&mut self, ui: &mut Ui<E, M>, event: T) -> Option<E>
+  where
+    T: Into<E>,
+  {
+    let event = event.into();
+    let () = self.record(ui, ui.now(), TraceInput::Event(event.clone()));
+    ui.handle(event).await
+  }
+
+  /// Record a message and send it to the given widget.
+  pub async fn send(&mut self, ui: &mut Ui<E, M>, widget: Id, message: M) -> Option<M> {
+    let input = TraceInput::Message {
+      widget: widget.index(),
+      message: message.clone(),
+    };
+    let () = self.record(ui, ui.now(), input);
+    ui.send(widget, message).await
+  }
+
+  /// Record the firing of timers and pass it on to [`Ui::fire_due`].
+  pub async fn fire_due(&mut self, ui: &mut Ui<E, M>, now: Instant) -> usize {
+    let () = self.record(ui, now, TraceInput::Timers);
+    ui.fire_due(now).await
+  }
+
+  /// Drive the tasks of the given `Ui` as [`Ui::run_tasks`] does,
+  /// recording the outputs of those that completed.
+  pub async fn run_tasks(&mut self, ui: &mut Ui<E, M>) -> usize {
+    let completed = ui.poll_tasks().await;
+    let count = completed.len();
+
+    if count > 0 {
+      let input = TraceInput::Tasks {
+        completed: completed
+          .iter()
+          .map(|(task, _, message)| (*task, message.clone()))
+          .collect(),
+      };
+      let () = self.record(ui, ui.now(), input);
+    }
+
+    let completed = completed
+      .into_iter()
+      .map(|(_, widget, message)| (widget, message))
+      .collect();
+    let () = ui.complete_tasks(completed).await;
+    count
+  }
+
+  /// Finish the recording, capturing the current state of the widget
+  /// tree.
+  pub fn finish(self, ui: &Ui<E, M>) -> Trace<E, M> {
+    Trace {
+      entries: self.entries,
+      dump: ui.dump(),
+    }
+  }
+}
@@ -0,0 +1,136 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::error::Error;
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::marker::PhantomData;
+
+use crate::Id;
+
+
+/// An [`Id`] that additionally captures the type of the widget it
+/// refers to as well as that of the widget's data.
+///
+/// A `TypedId` is handed out when adding a widget via
+/// [`Ui::add_ui_widget`][crate::Ui::add_ui_widget] and allows for
+/// accessing the widget's data without downcasting, via `get` and
+/// `get_mut` on [`Cap`][crate::Cap] and [`MutCap`][crate::MutCap]
+/// trait objects, respectively.
+pub struct TypedId<W, D> {
+  /// The untyped `Id` of the widget.
+  id: Id,
+  /// Phantom data for the widget and data types.
+  _phantom: PhantomData<fn() -> (W, D)>,
+}
+
+impl<W, D> TypedId<W, D> {
+  /// Create a new `TypedId` for a widget known to be of type `W` with
+  /// data of type `D`.
+  pub(crate) fn new(id: Id) -> Self {
+    Self {
+      id,
+      _phantom: PhantomData,
+    }
+  }
+
+  /// Retrieve the untyped [`Id`] of the widget.
+  #[inline]
+  pub fn id(&self) -> Id {
+    self.id
+  }
+}
+
+impl<W, D> Clone for TypedId<W, D> {
+  fn clone(&self) -> Self {
+    *self
+  }
+}
+
+impl<W, D> Copy for TypedId<W, D> {}
+
+impl<W, D> Debug for TypedId<W, D> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    f.debug_tuple("TypedId").field(&self.id).finish()
+  }
+}
+
+impl<W, D> Display for TypedId<W, D> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    Display::fmt(&self.id, f)
+  }
+}
+
+impl<W, D> PartialEq for TypedId<W, D> {
+  fn eq(&self, other: &Self) -> bool {
+    self.id == other.id
+  }
+}
+
+impl<W, D> Eq for TypedId<W, D> {}
+
+impl<W, D> Hash for TypedId<W, D> {
+  fn hash<H>(&self, state: &mut H)
+  where
+    H: Hasher,
+  {
+    self.id.hash(state)
+  }
+}
+
+impl<W, D> From<TypedId<W, D>> for Id {
+  fn from(typed: TypedId<W, D>) -> Self {
+    typed.id
+  }
+}
+
+impl<W, D> PartialEq<Id> for TypedId<W, D> {
+  fn eq(&self, other: &Id) -> bool {
+    self.id == *other
+  }
+}
+
+
+/// An error as reported when accessing a widget's data fails.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DataError {
+  /// The widget's data is not of the expected type.
+  TypeMismatch {
+    /// The [`Id`] of the widget whose data were accessed.
+    widget: Id,
+    /// The name of the expected type.
+    expected: &'static str,
+    /// The name of the actual type of the data.
+    ///
+    /// The name is only known for data of widgets added via
+    /// [`Ui::add_ui_widget`][crate::Ui::add_ui_widget].
+    actual: Option<&'static str>,
+  },
+}
+
+impl Display for DataError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    match self {
+      Self::TypeMismatch {
+        widget,
+        expected,
+        actual: Some(actual),
+      } => write!(
+        f,
+        "data of widget {} are of type {} and not {}",
+        widget, actual, expected
+      ),
+      Self::TypeMismatch {
+        widget,
+        expected,
+        actual: None,
+      } => write!(f, "data of widget {} are not of type {}", widget, expected),
+    }
+  }
+}
+
+impl Error for DataError {}
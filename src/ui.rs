@@ -1,7 +1,10 @@
 // Copyright (C) 2018-2024 Daniel Mueller (deso@posteo.net)
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::any::type_name;
 use std::any::Any;
+use std::any::TypeId;
+use std::cell::Cell;
 use std::fmt::Debug;
 use std::fmt::Display;
 use std::fmt::Formatter;
@@ -11,6 +14,10 @@ use std::ops::Deref;
 use std::pin::Pin;
 use std::rc::Rc;
 use std::slice::Iter;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
 #[cfg(debug_assertions)]
 use std::sync::atomic::AtomicUsize;
 #[cfg(debug_assertions)]
@@ -18,10 +25,25 @@ use std::sync::atomic::Ordering;
 
 use async_trait::async_trait;
 
+use crate::animation::Tween;
 use crate::BBox;
+use crate::Clock;
+use crate::DataError;
+use crate::Easing;
+use crate::Layout;
 use crate::Mergeable;
 use crate::Placeholder;
+use crate::Point;
+use crate::Query;
+use crate::QueryError;
 use crate::Renderer;
+use crate::Request;
+use crate::Size;
+use crate::Style;
+use crate::SystemClock;
+use crate::Theme;
+use crate::TypedId;
+use crate::Viewport;
 use crate::Widget;
 
 
@@ -64,6 +86,11 @@ impl Id {
       idx: Index::new(idx),
     }
   }
+
+  /// Retrieve the `Ui` independent index of the widget.
+  pub(crate) fn index(&self) -> usize {
+    self.idx.idx
+  }
 }
 
 impl Display for Id {
@@ -151,6 +178,8 @@ pub(crate) type ChildIter<'widget> = Iter<'widget, Id>;
 
 type NewDataFn = dyn FnOnce() -> Box<dyn Any>;
 type NewWidgetFn<E, M> = dyn FnOnce(Id, &mut dyn MutCap<E, M>) -> Box<dyn Widget<E, M>>;
+/// The type of a task as spawned via [`MutCap::spawn`].
+pub type TaskFuture<M> = Pin<Box<dyn Future<Output = M>>>;
 type EventHookFn<E, M> = &'static dyn for<'f> Fn(
   &'f dyn Widget<E, M>,
   &'f mut dyn MutCap<E, M>,
@@ -166,6 +195,12 @@ pub trait Cap: Debug + private::Sealed {
   /// Retrieve a reference to a widget's data.
   fn data(&self, widget: Id) -> &dyn Any;
 
+  /// Retrieve the name of the type of a widget's data.
+  ///
+  /// The name is only known for data of widgets added via
+  /// [`Ui::add_ui_widget`].
+  fn data_type_name(&self, widget: Id) -> Option<&'static str>;
+
   /// Retrieve an iterator over the children. Iteration happens in
   /// z-order, from highest to lowest.
   fn children(&self, widget: Id) -> ChildIter<'_>;
@@ -197,6 +232,67 @@ pub trait Cap: Debug + private::Sealed {
 
   /// Check whether the widget with the given [`Id`] is focused.
   fn is_focused(&self, widget: Id) -> bool;
+
+  /// Retrieve the [`TypeId`] of the given widget.
+  fn widget_type(&self, widget: Id) -> TypeId;
+
+  /// Check whether any displayed widget got invalidated since the last
+  /// time the `Ui` was rendered.
+  fn needs_render(&self) -> bool;
+
+  /// Retrieve the size of a widget as determined during the most recent
+  /// measure pass.
+  ///
+  /// `None` is returned if the widget was not measured, for example,
+  /// because it was not displayed.
+  fn measured_size(&self, widget: Id) -> Option<Size>;
+
+  /// Retrieve the viewport attached to a widget, if any.
+  fn viewport(&self, widget: Id) -> Option<Viewport>;
+
+  /// Retrieve the bounding box assigned to a widget during the most
+  /// recent render pass, before it got clipped to its parent's inner
+  /// bounding box.
+  ///
+  /// For widgets inside a scrolled [`Viewport`], the result may lie
+  /// partly or entirely outside of the renderable area, which is why
+  /// signed coordinates are used. Renderers can use it to tell which
+  /// part of a widget is actually visible.
+  fn unclipped_bbox(&self, widget: Id) -> Option<BBox<i64>>;
+
+  /// Check whether a widget is a portal.
+  ///
+  /// See [`MutCap::set_portal`] for details.
+  fn is_portal(&self, widget: Id) -> bool;
+
+  /// Retrieve the top-most displayed widget containing the given point,
+  /// based on the bounding boxes determined during the most recent
+  /// render pass.
+  ///
+  /// Portals, being rendered on top of all other widgets, are
+  /// considered first. `None` is returned if no widget contains the
+  /// point.
+  fn hit_test(&self, point: Point) -> Option<Id>;
+
+  /// Retrieve the current value of an animated property of a widget.
+  ///
+  /// The value is the one determined by the most recent invocation of
+  /// [`Ui::tick`]. Once an animation concluded, its final value remains
+  /// available until the next invocation of [`Ui::tick`], which removes
+  /// the animation. `None` is returned if the property is not animated.
+  fn animated_value(&self, widget: Id, property: &str) -> Option<f64>;
+
+  /// Retrieve the effective style of a widget.
+  ///
+  /// The style is resolved by starting with the base style of the
+  /// [`Theme`] and, for the root widget and each descendant down to the
+  /// given widget, applying the theme's style for the widget's type as
+  /// well as the widget's own style, if any. Properties set later
+  /// take precedence.
+  fn style(&self, widget: Id) -> Style;
+
+  /// Retrieve the [`Theme`] of the `Ui`.
+  fn theme(&self) -> &Theme;
 }
 
 
@@ -204,8 +300,19 @@ pub trait Cap: Debug + private::Sealed {
 #[async_trait(?Send)]
 pub trait MutCap<E, M>: Cap + Deref<Target = dyn Cap> {
   /// Retrieve a mutable reference to a widget's data.
+  ///
+  /// The access is reported as a change to all widgets watching the
+  /// given one. See [`MutCap::watch`].
   fn data_mut(&mut self, widget: Id) -> &mut dyn Any;
 
+  /// Retrieve a mutable reference to a widget's data without reporting
+  /// a change to the widgets watching it.
+  ///
+  /// This method is meant for bookkeeping that does not alter the
+  /// data as observed by others, such as temporarily taking out parts
+  /// of it.
+  fn data_mut_untracked(&mut self, widget: Id) -> &mut dyn Any;
+
   /// Add a widget to the `Ui` represented by the capability.
   // TODO: We should not require a Box here conceptually, but omitting
   //       it will require the unboxed closures feature to stabilize.
@@ -237,6 +344,54 @@ pub trait MutCap<E, M>: Cap + Deref<Target = dyn Cap> {
   /// being visible. This invariant is enforced internally.
   fn focus(&mut self, widget: Id);
 
+  /// Invalidate a widget, marking it as in need of rendering.
+  ///
+  /// Widgets are invalidated automatically when their data are accessed
+  /// mutably or when they are shown, hidden, or focused. Explicit
+  /// invalidation is only necessary if a widget's appearance changed
+  /// for other reasons.
+  fn invalidate(&mut self, widget: Id);
+
+  /// Attach a layout to a widget or remove it.
+  ///
+  /// The [`Layout`] determines the bounding boxes that the widget's
+  /// children are rendered into. Without a layout, each child receives
+  /// the widget's entire inner bounding box. The method returns the
+  /// layout that was previously attached, if any.
+  fn set_layout(&mut self, widget: Id, layout: Option<Box<dyn Layout>>) -> Option<Box<dyn Layout>>;
+
+  /// Attach a viewport to a widget or remove it.
+  ///
+  /// Scrolling happens by attaching a [`Viewport`] with a different
+  /// offset. The method returns the viewport that was previously
+  /// attached, if any.
+  fn set_viewport(&mut self, widget: Id, viewport: Option<Viewport>) -> Option<Viewport>;
+
+  /// Scroll all viewports containing the given widget such that it gets
+  /// displayed, if possible.
+  ///
+  /// Viewports are scrolled as little as possible. Should the widget be
+  /// larger than a viewport, its upper left corner is brought into
+  /// view. Scrolling is based on the geometry determined during the
+  /// most recent render pass and has no effect for widgets that were
+  /// not placed then.
+  fn scroll_into_view(&mut self, widget: Id);
+
+  /// Mark a widget as a portal or revert it to a regular widget.
+  ///
+  /// A portal stays a child of its parent logically, i.e., it receives
+  /// events bubbling up from its children and passes unhandled ones on
+  /// to its parent as usual. However, it is rendered, along with its
+  /// children, in an overlay pass after all regular widgets, so that it
+  /// appears on top of everything else. Rendering is clipped to the
+  /// entire renderable area instead of the parent's inner bounding
+  /// box. The bounding box of a portal is the one assigned by its
+  /// parent's layout, if any, and the entire renderable area otherwise.
+  /// Portals contained in other portals are rendered after those.
+  ///
+  /// The method returns whether the widget was a portal before.
+  fn set_portal(&mut self, widget: Id, portal: bool) -> bool;
+
   /// Install or remove an event hook handler.
   ///
   /// The event hook handler is a call back function that is invoked for
@@ -279,6 +434,188 @@ pub trait MutCap<E, M>: Cap + Deref<Target = dyn Cap> {
   /// Send the provided message to the given widget, without
   /// transferring ownership of the message.
   async fn call(&mut self, widget: Id, message: &mut M) -> Option<M>;
+
+  /// Send the provided type-erased query to the given widget.
+  ///
+  /// Most clients will want to use the typed `query` method instead.
+  async fn ask(&mut self, widget: Id, request: &mut Request<'_>);
+
+  /// Spawn a task on behalf of a widget.
+  ///
+  /// The task is a future that may outlive the current handler
+  /// invocation. It is driven by [`Ui::run_tasks`] and, once it
+  /// completed, its output is sent as a message to the given widget,
+  /// where it is handled by `Handleable::react`. No particular
+  /// executor is required for running tasks.
+  ///
+  /// Just like timers, pending tasks get dropped when the widget or one
+  /// of its ancestors is hidden. Likewise, tasks spawned for a widget
+  /// that is not displayed are dropped right away.
+  // TODO: We should not require a Box here conceptually, but we cannot
+  //       have generic methods on an object safe trait.
+  fn spawn(&mut self, widget: Id, task: TaskFuture<M>);
+
+  /// Schedule a message to be sent to a widget after the given delay.
+  ///
+  /// The timer expires once the [`Clock`] of the `Ui` reports a time
+  /// past the deadline and [`Ui::fire_due`] is invoked. The message is
+  /// then handled by the widget's `Handleable::react` method.
+  ///
+  /// Timers are cancelled automatically when the widget they belong to
+  /// (or one of its parents) is hidden. Likewise, timers scheduled for a
+  /// widget that is not displayed are cancelled right away.
+  fn schedule(&mut self, widget: Id, delay: Duration, message: M) -> TimerId;
+
+  /// Schedule a message to be sent to a widget repeatedly, using the
+  /// given interval.
+  ///
+  /// Expirations that were missed (because [`Ui::fire_due`] was not
+  /// invoked in time) are coalesced into a single one. Cancellation
+  /// works as for timers created via [`schedule`][Self::schedule].
+  ///
+  /// # Panics
+  ///
+  /// This method panics if `interval` is zero.
+  fn schedule_repeating(&mut self, widget: Id, interval: Duration, message: M) -> TimerId
+  where
+    M: Clone;
+
+  /// Cancel a timer.
+  ///
+  /// The method returns `true` if the timer was still active.
+  fn cancel(&mut self, timer: TimerId) -> bool;
+
+  /// Animate a property of a widget from one value to another over the
+  /// given duration.
+  ///
+  /// The animation starts at the current time, as reported by the
+  /// [`Clock`] of the `Ui`, and is advanced by [`Ui::tick`], which
+  /// invalidates the widget. The current value can be retrieved via
+  /// [`Cap::animated_value`]. Animating a property that is already
+  /// being animated replaces the existing animation.
+  fn animate(
+    &mut self,
+    widget: Id,
+    property: &'static str,
+    from: f64,
+    to: f64,
+    duration: Duration,
+    easing: Easing,
+  );
+
+  /// Set the style of a widget or remove it.
+  ///
+  /// Properties not set in the [`Style`] are inherited from the
+  /// widget's parent. The widget gets invalidated and, with it, all its
+  /// descendants get rendered anew. The method returns the style that
+  /// was previously set, if any.
+  fn set_style(&mut self, widget: Id, style: Option<Style>) -> Option<Style>;
+
+  /// Swap the [`Theme`] of the `Ui`, returning the previous one.
+  ///
+  /// All widgets are rendered anew during the next render pass.
+  fn set_theme(&mut self, theme: Theme) -> Theme;
+
+  /// Register interest of one widget in the data of another.
+  ///
+  /// Whenever the data of the `observed` widget are accessed mutably,
+  /// the `observer` is informed about the change via
+  /// [`Handleable::observe`][crate::Handleable::observe], once the
+  /// current dispatch concluded. See [`Ui::notify_observers`] for
+  /// details. Access via [`MutCap::data_mut_untracked`] is not
+  /// reported.
+  fn watch(&mut self, observer: Id, observed: Id);
+
+  /// Revoke interest of one widget in the data of another, returning
+  /// whether such interest was registered.
+  fn unwatch(&mut self, observer: Id, observed: Id) -> bool;
+}
+
+impl dyn Cap + '_ {
+  /// Retrieve a reference to the data of a widget added via
+  /// [`Ui::add_ui_widget`].
+  ///
+  /// # Panics
+  ///
+  /// This method panics if the [`TypedId`] was handed out by a
+  /// different `Ui`.
+  pub fn get<W, D>(&self, widget: TypedId<W, D>) -> &D
+  where
+    D: 'static,
+  {
+    self
+      .try_data(widget.id())
+      .unwrap_or_else(|err| panic!("{}", err))
+  }
+
+  /// Retrieve a reference to a widget's data, if they are of type `D`.
+  pub fn try_data<D>(&self, widget: Id) -> std::result::Result<&D, DataError>
+  where
+    D: 'static,
+  {
+    self
+      .data(widget)
+      .downcast_ref::<D>()
+      .ok_or_else(|| DataError::TypeMismatch {
+        widget,
+        expected: type_name::<D>(),
+        actual: self.data_type_name(widget),
+      })
+  }
+}
+
+impl<E, M> dyn MutCap<E, M> + '_ {
+  /// Retrieve a mutable reference to the data of a widget added via
+  /// [`Ui::add_ui_widget`].
+  ///
+  /// # Panics
+  ///
+  /// This method panics if the [`TypedId`] was handed out by a
+  /// different `Ui`.
+  pub fn get_mut<W, D>(&mut self, widget: TypedId<W, D>) -> &mut D
+  where
+    D: 'static,
+  {
+    self
+      .try_data_mut(widget.id())
+      .unwrap_or_else(|err| panic!("{}", err))
+  }
+
+  /// Retrieve a mutable reference to a widget's data, if they are of
+  /// type `D`.
+  ///
+  /// The widget is only invalidated if the data are of type `D`.
+  pub fn try_data_mut<D>(&mut self, widget: Id) -> std::result::Result<&mut D, DataError>
+  where
+    D: 'static,
+  {
+    if !self.data(widget).is::<D>() {
+      return Err(DataError::TypeMismatch {
+        widget,
+        expected: type_name::<D>(),
+        actual: self.data_type_name(widget),
+      })
+    }
+    // SANITY: We just checked the type of the data.
+    Ok(self.data_mut(widget).downcast_mut::<D>().unwrap())
+  }
+
+  /// Send a typed query to the given widget and retrieve its response.
+  ///
+  /// The query is handled by the widget's
+  /// [`Handleable::answer`][crate::Handleable::answer] method. If the
+  /// widget does not provide a response, a
+  /// [`QueryError::Unanswered`] error is reported.
+  pub async fn query<Q>(
+    &mut self,
+    widget: Id,
+    query: Q,
+  ) -> std::result::Result<Q::Response, QueryError>
+  where
+    Q: Query,
+  {
+    crate::query::query(self, widget, query).await
+  }
 }
 
 
@@ -290,6 +627,28 @@ fn get_next_ui_id() -> usize {
 }
 
 
+/// Clip the unclipped bounding box of a child to its parent's inner
+/// bounding box.
+fn clip(bbox: BBox<i64>, inner_bbox: BBox) -> BBox {
+  bbox.cast::<u16>().intersect(&inner_bbox)
+}
+
+
+/// Calculate the distance by which to scroll a viewport along one axis
+/// for the range `[pos, pos + len)` to be displayed in the range
+/// `[view, view + view_len)`.
+fn scroll_distance(pos: i64, len: i64, view: i64, view_len: i64) -> i64 {
+  if pos < view {
+    pos - view
+  } else if pos + len > view + view_len {
+    // If the range does not fit, make sure to display its start.
+    (pos + len - (view + view_len)).min(pos - view)
+  } else {
+    0
+  }
+}
+
+
 /// This type contains data that is common to all widgets.
 #[derive(Debug)]
 struct WidgetData<E, M>
@@ -317,16 +676,54 @@ where
   event_hook: Option<EventHook<E, M>>,
   /// Flag indicating the widget's visibility state.
   visible: bool,
+  /// An optional layout determining the bounding boxes of the widget's
+  /// children.
+  layout: Option<Box<dyn Layout>>,
+  /// An optional viewport through which the widget's children are
+  /// shown.
+  viewport: Option<Viewport>,
+  /// Flag indicating whether the widget is rendered in the overlay
+  /// pass.
+  portal: bool,
+  /// The widget's own style, if any.
+  style: Option<Style>,
+  /// The name of the type of the widget's data, if known.
+  data_type: Option<&'static str>,
+  /// Flag indicating whether the widget got invalidated since it was
+  /// last rendered.
+  dirty: Cell<bool>,
+  /// The size of the widget as determined by the last measure pass.
+  measured: Cell<Option<Size>>,
+  /// The bounding box the widget got assigned last, before clipping.
+  unclipped: Cell<Option<BBox<i64>>>,
+  /// The bounding box the widget got rendered into last.
+  bbox: Cell<Option<BBox>>,
+  /// The scroll offset of the widget's viewport when it was last
+  /// rendered.
+  rendered_offset: Cell<Option<Point>>,
+  /// The bounding box the widget's children got rendered into last.
+  inner_bbox: Cell<Option<BBox>>,
 }
 
 impl<E, M> WidgetData<E, M> {
-  fn new(parent_idx: Option<Index>, data: Box<dyn Any>) -> Self {
+  fn new(parent_idx: Option<Index>, data: Box<dyn Any>, data_type: Option<&'static str>) -> Self {
     Self {
       parent_idx,
       data,
       children: Default::default(),
       event_hook: None,
       visible: true,
+      layout: None,
+      viewport: None,
+      portal: false,
+      style: None,
+      data_type,
+      dirty: Cell::new(true),
+      measured: Cell::new(None),
+      unclipped: Cell::new(None),
+      bbox: Cell::new(None),
+      rendered_offset: Cell::new(None),
+      inner_bbox: Cell::new(None),
     }
   }
 }
@@ -345,6 +742,74 @@ impl<E, M> Debug for EventHook<E, M> {
 }
 
 
+/// A task spawned on behalf of a widget.
+struct Task<M> {
+  /// The task's sequence number, in the order of spawning.
+  id: usize,
+  /// The `Index` of the widget the task belongs to.
+  idx: Index,
+  /// The future representing the actual task.
+  future: TaskFuture<M>,
+}
+
+
+/// A future polling a set of tasks until at least one of them has
+/// completed.
+struct PollTasks<'tasks, M> {
+  tasks: &'tasks mut Vec<Task<M>>,
+}
+
+impl<M> Future for PollTasks<'_, M> {
+  type Output = Vec<(usize, Index, M)>;
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let tasks = &mut self.get_mut().tasks;
+    let mut completed = Vec::new();
+    let mut i = 0;
+
+    while i < tasks.len() {
+      if let Poll::Ready(message) = tasks[i].future.as_mut().poll(cx) {
+        let task = tasks.swap_remove(i);
+        completed.push((task.id, task.idx, message));
+      } else {
+        i += 1;
+      }
+    }
+
+    if completed.is_empty() && !tasks.is_empty() {
+      Poll::Pending
+    } else {
+      Poll::Ready(completed)
+    }
+  }
+}
+
+
+/// An identifier for a timer, as returned by [`MutCap::schedule`] and
+/// [`MutCap::schedule_repeating`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TimerId(usize);
+
+
+/// The interval and message cloning function of a repeating timer.
+type Repeat<M> = (Duration, fn(&M) -> M);
+
+
+/// A timer scheduled on behalf of a widget.
+struct Timer<M> {
+  /// The timer's identifier.
+  id: TimerId,
+  /// The `Index` of the widget the timer belongs to.
+  idx: Index,
+  /// The point in time at which the timer expires next.
+  deadline: Instant,
+  /// The message to send on expiration.
+  message: Option<M>,
+  /// Repetition information, present only for repeating timers.
+  repeat: Option<Repeat<M>>,
+}
+
+
 /// A `Ui` is a container for related widgets.
 pub struct Ui<E, M>
 where
@@ -358,6 +823,19 @@ where
   hooker: &'static dyn Hooker<E, M>,
   hooked: Rc<Vec<Index>>,
   focused: Option<Index>,
+  tasks: Vec<Task<M>>,
+  next_task: usize,
+  timers: Vec<Timer<M>>,
+  next_timer: usize,
+  clock: Box<dyn Clock>,
+  tweens: Vec<(Index, &'static str, Tween)>,
+  theme: Theme,
+  /// Pairs of observing and observed widgets.
+  watches: Vec<(Index, Index)>,
+  /// Observers along with the widgets they observe whose data changed
+  /// since they were last notified.
+  changes: Vec<(Index, Vec<Index>)>,
+  culled: Cell<usize>,
 }
 
 impl<E, M> Ui<E, M> {
@@ -378,31 +856,63 @@ impl<E, M> Ui<E, M> {
       hooker: &NOT_HOOKED,
       hooked: Default::default(),
       focused: None,
+      tasks: Vec::new(),
+      next_task: 0,
+      timers: Vec::new(),
+      next_timer: 0,
+      clock: Box::new(SystemClock),
+      tweens: Vec::new(),
+      theme: Theme::default(),
+      watches: Vec::new(),
+      changes: Vec::new(),
+      culled: Cell::new(0),
     };
 
-    let id = ui._add_widget(None, new_data, new_root_widget);
+    let id = ui._add_widget(None, None, new_data, new_root_widget);
     debug_assert_eq!(id.idx.idx, 0);
     (ui, id)
   }
 
-  /// Add a widget to the `Ui`.
+  /// Add a widget to the `Ui`, retrieving a [`TypedId`] for it.
   ///
-  /// This method fulfills the exact same purpose as
-  /// `MutCap::add_widget`, but it does not require boxing up the
-  /// provided `FnOnce`.
-  // TODO: This method should be removed once we no longer require
-  //       boxing up of `FnOnce` closures.
-  pub fn add_ui_widget<D, W>(&mut self, parent: Id, new_data: D, new_widget: W) -> Id
+  /// This method fulfills the same purpose as `MutCap::add_widget`,
+  /// but it does not require boxing up the provided `FnOnce` and it
+  /// captures the types of the widget and its data. The resulting
+  /// [`TypedId`] can be used for accessing the data without
+  /// downcasting, via `get` and `get_mut` on [`Cap`] and [`MutCap`]
+  /// trait objects, respectively. Errors reported by `try_data` and
+  /// `try_data_mut` include the name of the actual data type for
+  /// widgets added this way.
+  pub fn add_ui_widget<W, D, FD, FW>(
+    &mut self,
+    parent: Id,
+    new_data: FD,
+    new_widget: FW,
+  ) -> TypedId<W, D>
   where
-    D: FnOnce() -> Box<dyn Any>,
-    W: FnOnce(Id, &mut dyn MutCap<E, M>) -> Box<dyn Widget<E, M>>,
+    W: Widget<E, M> + 'static,
+    D: 'static,
+    FD: FnOnce() -> D,
+    FW: FnOnce(Id, &mut dyn MutCap<E, M>) -> W,
   {
     let parent_idx = self.validate(parent);
-    self._add_widget(Some(parent_idx), new_data, new_widget)
+    let id = self._add_widget(
+      Some(parent_idx),
+      Some(type_name::<D>()),
+      || Box::new(new_data()),
+      |id, cap| Box::new(new_widget(id, cap)),
+    );
+    TypedId::new(id)
   }
 
   /// Add a widget to the `Ui`.
-  fn _add_widget<D, W>(&mut self, parent_idx: Option<Index>, new_data: D, new_widget: W) -> Id
+  fn _add_widget<D, W>(
+    &mut self,
+    parent_idx: Option<Index>,
+    data_type: Option<&'static str>,
+    new_data: D,
+    new_widget: W,
+  ) -> Id
   where
     D: FnOnce() -> Box<dyn Any>,
     W: FnOnce(Id, &mut dyn MutCap<E, M>) -> Box<dyn Widget<E, M>>,
@@ -414,7 +924,7 @@ impl<E, M> Ui<E, M> {
     // placeholder in its stead.
     let dummy = Placeholder;
     let data = new_data();
-    let data = WidgetData::new(parent_idx, data);
+    let data = WidgetData::new(parent_idx, data, data_type);
     self.widgets.push((data, Rc::new(dummy)));
 
     // The widget is already linked to its parent but the parent needs to
@@ -425,6 +935,7 @@ impl<E, M> Ui<E, M> {
     if let Some(parent_idx) = parent_idx {
       self.widgets[parent_idx.idx].0.children.push(id)
     }
+    self.invalidate_layout(parent_idx);
 
     // TODO: Consider making NewWidgetFn return an Rc instead of a Box
     //       to begin with as Rc::from(Box) is a non-trivial operation.
@@ -440,6 +951,16 @@ impl<E, M> Ui<E, M> {
     id
   }
 
+  /// Convert a `Ui` independent widget index, as retrieved via
+  /// `Id::index`, into an `Id` for this `Ui`.
+  pub(crate) fn id_from_index(&self, idx: usize) -> Option<Id> {
+    if idx < self.widgets.len() {
+      Some(Id::new(idx, self))
+    } else {
+      None
+    }
+  }
+
   /// Validate an `Id`, converting it into the internally used `Index`.
   #[inline]
   fn validate(&self, id: Id) -> Index {
@@ -457,7 +978,7 @@ impl<E, M> Ui<E, M> {
     self.widgets[idx.idx].0.children.iter()
   }
 
-  /// Show the widget with the given `Index` and recursively all its parents.
+  /// Show the widget with the given `Index` and all its parents.
   ///
   /// Note that the given reordering function needs to be idempotent
   /// with respect to repeated reordering of the same widgets.
@@ -465,16 +986,21 @@ impl<E, M> Ui<E, M> {
   where
     F: Fn(&mut Ui<E, M>, Index),
   {
-    // Always run before making the widget visible. The reorder function
-    // may check for visibility internally and relies in the value being
-    // that before the change.
-    reorder_fn(self, idx);
-
-    let data = &mut self.widgets[idx.idx].0;
-    data.visible = true;
-
-    if let Some(parent_idx) = data.parent_idx {
-      self.show(parent_idx, reorder_fn)
+    let mut next = Some(idx);
+    while let Some(idx) = next {
+      // Always run before making the widget visible. The reorder
+      // function may check for visibility internally and relies in the
+      // value being that before the change.
+      reorder_fn(self, idx);
+
+      let data = &mut self.widgets[idx.idx].0;
+      let parent_idx = data.parent_idx;
+      if !data.visible {
+        data.visible = true;
+        data.dirty.set(true);
+        self.invalidate_layout(parent_idx);
+      }
+      next = parent_idx;
     }
   }
 
@@ -518,8 +1044,15 @@ impl<E, M> Ui<E, M> {
   }
 
   fn is_displayed(&self, idx: Index) -> bool {
-    let data = &self.widgets[idx.idx].0;
-    data.visible && data.parent_idx.map_or(true, |x| self.is_displayed(x))
+    let mut next = Some(idx);
+    while let Some(idx) = next {
+      let data = &self.widgets[idx.idx].0;
+      if !data.visible {
+        return false
+      }
+      next = data.parent_idx;
+    }
+    true
   }
 
   fn is_top_most_child(&self, idx: Index) -> bool {
@@ -537,7 +1070,14 @@ impl<E, M> Ui<E, M> {
     // We want to provide the invariant that a focused widget needs to
     // be visible.
     self.show(idx, Ui::reorder_as_focused);
-    self.focused = Some(idx);
+
+    if self.focused != Some(idx) {
+      if let Some(focused) = self.focused {
+        self.widgets[focused.idx].0.dirty.set(true);
+      }
+      self.widgets[idx.idx].0.dirty.set(true);
+      self.focused = Some(idx);
+    }
   }
 
   /// Render the `Ui` with the given `Renderer`.
@@ -546,34 +1086,891 @@ impl<E, M> Ui<E, M> {
     // when rendering, because we need to take parent-child
     // relationships into account in case widgets cover each other.
     let idx = self.validate(self.root_id());
-    let root = self.lookup(idx);
     let bbox = renderer.renderable_area();
 
+    for (data, _) in &self.widgets {
+      data.measured.set(None);
+      data.unclipped.set(None);
+      data.bbox.set(None);
+      data.rendered_offset.set(None);
+      data.inner_bbox.set(None);
+    }
+
+    let _ = self.measure_all(idx, renderer, bbox.size(), bbox.size());
+    self.widgets[idx.idx].0.unclipped.set(Some(bbox.cast()));
+
+    self.culled.set(0);
     renderer.pre_render();
-    self.render_all(idx, root, renderer, bbox);
+    let mut portals = Vec::new();
+    let () = self.render_all(idx, renderer, bbox, bbox, &mut portals);
+    let () = self.render_portals(portals, renderer, bbox);
+    renderer.post_render();
+    self.clear_dirty();
+  }
+
+  /// Render only those parts of the `Ui` that got invalidated since the
+  /// last time it was rendered.
+  ///
+  /// Each invalidated widget is rendered along with all its children,
+  /// reusing the bounding box it got assigned during the previous pass.
+  /// Siblings with a higher z-index that overlap a re-rendered area
+  /// are rendered as well, so that they stay on top. Note that
+  /// [`Renderer::render_done`] is not invoked for the parents of
+  /// re-rendered widgets and that the pass is started with
+  /// [`Renderer::pre_render_partial`] instead of
+  /// [`Renderer::pre_render`].
+  ///
+  /// Invalidated widgets and their parents are measured anew before
+  /// rendering. Should the size of a widget change, its parent gets
+  /// invalidated in case it has a layout attached.
+  ///
+  /// If the root widget got invalidated or the renderable area changed
+  /// since the previous pass, the `Ui` is rendered in its entirety, as
+  /// [`Ui::render`] would.
+  pub fn render_dirty(&self, renderer: &dyn Renderer) {
+    if !Cap::needs_render(self) {
+      return
+    }
+
+    let root = &self.widgets[0].0;
+    let bbox = renderer.renderable_area();
+    if root.dirty.get() || root.bbox.get() != Some(bbox) {
+      return self.render(renderer)
+    }
+
+    let pending = self.pending();
+    let () = self.measure_dirty(Index::new(0), &pending, renderer, bbox.size());
+
+    // Re-measuring may have invalidated the root widget's layout.
+    if root.dirty.get() {
+      return self.render(renderer)
+    }
+
+    self.culled.set(0);
+    renderer.pre_render_partial();
+    let damaged = self.render_damaged(Index::new(0), &pending, renderer, bbox);
+    let () = self.render_damaged_portals(&pending, renderer, bbox, damaged);
     renderer.post_render();
+    self.clear_dirty();
+  }
+
+  /// Retrieve the number of widgets culled during the last render pass.
+  ///
+  /// A widget is culled if its bounding box does not intersect the
+  /// region visible to it, i.e., the intersection of the renderable
+  /// area and the inner bounding boxes of all its parents. Neither the
+  /// widget nor any of its children are rendered in that case. Only the
+  /// culled widget itself is counted, not its children.
+  pub fn culled_count(&self) -> usize {
+    self.culled.get()
+  }
+
+  /// Flag all widgets that have an invalidated descendant, so that we
+  /// know which subtrees to descend into.
+  fn pending(&self) -> Vec<bool> {
+    let mut pending = vec![false; self.widgets.len()];
+    for (data, _) in self.widgets.iter().filter(|(data, _)| data.dirty.get()) {
+      let mut parent_idx = data.parent_idx;
+      while let Some(idx) = parent_idx {
+        if pending[idx.idx] {
+          break
+        }
+        pending[idx.idx] = true;
+        parent_idx = self.widgets[idx.idx].0.parent_idx;
+      }
+    }
+    pending
+  }
+
+  /// Measure the given widget and its children, returning whether the
+  /// widget's size changed.
+  fn measure_all(&self, idx: Index, renderer: &dyn Renderer, available: Size, area: Size) -> bool {
+    let mut changed = false;
+    // Children are measured first, so that their parent can take their
+    // sizes into account. Hence, widgets are visited twice: once to
+    // schedule their children and once to measure them.
+    let mut stack = vec![(idx, false, available)];
+
+    while let Some((next, visited, available)) = stack.pop() {
+      let visible = self.is_visible(next);
+      if visible && !visited {
+        let () = stack.push((next, true, available));
+        let children = self.children(next).zip(self.allot(next, available, area)).rev();
+        let () = stack.extend(
+          children.map(|(child_id, allotted)| (self.validate(*child_id), false, allotted)),
+        );
+        continue
+      }
+
+      let size = visible.then(|| self.lookup(next).measure(self, renderer, available));
+      let data = &self.widgets[next.idx].0;
+      let size_changed = data.measured.replace(size) != size;
+      if next == idx {
+        changed = size_changed;
+      }
+    }
+    changed
+  }
+
+  /// Measure all invalidated widgets as well as their parents.
+  fn measure_dirty(&self, idx: Index, pending: &[bool], renderer: &dyn Renderer, area: Size) {
+    let mut stack = vec![(idx, false, area)];
+
+    while let Some((next, visited, available)) = stack.pop() {
+      if !self.is_visible(next) {
+        continue
+      }
+
+      let data = &self.widgets[next.idx].0;
+      let changed = if visited {
+        let size = Some(self.lookup(next).measure(self, renderer, available));
+        data.measured.replace(size) != size
+      } else if data.dirty.get() {
+        self.measure_all(next, renderer, available, area)
+      } else if pending[next.idx] {
+        // Measure the children first and then revisit the widget.
+        let () = stack.push((next, true, available));
+        let children = self.children(next).zip(self.allot(next, available, area)).rev();
+        let () = stack.extend(
+          children.map(|(child_id, allotted)| (self.validate(*child_id), false, allotted)),
+        );
+        false
+      } else {
+        false
+      };
+
+      if changed {
+        self.invalidate_layout(data.parent_idx);
+      }
+    }
+  }
+
+  /// Determine the space available to the children of the widget with
+  /// the given `Index` during the measure pass, given the space
+  /// available to the widget itself and the renderable `area`.
+  ///
+  /// Sizes are reported in the order of the widget's children.
+  fn allot(&self, idx: Index, available: Size, area: Size) -> Vec<Size> {
+    let data = &self.widgets[idx.idx].0;
+    let available = data
+      .viewport
+      .map_or(available, |viewport| viewport.content);
+    let allotted = match &data.layout {
+      Some(layout) => layout.allot(self, Id::new(idx.idx, self), available),
+      None => Vec::new(),
+    };
+
+    self
+      .children(idx)
+      .map(|child| {
+        let allotted = allotted.iter().find(|(id, _)| id == child);
+        match allotted {
+          Some((_, size)) if self.widgets[child.idx.idx].0.portal => *size,
+          Some((_, size)) => Size {
+            w: size.w.min(available.w),
+            h: size.h.min(available.h),
+          },
+          // Portals not placed by a layout cover the entire renderable
+          // area.
+          None if self.widgets[child.idx.idx].0.portal => area,
+          None => available,
+        }
+      })
+      .collect()
+  }
+
+  /// Render the invalidated descendants of the given widget as well as
+  /// those overlapping them, returning the areas that got rendered.
+  ///
+  /// Portals are skipped.
+  fn render_damaged(
+    &self,
+    idx: Index,
+    pending: &[bool],
+    renderer: &dyn Renderer,
+    area: BBox,
+  ) -> Vec<BBox> {
+    /// The state of a widget whose children are being inspected.
+    struct Frame {
+      /// The widget's inner bounding box.
+      inner_bbox: BBox,
+      /// The region visible to the widget's children.
+      region: BBox,
+      /// The widget's children along with their placements, in the
+      /// reverse order in which they are to be inspected.
+      children: Vec<(Index, BBox<i64>)>,
+      /// The areas that got re-rendered so far.
+      damaged: Vec<BBox>,
+    }
+
+    let frame = |idx: Index, region: BBox| {
+      let inner_bbox = match self.widgets[idx.idx].0.inner_bbox.get() {
+        Some(bbox) if !bbox.is_empty() => bbox,
+        _ => return None,
+      };
+      let placements = self.place_children(idx, inner_bbox);
+      let children = self
+        .children(idx)
+        .zip(placements)
+        .map(|(child_id, placement)| (self.validate(*child_id), placement))
+        .collect();
+
+      Some(Frame {
+        inner_bbox,
+        region: region.intersect(&inner_bbox),
+        children,
+        damaged: Vec::new(),
+      })
+    };
+
+    let mut stack = frame(idx, area).into_iter().collect::<Vec<_>>();
+    while let Some(top) = stack.last_mut() {
+      // Just as when rendering everything, we have to start with the
+      // widget with the lowest z-index, which is the last child.
+      let (child_idx, placement) = match top.children.pop() {
+        Some(child) => child,
+        None => {
+          // All children got inspected. Areas re-rendered in this
+          // subtree count as damaged for the parent as well.
+          // SANITY: We just peeked at the top frame.
+          let done = stack.pop().unwrap();
+          match stack.last_mut() {
+            Some(parent) => parent.damaged.extend(done.damaged),
+            None => return done.damaged,
+          }
+          continue
+        },
+      };
+
+      if !self.is_visible(child_idx) {
+        continue
+      }
+
+      let data = &self.widgets[child_idx.idx].0;
+      if data.portal {
+        data.unclipped.set(Some(placement));
+        continue
+      }
+
+      // Widgets that were not rendered previously receive the bounding
+      // box assigned to them by their parent.
+      let bbox = data.bbox.get().unwrap_or_else(|| {
+        data.unclipped.set(Some(placement));
+        clip(placement, top.inner_bbox)
+      });
+      if bbox.is_empty() {
+        continue
+      }
+
+      if data.dirty.get() || top.damaged.iter().any(|damaged| damaged.overlaps(&bbox)) {
+        if bbox.overlaps(&top.region) {
+          // Portals are taken care of separately.
+          let () = self.render_all(child_idx, renderer, bbox, top.region, &mut Vec::new());
+          let () = top.damaged.push(bbox);
+        } else {
+          data.bbox.set(None);
+          self.culled.set(self.culled.get() + 1);
+        }
+      } else if pending[child_idx.idx] {
+        if let Some(frame) = frame(child_idx, top.region) {
+          let () = stack.push(frame);
+        }
+      }
+    }
+    Vec::new()
+  }
+
+  /// Render the given portals, along with all portals contained in
+  /// them, on top of everything else.
+  fn render_portals(&self, mut portals: Vec<Index>, renderer: &dyn Renderer, area: BBox) {
+    let mut i = 0;
+    while let Some(idx) = portals.get(i).copied() {
+      i += 1;
+
+      // SANITY: Portals are only collected once placed.
+      let bbox = clip(self.widgets[idx.idx].0.unclipped.get().unwrap(), area);
+      if bbox.overlaps(&area) {
+        let () = self.render_all(idx, renderer, bbox, area, &mut portals);
+      } else {
+        self.culled.set(self.culled.get() + 1);
+      }
+    }
+  }
+
+  /// Render the invalidated portals and their descendants as well as
+  /// those overlapping them or any of the `damaged` areas.
+  fn render_damaged_portals(
+    &self,
+    pending: &[bool],
+    renderer: &dyn Renderer,
+    area: BBox,
+    mut damaged: Vec<BBox>,
+  ) {
+    for idx in self.portals() {
+      let data = &self.widgets[idx.idx].0;
+      let bbox = match data.unclipped.get() {
+        Some(placement) => clip(placement, area),
+        None => continue,
+      };
+
+      // A portal may have been moved by a re-rendered parent.
+      if data.dirty.get()
+        || data.bbox.get() != Some(bbox)
+        || damaged.iter().any(|damaged| damaged.overlaps(&bbox))
+      {
+        if bbox.overlaps(&area) {
+          let () = self.render_all(idx, renderer, bbox, area, &mut Vec::new());
+          let () = damaged.push(bbox);
+        } else {
+          data.bbox.set(None);
+          self.culled.set(self.culled.get() + 1);
+        }
+      } else if pending[idx.idx] {
+        let () = damaged.extend(self.render_damaged(idx, pending, renderer, area));
+      }
+    }
+  }
+
+  /// Collect the displayed portals whose parents got rendered, in the
+  /// order in which they are rendered.
+  fn portals(&self) -> Vec<Index> {
+    // The root widget acts as the first "portal", containing all
+    // others.
+    let mut portals = vec![Index::new(0)];
+    let mut i = 0;
+
+    while let Some(root) = portals.get(i).copied() {
+      i += 1;
+
+      let mut stack = vec![root];
+      while let Some(idx) = stack.pop() {
+        match self.widgets[idx.idx].0.inner_bbox.get() {
+          Some(inner_bbox) if !inner_bbox.is_empty() => (),
+          _ => continue,
+        }
+
+        let start = portals.len();
+        for child_id in self.children(idx) {
+          let child_idx = self.validate(*child_id);
+          let data = &self.widgets[child_idx.idx].0;
+          if !data.visible {
+            continue
+          }
+
+          if data.portal {
+            let () = portals.push(child_idx);
+          } else if data.bbox.get().is_some() {
+            let () = stack.push(child_idx);
+          }
+        }
+        // Siblings are rendered starting with the lowest z-index.
+        let () = portals[start..].reverse();
+      }
+    }
+
+    let _root = portals.remove(0);
+    portals
+  }
+
+  /// Retrieve the top-most visible widget in the given subtree that
+  /// contains the given point.
+  fn hit_test_tree(&self, idx: Index, point: Point) -> Option<Index> {
+    let contains = |idx: Index| {
+      let data = &self.widgets[idx.idx].0;
+      data.visible && data.bbox.get().map_or(false, |bbox| bbox.contains(point))
+    };
+
+    if !contains(idx) {
+      return None
+    }
+
+    let mut hit = idx;
+    'descend: loop {
+      // Children are checked in z-order, starting with the top-most.
+      for child_id in self.children(hit) {
+        let child_idx = self.validate(*child_id);
+        if !self.widgets[child_idx.idx].0.portal && contains(child_idx) {
+          hit = child_idx;
+          continue 'descend
+        }
+      }
+      break Some(hit)
+    }
   }
 
-  /// Recursively render the given widget and its children.
-  fn render_all(&self, idx: Index, widget: &dyn Widget<E, M>, renderer: &dyn Renderer, bbox: BBox) {
-    if self.is_visible(idx) {
-      // TODO: Ideally we would want to go without the recursion stuff we
-      //       have. This may not be possible (efficiently) with safe
-      //       Rust, though. Not sure.
-      let inner_bbox = widget.render(self, renderer, bbox);
+  /// Invalidate the widget with the given `Index` if it has a layout
+  /// attached, as the bounding boxes of its children may have changed.
+  fn invalidate_layout(&self, idx: Option<Index>) {
+    if let Some(idx) = idx {
+      let data = &self.widgets[idx.idx].0;
+      if data.layout.is_some() {
+        data.dirty.set(true);
+      }
+    }
+  }
+
+  /// Determine the unclipped bounding boxes of the children of the
+  /// widget with the given `Index`, in z-order.
+  ///
+  /// Children receive the bounding boxes assigned by the widget's
+  /// layout, if any, and the entire available area otherwise. For a
+  /// widget with a viewport, the available area is the content area,
+  /// which gets moved according to the scroll offset. Portals are not
+  /// confined to the available area and receive the root widget's
+  /// bounding box in the absence of a layout.
+  fn place_children(&self, idx: Index, inner_bbox: BBox) -> Vec<BBox<i64>> {
+    let data = &self.widgets[idx.idx].0;
+    let (area, dx, dy) = match data.viewport {
+      Some(viewport) => (
+        BBox::new(Point::default(), viewport.content),
+        i64::from(inner_bbox.x) - i64::from(viewport.offset.x),
+        i64::from(inner_bbox.y) - i64::from(viewport.offset.y),
+      ),
+      None => (inner_bbox, 0, 0),
+    };
+    let layout = match &data.layout {
+      Some(layout) => layout.layout(self, Id::new(idx.idx, self), area),
+      None => Vec::new(),
+    };
+
+    self
+      .children(idx)
+      .map(|child| {
+        let placed = layout.iter().find(|(id, _)| id == child);
+        if self.widgets[child.idx.idx].0.portal {
+          return match placed {
+            Some((_, bbox)) => bbox.cast::<i64>().translate(dx, dy),
+            None => self.widgets[0].0.bbox.get().unwrap_or_default().cast::<i64>(),
+          }
+        }
+
+        // Children are never allowed to exceed the area available to
+        // them.
+        placed
+          .map_or(area, |(_, bbox)| bbox.intersect(&area))
+          .cast::<i64>()
+          .translate(dx, dy)
+      })
+      .collect()
+  }
+
+  /// Clear the invalidation state of all widgets.
+  fn clear_dirty(&self) {
+    for (data, _) in &self.widgets {
+      data.dirty.set(false);
+    }
+  }
+
+  /// Render the given widget and its children.
+  ///
+  /// Rendering happens in depth-first order, with a widget's children
+  /// being rendered in between the invocations of its `render` and
+  /// `render_done` methods. We use an explicit stack instead of
+  /// recursion, so that the depth of the widget tree is not limited by
+  /// the size of the call stack.
+  ///
+  /// `region` is the region visible to the widget. Children whose
+  /// bounding box does not intersect the region visible to them are
+  /// culled. Portals encountered are not rendered but added to
+  /// `portals`, in the order in which they are to be rendered.
+  fn render_all(
+    &self,
+    idx: Index,
+    renderer: &dyn Renderer,
+    bbox: BBox,
+    region: BBox,
+    portals: &mut Vec<Index>,
+  ) {
+    /// A step to perform while rendering.
+    enum Step {
+      /// Render the widget with the given `Index` and schedule its
+      /// children.
+      Render(Index, BBox, BBox),
+      /// Conclude rendering of the widget with the given `Index`.
+      Done(Index, BBox),
+    }
+
+    let mut stack = vec![Step::Render(idx, bbox, region)];
+    while let Some(step) = stack.pop() {
+      let (idx, bbox, region) = match step {
+        Step::Render(idx, bbox, region) => (idx, bbox, region),
+        Step::Done(idx, bbox) => {
+          let () = self.lookup(idx).render_done(self, renderer, bbox);
+          continue
+        },
+      };
+
+      if !self.is_visible(idx) {
+        continue
+      }
+
+      let data = &self.widgets[idx.idx].0;
+      data.bbox.set(Some(bbox));
+      let inner_bbox = self.lookup(idx).render(self, renderer, bbox);
+      data.inner_bbox.set(Some(inner_bbox));
+      data.rendered_offset.set(data.viewport.map(|viewport| viewport.offset));
+      let () = stack.push(Step::Done(idx, bbox));
+
+      if !inner_bbox.is_empty() {
+        let placements = self.place_children(idx, inner_bbox);
+        let region = region.intersect(&inner_bbox);
+
+        let start = portals.len();
 
-      if inner_bbox.w != 0 && inner_bbox.h != 0 {
         // We start rendering with the widget with the lowest z-index,
-        // i.e., the one the furthest in the background.
-        for child_id in self.children(idx).rev() {
+        // i.e., the one the furthest in the background. Because steps
+        // are popped off the stack, we push children in z-order.
+        for (child_id, placement) in self.children(idx).zip(placements) {
           let child_idx = self.validate(*child_id);
-          let child = self.lookup(child_idx);
-          self.render_all(child_idx, child, renderer, inner_bbox)
+          let child = &self.widgets[child_idx.idx].0;
+          child.unclipped.set(Some(placement));
+
+          if child.portal {
+            if child.visible {
+              let () = portals.push(child_idx);
+            }
+            continue
+          }
+
+          let child_bbox = clip(placement, inner_bbox);
+          if child_bbox.overlaps(&region) {
+            let () = stack.push(Step::Render(child_idx, child_bbox, region));
+          } else if child.visible {
+            child.bbox.set(None);
+            self.culled.set(self.culled.get() + 1);
+          }
         }
+        let () = portals[start..].reverse();
+      }
+    }
+  }
+
+  /// Retrieve a mutable reference to the data of a widget added via
+  /// [`Ui::add_ui_widget`].
+  ///
+  /// This method fulfills the exact same purpose as the `get_mut`
+  /// method available on [`MutCap`] trait objects.
+  ///
+  /// # Panics
+  ///
+  /// This method panics if the [`TypedId`] was handed out by a
+  /// different `Ui`.
+  pub fn get_mut<W, D>(&mut self, widget: TypedId<W, D>) -> &mut D
+  where
+    D: 'static,
+  {
+    (self as &mut dyn MutCap<E, M>).get_mut(widget)
+  }
+
+  /// Retrieve a mutable reference to a widget's data, if they are of
+  /// type `D`.
+  ///
+  /// This method fulfills the exact same purpose as the `try_data_mut`
+  /// method available on [`MutCap`] trait objects.
+  pub fn try_data_mut<D>(&mut self, widget: Id) -> std::result::Result<&mut D, DataError>
+  where
+    D: 'static,
+  {
+    (self as &mut dyn MutCap<E, M>).try_data_mut(widget)
+  }
+
+  /// Send a typed query to the given widget and retrieve its response.
+  ///
+  /// This method fulfills the exact same purpose as the `query` method
+  /// available on [`MutCap`] trait objects.
+  pub async fn query<Q>(
+    &mut self,
+    widget: Id,
+    query: Q,
+  ) -> std::result::Result<Q::Response, QueryError>
+  where
+    Q: Query,
+  {
+    crate::query::query(self, widget, query).await
+  }
+
+  /// Check whether there are any pending tasks.
+  pub fn has_tasks(&self) -> bool {
+    !self.tasks.is_empty()
+  }
+
+  /// Drive the tasks spawned via [`MutCap::spawn`].
+  ///
+  /// This method waits until at least one of the pending tasks has
+  /// completed and sends the output of each completed task as a message
+  /// to the widget that spawned it. It returns the number of tasks that
+  /// completed, which is zero only if no tasks are pending.
+  pub async fn run_tasks(&mut self) -> usize {
+    let completed = self.poll_tasks().await;
+    let count = completed.len();
+    let completed = completed
+      .into_iter()
+      .map(|(_, widget, message)| (widget, message))
+      .collect();
+    let () = self.complete_tasks(completed).await;
+    count
+  }
+
+  /// Wait until at least one of the pending tasks has completed,
+  /// returning the sequence number, the widget, and the output of each
+  /// completed task.
+  pub(crate) async fn poll_tasks(&mut self) -> Vec<(usize, Id, M)> {
+    let completed = PollTasks {
+      tasks: &mut self.tasks,
+    }
+    .await;
+
+    completed
+      .into_iter()
+      .map(|(task, idx, message)| (task, Id::new(idx.idx, self), message))
+      .collect()
+  }
+
+  /// Remove the pending task with the given sequence number, returning
+  /// the widget it belongs to.
+  pub(crate) fn take_task(&mut self, task: usize) -> Option<Id> {
+    let pos = self.tasks.iter().position(|t| t.id == task)?;
+    let task = self.tasks.remove(pos);
+    Some(Id::new(task.idx.idx, self))
+  }
+
+  /// Send the outputs of completed tasks to the widgets the tasks
+  /// belonged to.
+  pub(crate) async fn complete_tasks(&mut self, completed: Vec<(Id, M)>) {
+    for (widget, message) in completed {
+      let idx = self.validate(widget);
+      let widget = self.widgets[idx.idx].1.clone();
+      // The task has no way to receive a response to the message, so
+      // just drop it.
+      let _ = widget.react(message, self).await;
+    }
+    let _ = self.notify_observers().await;
+  }
+
+  /// Inform observers about changes to the data of the widgets they
+  /// watch, returning the number of observers notified.
+  ///
+  /// Each observer is notified once, with all changes accumulated since
+  /// it was last notified, and gets invalidated. Changes caused by
+  /// observers while being notified are delivered to other observers
+  /// right away, but to observers already notified only with the next
+  /// invocation. That way, every observer is notified at most once.
+  ///
+  /// This method is invoked automatically at the end of [`Ui::handle`],
+  /// [`Ui::fire_due`], and [`Ui::run_tasks`]. Changes made through the
+  /// `Ui` directly, outside of any handler, require an explicit
+  /// invocation.
+  pub async fn notify_observers(&mut self) -> usize {
+    let mut notified = Vec::new();
+
+    loop {
+      let (batch, deferred) = self
+        .changes
+        .drain(..)
+        .partition::<Vec<_>, _>(|(observer, _)| !notified.contains(observer));
+      self.changes = deferred;
+
+      if batch.is_empty() {
+        break notified.len()
+      }
+
+      for (observer, changed) in batch {
+        let changed = changed
+          .into_iter()
+          .map(|idx| Id::new(idx.idx, self))
+          .collect::<Vec<_>>();
+        let () = notified.push(observer);
+        self.widgets[observer.idx].0.dirty.set(true);
+
+        let widget = self.widgets[observer.idx].1.clone();
+        let () = widget.observe(&changed, self).await;
+      }
+    }
+  }
+
+  /// Record a change to the data of the widget with the given `Index`
+  /// for all its observers.
+  fn record_change(&mut self, idx: Index) {
+    for (observer, _) in self.watches.iter().filter(|(_, observed)| *observed == idx) {
+      match self.changes.iter_mut().find(|(idx, _)| idx == observer) {
+        Some((_, changed)) => {
+          if !changed.contains(&idx) {
+            let () = changed.push(idx);
+          }
+        },
+        None => self.changes.push((*observer, vec![idx])),
+      }
+    }
+  }
+
+  /// Set the [`Clock`] used by the `Ui`.
+  ///
+  /// By default, a [`SystemClock`] is used.
+  pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+    self.clock = clock
+  }
+
+  /// Retrieve the current time, as reported by the `Ui`'s [`Clock`].
+  pub fn now(&self) -> Instant {
+    self.clock.now()
+  }
+
+  /// Retrieve the deadline of the timer expiring next, if any.
+  pub fn next_deadline(&self) -> Option<Instant> {
+    self.timers.iter().map(|timer| timer.deadline).min()
+  }
+
+  /// Fire all timers that expired at the given point in time.
+  ///
+  /// Messages are delivered in order of the timers' deadlines. The
+  /// method returns the number of messages sent.
+  pub async fn fire_due(&mut self, now: Instant) -> usize {
+    let mut due = self
+      .timers
+      .iter()
+      .filter(|timer| timer.deadline <= now)
+      .map(|timer| (timer.deadline, timer.id.0))
+      .collect::<Vec<_>>();
+    due.sort_unstable();
+
+    let mut count = 0;
+    for (_, id) in due {
+      // A timer may have been cancelled by a previously invoked
+      // handler. Skip it in that case.
+      let pos = match self.timers.iter().position(|timer| timer.id.0 == id) {
+        Some(pos) => pos,
+        None => continue,
+      };
+
+      let timer = &mut self.timers[pos];
+      let idx = timer.idx;
+      let message = match timer.repeat {
+        Some((interval, clone)) => {
+          let message = clone(timer.message.as_ref().unwrap());
+          timer.deadline += interval;
+          if timer.deadline <= now {
+            timer.deadline = now + interval;
+          }
+          message
+        },
+        None => self.timers.remove(pos).message.unwrap(),
+      };
+
+      let widget = self.widgets[idx.idx].1.clone();
+      let _ = widget.react(message, self).await;
+      count += 1;
+    }
+    let _ = self.notify_observers().await;
+    count
+  }
+
+  /// Advance all animations to the given point in time.
+  ///
+  /// Widgets with an animation in progress get invalidated. Animations
+  /// that concluded during a previous invocation are removed. The
+  /// method returns whether further frames are needed, i.e., whether
+  /// any animation is still in progress afterwards.
+  pub fn tick(&mut self, now: Instant) -> bool {
+    // Animations that concluded have had their final value rendered by
+    // now, so drop them instead of keeping them around forever.
+    let () = self.tweens.retain(|(_, _, tween)| !tween.is_done());
+
+    let mut animating = false;
+    for (idx, _, tween) in self.tweens.iter_mut() {
+      animating |= tween.advance(now);
+      self.widgets[idx.idx].0.dirty.set(true);
+    }
+    animating
+  }
+
+  /// Check whether any animation is in progress.
+  pub fn is_animating(&self) -> bool {
+    self.tweens.iter().any(|(_, _, tween)| !tween.is_done())
+  }
+
+  /// Add a timer for the widget with the given `Index`.
+  fn add_timer(
+    &mut self,
+    idx: Index,
+    delay: Duration,
+    message: M,
+    repeat: Option<Repeat<M>>,
+  ) -> TimerId {
+    let id = TimerId(self.next_timer);
+    self.next_timer += 1;
+
+    // Hiding a widget cancels its timers, so do not even bother
+    // creating one for a widget that is not displayed.
+    if !self.is_displayed(idx) {
+      return id
+    }
+
+    let timer = Timer {
+      id,
+      idx,
+      deadline: self.clock.now() + delay,
+      message: Some(message),
+      repeat,
+    };
+    self.timers.push(timer);
+    id
+  }
+
+  /// Cancel all timers and tasks of the widget with the given `Index`
+  /// and all its descendants.
+  fn cancel_pending(&mut self, idx: Index) {
+    if self.timers.is_empty() && self.tasks.is_empty() {
+      return
+    }
+
+    let mut cancel = Vec::new();
+    let mut stack = vec![idx];
+
+    while let Some(idx) = stack.pop() {
+      cancel.push(idx);
+      stack.extend(self.children(idx).map(|child| child.idx));
+    }
+
+    self.timers.retain(|timer| !cancel.contains(&timer.idx));
+    self.tasks.retain(|task| !cancel.contains(&task.idx))
+  }
+
+  /// Create a textual representation of the widget tree.
+  ///
+  /// Each widget is represented by a line containing its [`Id`], its
+  /// type name, and its visibility and focus state. Children are listed
+  /// (indented) below their parent, in z-order. The representation is
+  /// independent of the particular `Ui` object, meaning that two `Ui`
+  /// objects in the same state produce the same output.
+  pub fn dump(&self) -> String {
+    let mut dump = String::new();
+    let mut stack = vec![(Index::new(0), 0)];
+
+    while let Some((idx, depth)) = stack.pop() {
+      let widget = self.lookup(idx);
+      dump.push_str(&format!("{:indent$}{} {}", "", idx, widget.type_name(), indent = depth * 2));
+
+      if !self.is_visible(idx) {
+        dump.push_str(" hidden");
+      }
+      if self.focused == Some(idx) {
+        dump.push_str(" focused");
+      }
+      if self.widgets[idx.idx].0.portal {
+        dump.push_str(" portal");
       }
+      dump.push('\n');
 
-      let () = widget.render_done(self, renderer, bbox);
+      stack.extend(self.children(idx).rev().map(|child| (child.idx, depth + 1)));
     }
+    dump
+  }
+
+  /// Retrieve the name of the type of the given widget.
+  #[cfg(feature = "serde")]
+  pub(crate) fn widget_type_name(&self, widget: Id) -> &'static str {
+    let idx = self.validate(widget);
+    self.lookup(idx).type_name()
   }
 
   /// Handle an event.
@@ -596,45 +1993,30 @@ impl<E, M> Ui<E, M> {
     // but just returned.
     let unhandled = self.try_handle_event(idx, event).await;
 
-    self.hooker.invoke(self, hook_event, unhandled, None).await
+    let event = self.hooker.invoke(self, hook_event, unhandled, None).await;
+    let _ = self.notify_observers().await;
+    event
   }
 
   /// Bubble up an event until it is handled by some `Widget`.
-  fn handle_event(
-    &mut self,
-    idx: Index,
-    event: E,
-  ) -> Pin<Box<dyn Future<Output = Option<E>> + '_>> {
-    Box::pin(async move {
+  async fn try_handle_event(&mut self, mut idx: Option<Index>, mut event: E) -> Option<E> {
+    while let Some(next) = idx {
       // The clone we perform here allows us to decouple the Widget from
       // the Ui, which in turn makes it possible to pass a mutable Ui
       // reference (in the form of a MutCap) to an immutable widget. It is
       // nothing more but a reference count bump, though.
-      let widget = self.widgets[idx.idx].1.clone();
-      let event = widget.handle(self, event).await;
-      let parent_idx = self.widgets[idx.idx].0.parent_idx;
-
-      if let Some(event) = event {
-        self.try_handle_event(parent_idx, event).await
-      } else {
-        // The event got handled.
-        None
-      }
-    })
-  }
-
-  /// Handle an event.
-  async fn try_handle_event(&mut self, idx: Option<Index>, event: E) -> Option<E> {
-    if let Some(idx) = idx {
-      self.handle_event(idx, event).await
-    } else {
-      // There is no receiver for this event. That could have many
-      // reasons, for example, event propagation could have reached the
-      // root widget which does not contain a parent or we were trying
-      // to send an event to the focused widget and no widget had the
-      // focus. In any case, return the event as-is.
-      Some(event)
+      let widget = self.widgets[next.idx].1.clone();
+      // If the widget handled the event we are done.
+      event = widget.handle(self, event).await?;
+      idx = self.widgets[next.idx].0.parent_idx;
     }
+
+    // There is no (further) receiver for this event. That could have
+    // many reasons, for example, event propagation could have reached
+    // the root widget which does not contain a parent or we were trying
+    // to send an event to the focused widget and no widget had the
+    // focus. In any case, return the event as-is.
+    Some(event)
   }
 }
 
@@ -647,6 +2029,12 @@ impl<E, M> Cap for Ui<E, M> {
     self.widgets[idx.idx].0.data.as_ref()
   }
 
+  /// Retrieve the name of the type of a widget's data.
+  fn data_type_name(&self, widget: Id) -> Option<&'static str> {
+    let idx = self.validate(widget);
+    self.widgets[idx.idx].0.data_type
+  }
+
   /// Retrieve an iterator over the children. Iteration happens in
   /// z-order, from highest to lowest.
   fn children(&self, widget: Id) -> ChildIter<'_> {
@@ -697,6 +2085,98 @@ impl<E, M> Cap for Ui<E, M> {
     debug_assert!(result && self.is_top_most_child(idx) || !result);
     result
   }
+
+  /// Retrieve the `TypeId` of the given widget.
+  fn widget_type(&self, widget: Id) -> TypeId {
+    let idx = self.validate(widget);
+    Widget::type_id(self.lookup(idx))
+  }
+
+  /// Check whether any displayed widget got invalidated.
+  fn needs_render(&self) -> bool {
+    self.widgets.iter().enumerate().any(|(idx, (data, _))| {
+      // An invalidated root widget always requires rendering, as it
+      // may just have been hidden.
+      data.dirty.get() && (idx == 0 || self.is_displayed(Index::new(idx)))
+    })
+  }
+
+  /// Retrieve the size of a widget as determined by the last measure
+  /// pass.
+  fn measured_size(&self, widget: Id) -> Option<Size> {
+    let idx = self.validate(widget);
+    self.widgets[idx.idx].0.measured.get()
+  }
+
+  /// Retrieve the viewport attached to a widget, if any.
+  fn viewport(&self, widget: Id) -> Option<Viewport> {
+    let idx = self.validate(widget);
+    self.widgets[idx.idx].0.viewport
+  }
+
+  /// Retrieve the bounding box assigned to a widget during the most
+  /// recent render pass, before clipping.
+  fn unclipped_bbox(&self, widget: Id) -> Option<BBox<i64>> {
+    let idx = self.validate(widget);
+    self.widgets[idx.idx].0.unclipped.get()
+  }
+
+  /// Check whether a widget is a portal.
+  fn is_portal(&self, widget: Id) -> bool {
+    let idx = self.validate(widget);
+    self.widgets[idx.idx].0.portal
+  }
+
+  /// Retrieve the top-most displayed widget containing the given point.
+  fn hit_test(&self, point: Point) -> Option<Id> {
+    let mut portals = self.portals();
+    let () = portals.insert(0, Index::new(0));
+    portals
+      .into_iter()
+      .rev()
+      .find_map(|idx| self.hit_test_tree(idx, point))
+      .map(|idx| Id::new(idx.idx, self))
+  }
+
+  /// Retrieve the current value of an animated property of a widget.
+  fn animated_value(&self, widget: Id, property: &str) -> Option<f64> {
+    let idx = self.validate(widget);
+    self
+      .tweens
+      .iter()
+      .find(|(tween_idx, name, _)| *tween_idx == idx && *name == property)
+      .map(|(_, _, tween)| tween.value())
+  }
+
+  /// Retrieve the effective style of a widget.
+  fn style(&self, widget: Id) -> Style {
+    let mut chain = Vec::new();
+    let mut next = Some(self.validate(widget));
+    while let Some(idx) = next {
+      let () = chain.push(idx);
+      next = self.widgets[idx.idx].0.parent_idx;
+    }
+
+    chain
+      .into_iter()
+      .rev()
+      .fold(self.theme.base().clone(), |style, idx| {
+        let widget_type = Widget::type_id(self.lookup(idx));
+        let style = match self.theme.widget_style(widget_type) {
+          Some(type_style) => type_style.inherit(&style),
+          None => style,
+        };
+        match &self.widgets[idx.idx].0.style {
+          Some(own) => own.inherit(&style),
+          None => style,
+        }
+      })
+  }
+
+  /// Retrieve the theme of the `Ui`.
+  fn theme(&self) -> &Theme {
+    &self.theme
+  }
 }
 
 #[async_trait(?Send)]
@@ -704,7 +2184,17 @@ impl<E, M> MutCap<E, M> for Ui<E, M> {
   /// Retrieve a mutable reference to a widget's data.
   fn data_mut(&mut self, widget: Id) -> &mut dyn Any {
     let idx = self.validate(widget);
-    self.widgets[idx.idx].0.data.as_mut()
+    let () = self.record_change(idx);
+    self.data_mut_untracked(widget)
+  }
+
+  /// Retrieve a mutable reference to a widget's data without reporting
+  /// a change.
+  fn data_mut_untracked(&mut self, widget: Id) -> &mut dyn Any {
+    let idx = self.validate(widget);
+    let data = &mut self.widgets[idx.idx].0;
+    data.dirty.set(true);
+    data.data.as_mut()
   }
 
   /// Add a widget to the `Ui`.
@@ -714,7 +2204,8 @@ impl<E, M> MutCap<E, M> for Ui<E, M> {
     new_data: Box<NewDataFn>,
     new_widget: Box<NewWidgetFn<E, M>>,
   ) -> Id {
-    self.add_ui_widget(parent, new_data, new_widget)
+    let parent_idx = self.validate(parent);
+    self._add_widget(Some(parent_idx), None, new_data, new_widget)
   }
 
   /// Show a widget, i.e., set its and its parents' visibility flag.
@@ -730,7 +2221,19 @@ impl<E, M> MutCap<E, M> for Ui<E, M> {
     }
 
     let idx = self.validate(widget);
-    self.widgets[idx.idx].0.visible = false;
+    let data = &mut self.widgets[idx.idx].0;
+    if data.visible {
+      data.visible = false;
+      // The area covered by the widget has to be rendered anew by its
+      // parent. Portals may cover anything, though.
+      let parent_idx = if data.portal {
+        Index::new(0)
+      } else {
+        data.parent_idx.unwrap_or(idx)
+      };
+      self.widgets[parent_idx.idx].0.dirty.set(true);
+    }
+    self.cancel_pending(idx);
   }
 
   /// Focus a widget.
@@ -739,6 +2242,93 @@ impl<E, M> MutCap<E, M> for Ui<E, M> {
     self.focus(idx)
   }
 
+  /// Invalidate a widget, marking it as in need of rendering.
+  fn invalidate(&mut self, widget: Id) {
+    let idx = self.validate(widget);
+    self.widgets[idx.idx].0.dirty.set(true);
+  }
+
+  /// Attach a layout to a widget or remove it.
+  fn set_layout(&mut self, widget: Id, layout: Option<Box<dyn Layout>>) -> Option<Box<dyn Layout>> {
+    let idx = self.validate(widget);
+    let data = &mut self.widgets[idx.idx].0;
+    data.dirty.set(true);
+    std::mem::replace(&mut data.layout, layout)
+  }
+
+  /// Attach a viewport to a widget or remove it.
+  fn set_viewport(&mut self, widget: Id, viewport: Option<Viewport>) -> Option<Viewport> {
+    let idx = self.validate(widget);
+    let data = &mut self.widgets[idx.idx].0;
+    data.dirty.set(true);
+    std::mem::replace(&mut data.viewport, viewport)
+  }
+
+  /// Mark a widget as a portal or revert it to a regular widget.
+  fn set_portal(&mut self, widget: Id, portal: bool) -> bool {
+    let idx = self.validate(widget);
+    let previous = std::mem::replace(&mut self.widgets[idx.idx].0.portal, portal);
+    if previous != portal {
+      // The widget moves between the regular tree and the overlay,
+      // which affects arbitrary other widgets.
+      self.widgets[0].0.dirty.set(true);
+    }
+    previous
+  }
+
+  /// Scroll all viewports containing the given widget such that it gets
+  /// displayed.
+  fn scroll_into_view(&mut self, widget: Id) {
+    let idx = self.validate(widget);
+    let data = &self.widgets[idx.idx].0;
+    let mut target = match data.unclipped.get() {
+      Some(bbox) => bbox,
+      None => return,
+    };
+    let mut parent_idx = data.parent_idx;
+
+    while let Some(idx) = parent_idx {
+      let data = &mut self.widgets[idx.idx].0;
+      parent_idx = data.parent_idx;
+
+      let (viewport, inner_bbox, rendered) =
+        match (data.viewport.as_mut(), data.inner_bbox.get(), data.rendered_offset.get()) {
+          (Some(viewport), Some(inner_bbox), Some(rendered)) => (viewport, inner_bbox, rendered),
+          _ => continue,
+        };
+
+      // The recorded geometry does not yet reflect scrolling that
+      // happened since the last render pass.
+      let offset = viewport.offset;
+      target = target.translate(
+        i64::from(rendered.x) - i64::from(offset.x),
+        i64::from(rendered.y) - i64::from(offset.y),
+      );
+
+      let inner = inner_bbox.cast::<i64>();
+      let dx = scroll_distance(target.x, target.w, inner.x, inner.w);
+      let dy = scroll_distance(target.y, target.h, inner.y, inner.h);
+      let max_x = i64::from(viewport.content.w.saturating_sub(inner_bbox.w));
+      let max_y = i64::from(viewport.content.h.saturating_sub(inner_bbox.h));
+      let x = (i64::from(offset.x) + dx).clamp(0, max_x);
+      let y = (i64::from(offset.y) + dy).clamp(0, max_y);
+
+      viewport.offset = Point {
+        x: x as u16,
+        y: y as u16,
+      };
+      if viewport.offset != offset {
+        data.dirty.set(true);
+      }
+
+      // Continue with the part of the viewport that contains the
+      // widget.
+      target = target
+        .translate(i64::from(offset.x) - x, i64::from(offset.y) - y)
+        .intersect(&inner);
+    }
+  }
+
   /// Install or remove an event hook handler.
   fn hook_events(
     &mut self,
@@ -791,6 +2381,113 @@ impl<E, M> MutCap<E, M> for Ui<E, M> {
 
     widget.respond(message, self).await
   }
+
+  /// Send the provided type-erased query to the given widget.
+  async fn ask(&mut self, widget: Id, request: &mut Request<'_>) {
+    let idx = self.validate(widget);
+    let widget = self.widgets[idx.idx].1.clone();
+
+    widget.answer(request, self).await
+  }
+
+  /// Spawn a task on behalf of a widget.
+  fn spawn(&mut self, widget: Id, task: TaskFuture<M>) {
+    let idx = self.validate(widget);
+    let id = self.next_task;
+    self.next_task += 1;
+
+    // Hiding a widget drops its tasks, so do not even bother keeping
+    // one around for a widget that is not displayed.
+    if !self.is_displayed(idx) {
+      return
+    }
+
+    let task = Task {
+      id,
+      idx,
+      future: task,
+    };
+    self.tasks.push(task);
+  }
+
+  /// Schedule a message to be sent to a widget after the given delay.
+  fn schedule(&mut self, widget: Id, delay: Duration, message: M) -> TimerId {
+    let idx = self.validate(widget);
+    self.add_timer(idx, delay, message, None)
+  }
+
+  /// Schedule a message to be sent to a widget repeatedly.
+  fn schedule_repeating(&mut self, widget: Id, interval: Duration, message: M) -> TimerId
+  where
+    M: Clone,
+  {
+    // A zero interval would cause the timer to be due all the time.
+    assert!(!interval.is_zero(), "Timer interval must not be zero");
+
+    let idx = self.validate(widget);
+    self.add_timer(idx, interval, message, Some((interval, M::clone)))
+  }
+
+  /// Cancel a timer.
+  fn cancel(&mut self, timer: TimerId) -> bool {
+    let count = self.timers.len();
+    self.timers.retain(|x| x.id != timer);
+    self.timers.len() != count
+  }
+
+  /// Animate a property of a widget.
+  fn animate(
+    &mut self,
+    widget: Id,
+    property: &'static str,
+    from: f64,
+    to: f64,
+    duration: Duration,
+    easing: Easing,
+  ) {
+    let idx = self.validate(widget);
+    let tween = Tween::new(from, to, self.clock.now(), duration, easing);
+    match self
+      .tweens
+      .iter_mut()
+      .find(|(tween_idx, name, _)| *tween_idx == idx && *name == property)
+    {
+      Some((_, _, existing)) => *existing = tween,
+      None => self.tweens.push((idx, property, tween)),
+    }
+    self.widgets[idx.idx].0.dirty.set(true);
+  }
+
+  /// Set the style of a widget or remove it.
+  fn set_style(&mut self, widget: Id, style: Option<Style>) -> Option<Style> {
+    let idx = self.validate(widget);
+    let data = &mut self.widgets[idx.idx].0;
+    data.dirty.set(true);
+    std::mem::replace(&mut data.style, style)
+  }
+
+  /// Swap the theme of the `Ui`.
+  fn set_theme(&mut self, theme: Theme) -> Theme {
+    // Invalidating the root widget causes everything to be rendered.
+    self.widgets[0].0.dirty.set(true);
+    std::mem::replace(&mut self.theme, theme)
+  }
+
+  /// Register interest of one widget in the data of another.
+  fn watch(&mut self, observer: Id, observed: Id) {
+    let watch = (self.validate(observer), self.validate(observed));
+    if !self.watches.contains(&watch) {
+      let () = self.watches.push(watch);
+    }
+  }
+
+  /// Revoke interest of one widget in the data of another.
+  fn unwatch(&mut self, observer: Id, observed: Id) -> bool {
+    let watch = (self.validate(observer), self.validate(observed));
+    let count = self.watches.len();
+    self.watches.retain(|x| *x != watch);
+    self.watches.len() != count
+  }
 }
 
 impl<E, M> Debug for Ui<E, M> {
@@ -5,6 +5,7 @@ use std::any::TypeId;
 use std::fmt::Debug;
 
 use crate::Cap;
+use crate::DataError;
 use crate::Handleable;
 use crate::MutCap;
 use crate::Object;
@@ -27,13 +28,14 @@ pub trait Widget<E, M>: Handleable<E, M> + Renderable + Object + Debug {
   /// # Panics
   ///
   /// This function will panic if the data associated with the object is
-  /// not of type `D`.
+  /// not of type `D`. Use [`Widget::try_data`] for a non-panicking
+  /// version.
   fn data<'c, D>(&self, cap: &'c dyn Cap) -> &'c D
   where
     Self: Sized,
     D: 'static,
   {
-    cap.data(self.id()).downcast_ref::<D>().unwrap()
+    self.try_data(cap).unwrap_or_else(|err| panic!("{}", err))
   }
 
   /// Retrieve a mutable reference to a widget's data.
@@ -41,13 +43,33 @@ pub trait Widget<E, M>: Handleable<E, M> + Renderable + Object + Debug {
   /// # Panics
   ///
   /// This function will panic if the data associated with the object is
-  /// not of type `D`.
+  /// not of type `D`. Use [`Widget::try_data_mut`] for a non-panicking
+  /// version.
   fn data_mut<'c, D>(&self, cap: &'c mut dyn MutCap<E, M>) -> &'c mut D
   where
     Self: Sized,
     D: 'static,
   {
-    cap.data_mut(self.id()).downcast_mut::<D>().unwrap()
+    self.try_data_mut(cap).unwrap_or_else(|err| panic!("{}", err))
+  }
+
+  /// Retrieve a reference to a widget's data, if they are of type `D`.
+  fn try_data<'c, D>(&self, cap: &'c dyn Cap) -> Result<&'c D, DataError>
+  where
+    Self: Sized,
+    D: 'static,
+  {
+    cap.try_data(self.id())
+  }
+
+  /// Retrieve a mutable reference to a widget's data, if they are of
+  /// type `D`.
+  fn try_data_mut<'c, D>(&self, cap: &'c mut dyn MutCap<E, M>) -> Result<&'c mut D, DataError>
+  where
+    Self: Sized,
+    D: 'static,
+  {
+    cap.try_data_mut(self.id())
   }
 }
 
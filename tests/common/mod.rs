@@ -5,7 +5,6 @@
   clippy::redundant_field_names,
 )]
 
-use std::any::Any;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fmt::Result;
@@ -14,17 +13,24 @@ use std::ops::DerefMut;
 
 use async_trait::async_trait;
 
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
 use gui::derive::Widget;
 use gui::Handleable;
 use gui::Id;
 use gui::Mergeable;
 use gui::MutCap;
+use gui::Request;
 use gui::Widget;
 
 
 /// An event type used for testing purposes.
 #[allow(unused)]
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Event {
   /// An empty event.
   Empty,
@@ -68,6 +74,7 @@ impl Mergeable for Event {
 
 #[allow(unused)]
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Message {
   /// An integer value.
   pub value: u64,
@@ -106,10 +113,14 @@ impl<T> DerefMut for Handler<T> {
 type EventFn = dyn FnMut(Id, &mut dyn MutCap<Event, Message>, Event) -> Option<Event>;
 type ReactFn = dyn FnMut(Message, &mut dyn MutCap<Event, Message>) -> Option<Message>;
 type RespondFn = dyn FnMut(&mut Message, &mut dyn MutCap<Event, Message>) -> Option<Message>;
+type AnswerFn = dyn FnMut(&mut Request<'_>, &mut dyn MutCap<Event, Message>);
+type ObserveFn = dyn FnMut(Id, &[Id], &mut dyn MutCap<Event, Message>);
 
 type EventHandler = Handler<Box<EventFn>>;
 type ReactHandler = Handler<Box<ReactFn>>;
 type RespondHandler = Handler<Box<RespondFn>>;
+type AnswerHandler = Handler<Box<AnswerFn>>;
+type ObserveHandler = Handler<Box<ObserveFn>>;
 
 
 #[derive(Debug)]
@@ -117,6 +128,8 @@ pub struct TestWidgetData {
   event_handler: Option<EventHandler>,
   react_handler: Option<ReactHandler>,
   respond_handler: Option<RespondHandler>,
+  answer_handler: Option<AnswerHandler>,
+  observe_handler: Option<ObserveHandler>,
 }
 
 #[derive(Debug)]
@@ -124,6 +137,8 @@ pub struct TestWidgetDataBuilder {
   event_handler: Option<EventHandler>,
   react_handler: Option<ReactHandler>,
   respond_handler: Option<RespondHandler>,
+  answer_handler: Option<AnswerHandler>,
+  observe_handler: Option<ObserveHandler>,
 }
 
 #[allow(unused)]
@@ -134,6 +149,8 @@ impl TestWidgetDataBuilder {
       event_handler: None,
       react_handler: None,
       respond_handler: None,
+      answer_handler: None,
+      observe_handler: None,
     }
   }
 
@@ -164,14 +181,33 @@ impl TestWidgetDataBuilder {
     self
   }
 
+  /// Set a handler for `Handleable::answer`.
+  pub fn answer_handler<F>(mut self, handler: F) -> Self
+  where
+    F: 'static + FnMut(&mut Request<'_>, &mut dyn MutCap<Event, Message>),
+  {
+    self.answer_handler = Some(Handler(Box::new(handler)));
+    self
+  }
+
+  /// Set a handler for `Handleable::observe`.
+  pub fn observe_handler<F>(mut self, handler: F) -> Self
+  where
+    F: 'static + FnMut(Id, &[Id], &mut dyn MutCap<Event, Message>),
+  {
+    self.observe_handler = Some(Handler(Box::new(handler)));
+    self
+  }
+
   /// Build the `TestWidgetData` object.
-  pub fn build(self) -> Box<dyn Any> {
-    let data = TestWidgetData {
+  pub fn build(self) -> TestWidgetData {
+    TestWidgetData {
       event_handler: self.event_handler,
       react_handler: self.react_handler,
       respond_handler: self.respond_handler,
-    };
-    Box::new(data)
+      answer_handler: self.answer_handler,
+      observe_handler: self.observe_handler,
+    }
   }
 }
 
@@ -185,6 +221,15 @@ impl TestWidget {
   pub fn new(id: Id) -> Self {
     Self { id }
   }
+
+  /// Retrieve the widget's data holding its handlers, without
+  /// reporting a change to observers.
+  fn handlers<'c>(&self, cap: &'c mut dyn MutCap<Event, Message>) -> &'c mut TestWidgetData {
+    cap
+      .data_mut_untracked(self.id)
+      .downcast_mut::<TestWidgetData>()
+      .unwrap()
+  }
 }
 
 #[async_trait(?Send)]
@@ -194,11 +239,11 @@ impl Handleable<Event, Message> for TestWidget {
     // Also check that we can access the non-mutable version of the data.
     let _ = self.data::<TestWidgetData>(cap);
 
-    let data = self.data_mut::<TestWidgetData>(cap);
+    let data = self.handlers(cap);
     match data.event_handler.take() {
       Some(mut handler) => {
         let event = handler(self.id, cap, event);
-        let data = self.data_mut::<TestWidgetData>(cap);
+        let data = self.handlers(cap);
         data.event_handler = Some(handler);
         event
       },
@@ -207,11 +252,11 @@ impl Handleable<Event, Message> for TestWidget {
   }
 
   async fn react(&self, message: Message, cap: &mut dyn MutCap<Event, Message>) -> Option<Message> {
-    let data = self.data_mut::<TestWidgetData>(cap);
+    let data = self.handlers(cap);
     match data.react_handler.take() {
       Some(mut handler) => {
         let message = handler(message, cap);
-        let data = self.data_mut::<TestWidgetData>(cap);
+        let data = self.handlers(cap);
         data.react_handler = Some(handler);
         message
       },
@@ -224,16 +269,36 @@ impl Handleable<Event, Message> for TestWidget {
     message: &mut Message,
     cap: &mut dyn MutCap<Event, Message>,
   ) -> Option<Message> {
-    let data = self.data_mut::<TestWidgetData>(cap);
+    let data = self.handlers(cap);
     match data.respond_handler.take() {
       Some(mut handler) => {
         let result = handler(message, cap);
 
-        let data = self.data_mut::<TestWidgetData>(cap);
+        let data = self.handlers(cap);
         data.respond_handler = Some(handler);
         result
       },
       None => None,
     }
   }
+
+  async fn answer(&self, request: &mut Request<'_>, cap: &mut dyn MutCap<Event, Message>) {
+    let data = self.handlers(cap);
+    if let Some(mut handler) = data.answer_handler.take() {
+      let () = handler(request, cap);
+
+      let data = self.handlers(cap);
+      data.answer_handler = Some(handler);
+    }
+  }
+
+  async fn observe(&self, changed: &[Id], cap: &mut dyn MutCap<Event, Message>) {
+    let data = self.handlers(cap);
+    if let Some(mut handler) = data.observe_handler.take() {
+      let () = handler(self.id, changed, cap);
+
+      let data = self.handlers(cap);
+      data.observe_handler = Some(handler);
+    }
+  }
 }
@@ -0,0 +1,160 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+mod common;
+
+use std::cell::RefCell;
+use std::time::Duration;
+
+use gui::BBox;
+use gui::Cap;
+use gui::Clock;
+use gui::Easing;
+use gui::Id;
+use gui::ManualClock;
+use gui::MutCap;
+use gui::Renderable;
+use gui::Renderer;
+use gui::Ui;
+
+use crate::common::Event;
+use crate::common::Message;
+use crate::common::TestWidget;
+use crate::common::TestWidgetDataBuilder;
+
+
+/// A renderer recording the value of the "offset" property of each
+/// rendered widget.
+#[derive(Debug, Default)]
+struct OffsetRecorder {
+  offsets: RefCell<Vec<(Id, Option<f64>)>>,
+}
+
+impl OffsetRecorder {
+  fn rendered(&self) -> Vec<(Id, Option<f64>)> {
+    self.offsets.borrow_mut().drain(..).collect()
+  }
+}
+
+impl Renderer for OffsetRecorder {
+  fn renderable_area(&self) -> BBox {
+    BBox {
+      x: 0,
+      y: 0,
+      w: 10,
+      h: 10,
+    }
+  }
+
+  fn render(&self, object: &dyn Renderable, cap: &dyn Cap, bbox: BBox) -> BBox {
+    let id = object.id();
+    self
+      .offsets
+      .borrow_mut()
+      .push((id, cap.animated_value(id, "offset")));
+    bbox
+  }
+}
+
+
+/// Create a `Ui` with a root widget and a child, along with a
+/// `ManualClock` driving it.
+fn new_ui() -> (Ui<Event, Message>, ManualClock, Id, Id) {
+  let (mut ui, root) = Ui::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let child = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+
+  let clock = ManualClock::default();
+  ui.set_clock(Box::new(clock.clone()));
+  (ui, clock, root, child)
+}
+
+
+/// Check that easing functions map the boundaries as expected.
+#[test]
+fn easing_functions() {
+  for easing in [Easing::Linear, Easing::EaseIn, Easing::EaseOut, Easing::EaseInOut] {
+    assert_eq!(easing.apply(0.0), 0.0);
+    assert_eq!(easing.apply(1.0), 1.0);
+    assert_eq!(easing.apply(-1.0), 0.0);
+    assert_eq!(easing.apply(2.0), 1.0);
+  }
+
+  assert_eq!(Easing::Linear.apply(0.25), 0.25);
+  assert_eq!(Easing::EaseIn.apply(0.5), 0.25);
+  assert_eq!(Easing::EaseOut.apply(0.5), 0.75);
+  assert_eq!(Easing::EaseInOut.apply(0.25), 0.125);
+  assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+  assert_eq!(Easing::EaseInOut.apply(0.75), 0.875);
+}
+
+/// Check that animated values are interpolated as time advances and
+/// that animated widgets get invalidated.
+#[test]
+fn tween_progress() {
+  let (mut ui, clock, root, child) = new_ui();
+  let renderer = OffsetRecorder::default();
+  ui.render(&renderer);
+  let _ = renderer.rendered();
+
+  assert_eq!(ui.animated_value(child, "offset"), None);
+  assert!(!ui.is_animating());
+
+  ui.animate(child, "offset", 10.0, 20.0, Duration::from_secs(4), Easing::Linear);
+  assert_eq!(ui.animated_value(child, "offset"), Some(10.0));
+  assert!(ui.is_animating());
+  assert!(ui.needs_render());
+  ui.render_dirty(&renderer);
+  assert_eq!(renderer.rendered(), vec![(child, Some(10.0))]);
+
+  clock.advance(Duration::from_secs(1));
+  assert!(ui.tick(clock.now()));
+  assert!(ui.needs_render());
+  ui.render_dirty(&renderer);
+  assert_eq!(renderer.rendered(), vec![(child, Some(12.5))]);
+
+  clock.advance(Duration::from_secs(5));
+  assert!(!ui.tick(clock.now()));
+  assert!(!ui.is_animating());
+  ui.render_dirty(&renderer);
+  assert_eq!(renderer.rendered(), vec![(child, Some(20.0))]);
+
+  assert_eq!(ui.animated_value(child, "offset"), Some(20.0));
+
+  // Concluded animations no longer invalidate the widget and get
+  // removed.
+  clock.advance(Duration::from_secs(1));
+  assert!(!ui.tick(clock.now()));
+  assert!(!ui.needs_render());
+  assert_eq!(ui.animated_value(child, "offset"), None);
+  assert_eq!(ui.animated_value(root, "offset"), None);
+}
+
+/// Check that animating a property anew replaces the existing
+/// animation.
+#[test]
+fn tween_replacement() {
+  let (mut ui, clock, root, _child) = new_ui();
+
+  ui.animate(root, "offset", 0.0, 1.0, Duration::from_secs(2), Easing::EaseIn);
+  ui.animate(root, "opacity", 1.0, 0.0, Duration::from_secs(1), Easing::Linear);
+  clock.advance(Duration::from_secs(1));
+  assert!(ui.tick(clock.now()));
+  assert_eq!(ui.animated_value(root, "offset"), Some(0.25));
+  assert_eq!(ui.animated_value(root, "opacity"), Some(0.0));
+
+  ui.animate(root, "offset", 1.0, 0.0, Duration::ZERO, Easing::Linear);
+  assert!(!ui.tick(clock.now()));
+  assert_eq!(ui.animated_value(root, "offset"), Some(0.0));
+  // The opacity animation concluded during the previous tick and got
+  // removed.
+  assert_eq!(ui.animated_value(root, "opacity"), None);
+}
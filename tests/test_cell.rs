@@ -0,0 +1,176 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+mod common;
+
+use gui::derive::Handleable;
+use gui::derive::Widget;
+use gui::BBox;
+use gui::Cell;
+use gui::CellBuffer;
+use gui::CellStyle;
+use gui::Color;
+use gui::Constraint;
+use gui::Emphasis;
+use gui::Flow;
+use gui::Id;
+use gui::Insets;
+use gui::MutCap;
+use gui::Object;
+use gui::Renderer;
+use gui::Size;
+use gui::Ui;
+
+use crate::common::Event;
+use crate::common::Message;
+use crate::common::TestWidget;
+use crate::common::TestWidgetDataBuilder;
+
+
+/// A widget displaying the `String` stored as its data.
+#[derive(Debug, Widget, Handleable)]
+#[gui(Event = Event, Message = Message)]
+struct Label {
+  id: Id,
+}
+
+
+/// Create a `CellBuffer` drawing `TestWidget`s as a frame of `#`
+/// characters and `Label`s as their text.
+fn new_buffer(w: u16, h: u16) -> CellBuffer {
+  CellBuffer::new(Size { w, h })
+    .on::<TestWidget, _>(|_widget, _cap, canvas| {
+      let () = canvas.frame('#', CellStyle::default());
+      canvas.bbox().inset(Insets::uniform(1))
+    })
+    .on::<Label, _>(|widget, cap, canvas| {
+      let text = cap.data(widget.id()).downcast_ref::<String>().unwrap();
+      let style = CellStyle {
+        fg: Color::Red,
+        emphasis: Emphasis {
+          bold: true,
+          ..Default::default()
+        },
+        ..Default::default()
+      };
+      let _ = canvas.text(0, 0, text, style);
+      canvas.bbox()
+    })
+}
+
+/// Add a `Label` displaying the given text to a widget.
+fn add_label(ui: &mut Ui<Event, Message>, parent: Id, text: &str) -> Id {
+  let text = text.to_string();
+  ui.add_ui_widget(parent, move || text, |id, _cap| Label { id })
+    .id()
+}
+
+
+/// Check that the buffer reports its size as the renderable area.
+#[test]
+fn renderable_area() {
+  let buffer = CellBuffer::new(Size { w: 7, h: 3 });
+  let expected = BBox {
+    x: 0,
+    y: 0,
+    w: 7,
+    h: 3,
+  };
+  assert_eq!(buffer.renderable_area(), expected);
+  assert_eq!(buffer.to_string(), "       \n       \n       \n");
+
+  let () = buffer.resize(Size { w: 2, h: 1 });
+  assert_eq!(buffer.size(), Size { w: 2, h: 1 });
+  assert_eq!(buffer.to_string(), "  \n");
+}
+
+/// Check that widgets get drawn and clipped to their bounding boxes.
+#[test]
+fn draw_and_clip() {
+  let (mut ui, root) = Ui::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let label1 = add_label(&mut ui, root, "hello world");
+  let label2 = add_label(&mut ui, root, "gui");
+  let flow = Flow::column()
+    .child(label1, Constraint::Fixed(1))
+    .child(label2, Constraint::Fixed(1));
+  let _ = ui.set_layout(root, Some(Box::new(flow)));
+
+  let buffer = new_buffer(9, 4);
+  ui.render(&buffer);
+
+  let expected = "\
+#########
+#hello w#
+#gui    #
+#########
+";
+  assert_eq!(buffer.to_string(), expected);
+
+  let cell = buffer.cell(1, 1).unwrap();
+  assert_eq!(cell.ch, 'h');
+  assert_eq!(cell.style.fg, Color::Red);
+  assert!(cell.style.emphasis.bold);
+  assert_eq!(buffer.cell(0, 0).unwrap().style, CellStyle::default());
+  assert_eq!(buffer.cell(9, 0), None);
+
+  // Contents from a previous render are cleared.
+  ui.hide(label1);
+  ui.render(&buffer);
+
+  let expected = "\
+#########
+#gui    #
+#       #
+#########
+";
+  assert_eq!(buffer.to_string(), expected);
+}
+
+/// Check that a partial render pass only redraws invalidated widgets,
+/// keeping the cells of all others.
+#[test]
+fn render_dirty() {
+  let (mut ui, root) = Ui::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let label1 = add_label(&mut ui, root, "abc");
+  let label2 = add_label(&mut ui, root, "def");
+  let flow = Flow::column()
+    .child(label1, Constraint::Fixed(1))
+    .child(label2, Constraint::Fixed(1));
+  let _ = ui.set_layout(root, Some(Box::new(flow)));
+
+  let buffer = new_buffer(5, 4);
+  ui.render(&buffer);
+
+  *ui.data_mut(label2).downcast_mut::<String>().unwrap() = "xyz".to_string();
+  ui.invalidate(label2);
+  ui.render_dirty(&buffer);
+
+  let expected = "\
+#####
+#abc#
+#xyz#
+#####
+";
+  assert_eq!(buffer.to_string(), expected);
+}
+
+/// Check that widgets of unregistered types are not drawn.
+#[test]
+fn unregistered_widgets() {
+  let (mut ui, root) = Ui::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let _ = add_label(&mut ui, root, "text");
+
+  let buffer = CellBuffer::new(Size { w: 3, h: 2 });
+  ui.render(&buffer);
+  assert_eq!(buffer.to_string(), "   \n   \n");
+  assert_eq!(buffer.cell(0, 0), Some(Cell::default()));
+}
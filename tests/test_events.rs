@@ -20,18 +20,19 @@ use crate::common::TestWidgetDataBuilder;
 #[tokio::test]
 async fn events_bubble_up_when_unhandled() {
   let new_data = || TestWidgetDataBuilder::new().build();
-  let (mut ui, r) = Ui::new(new_data, |id, _cap| {
-    Box::new(TestWidget::new(id))
-  });
-  let c1 = ui.add_ui_widget(r, new_data, |id, _cap| {
-    Box::new(TestWidget::new(id))
-  });
-  let c2 = ui.add_ui_widget(c1, new_data, |id, _cap| {
-    Box::new(TestWidget::new(id))
-  });
-  let w1 = ui.add_ui_widget(c2, new_data, |id, _cap| {
-    Box::new(TestWidget::new(id))
-  });
+  let (mut ui, r) = Ui::new(
+    || Box::new(new_data()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let c1 = ui
+    .add_ui_widget(r, new_data, |id, _cap| TestWidget::new(id))
+    .id();
+  let c2 = ui
+    .add_ui_widget(c1, new_data, |id, _cap| TestWidget::new(id))
+    .id();
+  let w1 = ui
+    .add_ui_widget(c2, new_data, |id, _cap| TestWidget::new(id))
+    .id();
 
   let event = Event::Key(' ');
   ui.focus(w1);
@@ -42,15 +43,38 @@ async fn events_bubble_up_when_unhandled() {
   assert_eq!(result.unwrap(), event);
 }
 
+#[tokio::test]
+async fn events_bubble_up_deep_widget_tree() {
+  let new_data = || TestWidgetDataBuilder::new().build();
+  let (mut ui, r) = Ui::new(
+    || Box::new(new_data()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let mut w = r;
+  for _ in 1..100_000 {
+    w = ui
+      .add_ui_widget(w, new_data, |id, _cap| TestWidget::new(id))
+      .id();
+  }
+
+  let event = Event::Key('d');
+  ui.focus(w);
+
+  // Bubbling through all the widgets should not overflow the stack.
+  let result = ui.handle(event).await;
+  assert_eq!(result.unwrap(), event);
+}
+
 #[tokio::test]
 async fn targeted_event_returned_on_no_focus() {
   let new_data = || TestWidgetDataBuilder::new().build();
-  let (mut ui, r) = Ui::new(new_data, |id, _cap| {
-    Box::new(TestWidget::new(id))
-  });
-  let w = ui.add_ui_widget(r, new_data, |id, _cap| {
-    Box::new(TestWidget::new(id))
-  });
+  let (mut ui, r) = Ui::new(
+    || Box::new(new_data()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let w = ui
+    .add_ui_widget(r, new_data, |id, _cap| TestWidget::new(id))
+    .id();
 
   let event = Event::Key('y');
   ui.focus(w);
@@ -81,27 +105,31 @@ fn key_handler(
 #[tokio::test]
 async fn event_handling_with_focus() {
   let (mut ui, r) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w1 = ui.add_ui_widget(
-    r,
-    || {
-      TestWidgetDataBuilder::new()
-        .event_handler(|_s, c, e| key_handler(c, e, None))
-        .build()
-    },
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w2 = ui.add_ui_widget(
-    r,
-    || {
-      TestWidgetDataBuilder::new()
-        .event_handler(move |_s, c, e| key_handler(c, e, Some(w1)))
-        .build()
-    },
+    || Box::new(TestWidgetDataBuilder::new().build()),
     |id, _cap| Box::new(TestWidget::new(id)),
   );
+  let w1 = ui
+    .add_ui_widget(
+      r,
+      || {
+        TestWidgetDataBuilder::new()
+          .event_handler(|_s, c, e| key_handler(c, e, None))
+          .build()
+      },
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w2 = ui
+    .add_ui_widget(
+      r,
+      || {
+        TestWidgetDataBuilder::new()
+          .event_handler(move |_s, c, e| key_handler(c, e, Some(w1)))
+          .build()
+      },
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
 
   ui.focus(w2);
   assert!(ui.is_focused(w2));
@@ -131,30 +159,35 @@ fn incrementing_event_handler(
 async fn event_propagation() {
   let (mut ui, r) = Ui::new(
     || {
-      TestWidgetDataBuilder::new()
-        .event_handler(incrementing_event_handler)
-        .build()
-    },
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let c1 = ui.add_ui_widget(
-    r,
-    || {
-      TestWidgetDataBuilder::new()
+      let data = TestWidgetDataBuilder::new()
         .event_handler(incrementing_event_handler)
-        .build()
-    },
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w1 = ui.add_ui_widget(
-    c1,
-    || {
-      TestWidgetDataBuilder::new()
-        .event_handler(incrementing_event_handler)
-        .build()
+        .build();
+      Box::new(data)
     },
     |id, _cap| Box::new(TestWidget::new(id)),
   );
+  let c1 = ui
+    .add_ui_widget(
+      r,
+      || {
+        TestWidgetDataBuilder::new()
+          .event_handler(incrementing_event_handler)
+          .build()
+      },
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w1 = ui
+    .add_ui_widget(
+      c1,
+      || {
+        TestWidgetDataBuilder::new()
+          .event_handler(incrementing_event_handler)
+          .build()
+      },
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
 
   // We focus the widget we just created, which means that the event
   // will travel through the widget and all its parents.
@@ -192,14 +225,16 @@ fn count_event_hook<'f>(
 #[test]
 fn hook_events_return_value() {
   let (mut ui, r) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w = ui.add_ui_widget(
-    r,
-    || TestWidgetDataBuilder::new().build(),
+    || Box::new(TestWidgetDataBuilder::new().build()),
     |id, _cap| Box::new(TestWidget::new(id)),
   );
+  let w = ui
+    .add_ui_widget(
+      r,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
 
   assert!(ui.hook_events(w, None).is_none());
   assert!(ui.hook_events(r, None).is_none());
@@ -214,19 +249,23 @@ fn hook_events_return_value() {
 #[tokio::test]
 async fn hook_events_handler() {
   let (mut ui, r) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let c1 = ui.add_ui_widget(
-    r,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w1 = ui.add_ui_widget(
-    c1,
-    || TestWidgetDataBuilder::new().build(),
+    || Box::new(TestWidgetDataBuilder::new().build()),
     |id, _cap| Box::new(TestWidget::new(id)),
   );
+  let c1 = ui
+    .add_ui_widget(
+      r,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w1 = ui
+    .add_ui_widget(
+      c1,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
 
   ui.focus(w1);
   ui.hook_events(c1, Some(&count_event_hook));
@@ -275,18 +314,20 @@ fn checking_event_handler(
 #[tokio::test]
 async fn hook_emitted_events() {
   let (mut ui, r) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w = ui.add_ui_widget(
-    r,
-    || {
-      TestWidgetDataBuilder::new()
-        .event_handler(checking_event_handler)
-        .build()
-    },
+    || Box::new(TestWidgetDataBuilder::new().build()),
     |id, _cap| Box::new(TestWidget::new(id)),
   );
+  let w = ui
+    .add_ui_widget(
+      r,
+      || {
+        TestWidgetDataBuilder::new()
+          .event_handler(checking_event_handler)
+          .build()
+      },
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
 
   ui.focus(w);
   ui.hook_events(w, Some(&emitting_event_hook));
@@ -311,27 +352,31 @@ fn different_emitting_event_hook<'f>(
 #[should_panic(expected = "left: 'a'\n right: 'z'")]
 async fn hook_event_merging() {
   let (mut ui, r) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w1 = ui.add_ui_widget(
-    r,
-    || {
-      TestWidgetDataBuilder::new()
-        .event_handler(checking_event_handler)
-        .build()
-    },
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w2 = ui.add_ui_widget(
-    r,
-    || {
-      TestWidgetDataBuilder::new()
-        .event_handler(checking_event_handler)
-        .build()
-    },
+    || Box::new(TestWidgetDataBuilder::new().build()),
     |id, _cap| Box::new(TestWidget::new(id)),
   );
+  let w1 = ui
+    .add_ui_widget(
+      r,
+      || {
+        TestWidgetDataBuilder::new()
+          .event_handler(checking_event_handler)
+          .build()
+      },
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w2 = ui
+    .add_ui_widget(
+      r,
+      || {
+        TestWidgetDataBuilder::new()
+          .event_handler(checking_event_handler)
+          .build()
+      },
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
 
   // We register two event hooks that emit different events that are not
   // actually mergeable by our definition. So we expect a panic.
@@ -364,35 +409,40 @@ static mut RECEIVED_VALUE: u64 = 42;
 async fn hook_can_send_message() {
   let (mut ui, r) = Ui::new(
     || {
-      TestWidgetDataBuilder::new()
+      let data = TestWidgetDataBuilder::new()
         .react_handler(|m, _| {
           unsafe {
             RECEIVED_VALUE = m.value;
           }
           None
         })
-        .build()
-    },
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let c1 = ui.add_ui_widget(
-    r,
-    || {
-      TestWidgetDataBuilder::new()
-        .event_handler(move |_, _, _| None)
-        .build()
-    },
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w1 = ui.add_ui_widget(
-    c1,
-    || {
-      TestWidgetDataBuilder::new()
-        .event_handler(move |_, _, _| None)
-        .build()
+        .build();
+      Box::new(data)
     },
     |id, _cap| Box::new(TestWidget::new(id)),
   );
+  let c1 = ui
+    .add_ui_widget(
+      r,
+      || {
+        TestWidgetDataBuilder::new()
+          .event_handler(move |_, _, _| None)
+          .build()
+      },
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w1 = ui
+    .add_ui_widget(
+      c1,
+      || {
+        TestWidgetDataBuilder::new()
+          .event_handler(move |_, _, _| None)
+          .build()
+      },
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
 
   ui.focus(w1);
   assert_eq!(unsafe { RECEIVED_VALUE }, 42);
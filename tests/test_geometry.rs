@@ -0,0 +1,216 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use gui::BBox;
+use gui::Coord;
+use gui::Insets;
+use gui::Point;
+use gui::Size;
+
+
+fn bbox(x: u16, y: u16, w: u16, h: u16) -> BBox {
+  BBox { x, y, w, h }
+}
+
+
+/// Check that a `BBox` can be decomposed into origin and size.
+#[test]
+fn origin_and_size() {
+  let origin = Point { x: 1, y: 2 };
+  let size = Size { w: 3, h: 4 };
+  let bbox = BBox::new(origin, size);
+
+  assert_eq!(bbox, self::bbox(1, 2, 3, 4));
+  assert_eq!(bbox.origin(), origin);
+  assert_eq!(bbox.size(), size);
+  assert!(!bbox.is_empty());
+  assert!(BBox::<u16>::default().is_empty());
+}
+
+/// Test point containment, including the exclusive edges.
+#[test]
+fn contains() {
+  let bbox = bbox(2, 2, 3, 3);
+  assert!(bbox.contains(Point { x: 2, y: 2 }));
+  assert!(bbox.contains(Point { x: 4, y: 4 }));
+  assert!(!bbox.contains(Point { x: 5, y: 4 }));
+  assert!(!bbox.contains(Point { x: 1, y: 2 }));
+
+  let bbox = self::bbox(u16::MAX - 1, 0, u16::MAX, 1);
+  assert!(bbox.contains(Point { x: u16::MAX, y: 0 }));
+}
+
+/// Test intersection of bounding boxes.
+#[test]
+fn intersect() {
+  let a = bbox(0, 0, 10, 10);
+  let b = bbox(5, 8, 10, 10);
+  assert_eq!(a.intersect(&b), bbox(5, 8, 5, 2));
+  assert_eq!(b.intersect(&a), bbox(5, 8, 5, 2));
+  assert!(a.overlaps(&b));
+
+  let c = bbox(20, 20, 5, 5);
+  assert!(a.intersect(&c).is_empty());
+  assert!(!a.overlaps(&c));
+
+  // Edges beyond the coordinate space must not cause an overflow.
+  let d = bbox(u16::MAX - 5, u16::MAX - 5, u16::MAX, u16::MAX);
+  let e = bbox(u16::MAX - 10, u16::MAX - 10, 8, 8);
+  assert_eq!(d.intersect(&e), bbox(u16::MAX - 5, u16::MAX - 5, 3, 3));
+}
+
+/// Test the union of bounding boxes.
+#[test]
+fn union() {
+  let a = bbox(0, 0, 2, 2);
+  let b = bbox(5, 3, 2, 2);
+  assert_eq!(a.union(&b), bbox(0, 0, 7, 5));
+  assert_eq!(a.union(&BBox::default()), a);
+  assert_eq!(BBox::default().union(&b), b);
+
+  let c = bbox(u16::MAX - 1, 0, 10, 1);
+  assert_eq!(a.union(&c), bbox(0, 0, u16::MAX, 2));
+}
+
+/// Check that insets shrink a bounding box without underflowing.
+#[test]
+fn inset() {
+  let bbox = bbox(1, 1, 10, 6);
+  let insets = Insets {
+    top: 1,
+    right: 2,
+    bottom: 3,
+    left: 4,
+  };
+  assert_eq!(bbox.inset(insets), self::bbox(5, 2, 4, 2));
+  assert_eq!(bbox.inset(Insets::uniform(1)), self::bbox(2, 2, 8, 4));
+
+  // The result collapses at the bounding box's far edges.
+  assert_eq!(bbox.inset(Insets::uniform(10)), self::bbox(11, 7, 0, 0));
+}
+
+/// Test splitting of bounding boxes.
+#[test]
+fn split() {
+  let bbox = bbox(2, 3, 10, 4);
+  assert_eq!(
+    bbox.split_horizontal(3),
+    (self::bbox(2, 3, 3, 4), self::bbox(5, 3, 7, 4))
+  );
+  assert_eq!(
+    bbox.split_vertical(1),
+    (self::bbox(2, 3, 10, 1), self::bbox(2, 4, 10, 3))
+  );
+
+  let (left, right) = bbox.split_horizontal(20);
+  assert_eq!(left, bbox);
+  assert!(right.is_empty());
+}
+
+/// Check that translation saturates at the bounds of the coordinate
+/// space.
+#[test]
+fn translate() {
+  let bbox = bbox(5, 5, 10, 10);
+  assert_eq!(bbox.translate(2, -3), self::bbox(7, 2, 10, 10));
+  assert_eq!(bbox.translate(-10, 0), self::bbox(0, 5, 5, 10));
+  assert!(bbox.translate(-20, 0).is_empty());
+
+  let moved = bbox.translate(i64::from(u16::MAX) - 8, 0);
+  assert_eq!(moved, self::bbox(u16::MAX - 3, 5, 3, 10));
+  assert!(bbox.translate(i64::MAX, 0).is_empty());
+}
+
+/// Check that bounding boxes work with signed coordinates and can be
+/// clipped to the coordinate space used for rendering.
+#[test]
+fn signed_coordinates() {
+  let bbox = BBox::<i32> {
+    x: -5,
+    y: -2,
+    w: 10,
+    h: 4,
+  };
+  assert!(bbox.contains(Point { x: -1, y: -1 }));
+  assert_eq!(bbox.cast::<u16>(), self::bbox(0, 0, 5, 2));
+  assert!(bbox.translate(-10, 0).cast::<u16>().is_empty());
+
+  let (left, right) = bbox.split_horizontal(7);
+  assert_eq!(left.cast::<u16>(), self::bbox(0, 0, 2, 2));
+  assert_eq!(right.x, 2);
+
+  // A negative extent is treated as empty.
+  let empty = BBox::<i32> { w: -1, ..bbox };
+  assert!(empty.is_empty());
+  assert!(empty.intersect(&bbox).is_empty());
+
+  let wide = BBox::<u32> {
+    x: 70_000,
+    y: 0,
+    w: 10,
+    h: 10,
+  };
+  assert!(wide.cast::<u16>().is_empty());
+  assert_eq!(wide.cast::<i64>().x, 70_000);
+}
+
+/// Check that bounding boxes work with fractional coordinates.
+#[test]
+fn fractional_coordinates() {
+  let bbox = BBox::<f64> {
+    x: 0.5,
+    y: 1.25,
+    w: 2.5,
+    h: 1.5,
+  };
+  let inset = bbox.inset(Insets::uniform(0.25));
+  assert_eq!(
+    inset,
+    BBox {
+      x: 0.75,
+      y: 1.5,
+      w: 2.0,
+      h: 1.0,
+    }
+  );
+  assert!(bbox.contains(Point { x: 2.9, y: 2.7 }));
+  assert_eq!(bbox.cast::<u16>(), self::bbox(1, 1, 2, 2));
+
+  // Conversions between floating point types keep the fraction, while
+  // conversions to integer types round each edge.
+  assert_eq!(
+    bbox.cast::<f32>(),
+    BBox {
+      x: 0.5,
+      y: 1.25,
+      w: 2.5,
+      h: 1.5,
+    }
+  );
+  let signed = BBox::<f64> {
+    x: -1.4,
+    y: -0.6,
+    w: 2.8,
+    h: 1.2,
+  };
+  assert_eq!(
+    signed.cast::<i32>(),
+    BBox {
+      x: -1,
+      y: -1,
+      w: 2,
+      h: 2,
+    }
+  );
+  assert_eq!(Coord::cast::<f64>(3_u16), 3.0);
+
+  // Arithmetic saturates instead of overflowing to infinity.
+  assert_eq!(Coord::saturating_add(f32::MAX, f32::MAX), f32::MAX);
+  assert_eq!(Coord::saturating_sub(f64::MIN, f64::MAX), f64::MIN);
+  let huge = BBox::<f32> {
+    x: f32::MAX,
+    w: f32::MAX,
+    ..Default::default()
+  };
+  assert_eq!(huge.translate(1, 0).x, f32::MAX);
+}
@@ -0,0 +1,157 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+#![cfg(feature = "terminal")]
+
+use std::time::Duration;
+use std::time::Instant;
+
+use gui::InputDecoder;
+use gui::InputEvent;
+use gui::Key;
+use gui::Modifiers;
+use gui::MouseButton;
+use gui::MouseEvent;
+use gui::MouseKind;
+
+
+/// Create a key event without modifiers.
+fn key(key: Key) -> InputEvent {
+  InputEvent::Key(key, Modifiers::default())
+}
+
+/// Create a key event with the given modifiers.
+fn key_mod(key: Key, shift: bool, alt: bool, ctrl: bool) -> InputEvent {
+  InputEvent::Key(key, Modifiers { shift, alt, ctrl })
+}
+
+/// Decode the given input in one go.
+fn decode(bytes: &[u8]) -> Vec<InputEvent> {
+  InputDecoder::default().decode(bytes, Instant::now())
+}
+
+
+/// Check that plain characters and control keys are decoded properly.
+#[test]
+fn characters() {
+  let events = decode("aZ ü€\r\t\x7f\x01\x1a".as_bytes());
+  let expected = vec![
+    key(Key::Char('a')),
+    key(Key::Char('Z')),
+    key(Key::Char(' ')),
+    key(Key::Char('ü')),
+    key(Key::Char('€')),
+    key(Key::Enter),
+    key(Key::Tab),
+    key(Key::Backspace),
+    key_mod(Key::Char('a'), false, false, true),
+    key_mod(Key::Char('z'), false, false, true),
+  ];
+  assert_eq!(events, expected);
+
+  // Alt is reported as escape prefix.
+  let events = decode(b"\x1bx\x1b\x03");
+  let expected = vec![
+    key_mod(Key::Char('x'), false, true, false),
+    key_mod(Key::Char('c'), false, true, true),
+  ];
+  assert_eq!(events, expected);
+}
+
+/// Check that escape sequences for special keys are decoded properly.
+#[test]
+fn special_keys() {
+  let events = decode(b"\x1b[A\x1bOB\x1b[1;5C\x1b[3~\x1b[5;2~\x1b[15~\x1b[24~\x1bOP\x1b[Z");
+  let expected = vec![
+    key(Key::Up),
+    key(Key::Down),
+    key_mod(Key::Right, false, false, true),
+    key(Key::Delete),
+    key_mod(Key::PageUp, true, false, false),
+    key(Key::F(5)),
+    key(Key::F(12)),
+    key(Key::F(1)),
+    key_mod(Key::BackTab, true, false, false),
+  ];
+  assert_eq!(events, expected);
+
+  // Unsupported sequences are ignored.
+  let events = decode(b"\x1b[99~\x1b[?1hx");
+  assert_eq!(events, vec![key(Key::Char('x'))]);
+}
+
+/// Check that SGR mouse reports are decoded properly.
+#[test]
+fn mouse() {
+  let events = decode(b"\x1b[<0;10;5M\x1b[<0;10;5m\x1b[<34;1;2M\x1b[<35;3;4M\x1b[<81;7;8M");
+  let mouse = |kind, x, y, modifiers| InputEvent::Mouse(MouseEvent { kind, x, y, modifiers });
+  let expected = vec![
+    mouse(MouseKind::Press(MouseButton::Left), 9, 4, Modifiers::default()),
+    mouse(MouseKind::Release(MouseButton::Left), 9, 4, Modifiers::default()),
+    mouse(MouseKind::Drag(MouseButton::Right), 0, 1, Modifiers::default()),
+    mouse(MouseKind::Move, 2, 3, Modifiers::default()),
+    mouse(
+      MouseKind::ScrollDown,
+      6,
+      7,
+      Modifiers {
+        ctrl: true,
+        ..Default::default()
+      },
+    ),
+  ];
+  assert_eq!(events, expected);
+}
+
+/// Check that bracketed pastes are reported as a whole, even when
+/// arriving in chunks.
+#[test]
+fn bracketed_paste() {
+  let now = Instant::now();
+  let mut decoder = InputDecoder::default();
+  let events = decoder.decode(b"a\x1b[200~hello\x1b", now);
+  assert_eq!(events, vec![key(Key::Char('a'))]);
+  assert_eq!(decoder.deadline(), None);
+
+  let events = decoder.decode(b"[A\nworld\x1b[20", now);
+  assert_eq!(events, vec![]);
+
+  let events = decoder.decode(b"1~b", now);
+  let expected = vec![
+    InputEvent::Paste("hello\x1b[A\nworld".to_string()),
+    key(Key::Char('b')),
+  ];
+  assert_eq!(events, expected);
+}
+
+/// Check that sequences split across chunks are decoded once complete
+/// and that a lone escape character is reported after the timeout.
+#[test]
+fn partial_input_and_timeout() {
+  let now = Instant::now();
+  let timeout = Duration::from_millis(10);
+  let mut decoder = InputDecoder::new(timeout);
+
+  assert_eq!(decoder.decode(b"\x1b[1;", now), vec![]);
+  assert_eq!(decoder.deadline(), Some(now + timeout));
+  assert_eq!(decoder.decode(b"3", now), vec![]);
+  let events = decoder.decode(b"D", now);
+  assert_eq!(events, vec![key_mod(Key::Left, false, true, false)]);
+  assert_eq!(decoder.deadline(), None);
+
+  // Partial UTF-8 characters are not subject to the timeout.
+  assert_eq!(decoder.decode(&[0xc3], now), vec![]);
+  assert_eq!(decoder.deadline(), None);
+  assert_eq!(decoder.decode(&[0xa4], now), vec![key(Key::Char('ä'))]);
+
+  assert_eq!(decoder.decode(b"\x1b", now), vec![]);
+  assert_eq!(decoder.expire(now + timeout / 2), vec![]);
+  assert_eq!(decoder.expire(now + timeout), vec![key(Key::Esc)]);
+  assert_eq!(decoder.deadline(), None);
+
+  // Input arriving after the timeout expires pending input first.
+  assert_eq!(decoder.decode(b"\x1b[", now), vec![]);
+  let events = decoder.decode(b"x", now + timeout * 2);
+  let expected = vec![key(Key::Esc), key(Key::Char('[')), key(Key::Char('x'))];
+  assert_eq!(events, expected);
+}
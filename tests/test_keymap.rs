@@ -0,0 +1,253 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use std::time::Instant;
+
+use gui::Id;
+use gui::KeyContext;
+use gui::Keymap;
+use gui::MutCap;
+use gui::Resolution;
+use gui::Ui;
+
+use crate::common::Event;
+use crate::common::Message;
+use crate::common::TestWidget;
+use crate::common::TestWidgetDataBuilder;
+
+
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+type Received = Rc<RefCell<Vec<(Id, u64)>>>;
+
+/// Create a `Ui` with a root widget, a container, and a widget inside
+/// the container. All widgets record the messages they receive.
+fn new_ui() -> (Ui<Event, Message>, Received, [Id; 3]) {
+  let received = Received::default();
+  let new_data = |received: Received| {
+    TestWidgetDataBuilder::new()
+      .react_handler(move |message, cap| {
+        // Record the focused widget along with the message's value.
+        let focused = cap.focused().unwrap_or_else(|| cap.root_id());
+        received.borrow_mut().push((focused, message.value));
+        Some(Message::new(message.value + 1))
+      })
+      .build()
+  };
+
+  let (mut ui, root) = Ui::new(
+    || Box::new(new_data(received.clone())),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let c = ui
+    .add_ui_widget(
+      root,
+      || new_data(received.clone()),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w = ui
+    .add_ui_widget(
+      c,
+      || new_data(received.clone()),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  (ui, received, [root, c, w])
+}
+
+
+/// Check that a single key binding is dispatched to the widget it got
+/// found for.
+#[tokio::test]
+async fn single_key_dispatch() {
+  let (mut ui, _, [_, c, w]) = new_ui();
+  let mut keymap = Keymap::new(TIMEOUT);
+  keymap.bind(KeyContext::Widget(c), vec!['q'], Message::new(1));
+  ui.focus(w);
+
+  let now = Instant::now();
+  let resolutions = keymap.handle(&mut ui, 'q', now).await;
+  let expected = Resolution::Dispatched {
+    widget: c,
+    response: Some(Message::new(2)),
+  };
+  assert_eq!(resolutions, vec![expected]);
+
+  let resolutions = keymap.handle(&mut ui, 'x', now).await;
+  assert_eq!(resolutions, vec![Resolution::Unbound(vec!['x'])]);
+}
+
+/// Verify that bindings closer to the focused widget take precedence
+/// and that widget bindings take precedence over type bindings.
+#[tokio::test]
+async fn focus_chain_precedence() {
+  let (mut ui, _, [root, c, w]) = new_ui();
+  let mut keymap = Keymap::new(TIMEOUT);
+  keymap.bind(KeyContext::Global, vec!['a'], Message::new(10));
+  keymap.bind(KeyContext::of::<TestWidget>(), vec!['a'], Message::new(20));
+  keymap.bind(KeyContext::Widget(c), vec!['a'], Message::new(30));
+  keymap.bind(KeyContext::Global, vec!['b'], Message::new(40));
+  ui.focus(c);
+
+  let now = Instant::now();
+  let resolutions = keymap.handle(&mut ui, 'a', now).await;
+  assert!(matches!(resolutions[..], [Resolution::Dispatched { widget, .. }] if widget == c));
+
+  ui.focus(w);
+  // `w` is a `TestWidget` and the type binding is found first.
+  let resolutions = keymap.handle(&mut ui, 'a', now).await;
+  let expected = Resolution::Dispatched {
+    widget: w,
+    response: Some(Message::new(21)),
+  };
+  assert_eq!(resolutions, vec![expected]);
+
+  // Global bindings are sent to the root widget.
+  let resolutions = keymap.handle(&mut ui, 'b', now).await;
+  assert!(matches!(resolutions[..], [Resolution::Dispatched { widget, .. }] if widget == root));
+}
+
+/// Test that multi-key chords are resolved.
+#[tokio::test]
+async fn chord_dispatch() {
+  let (mut ui, received, [root, ..]) = new_ui();
+  let mut keymap = Keymap::new(TIMEOUT);
+  keymap.bind(KeyContext::Global, vec!['d', 'd'], Message::new(1));
+  keymap.bind(KeyContext::Global, vec!['d', 'w'], Message::new(2));
+
+  let now = Instant::now();
+  assert_eq!(keymap.handle(&mut ui, 'd', now).await, vec![Resolution::Pending]);
+  assert_eq!(keymap.pending(), &['d']);
+  assert_eq!(keymap.deadline(), Some(now + TIMEOUT));
+
+  let resolutions = keymap.handle(&mut ui, 'w', now).await;
+  assert!(matches!(resolutions[..], [Resolution::Dispatched { .. }]));
+  assert!(keymap.pending().is_empty());
+
+  assert_eq!(keymap.handle(&mut ui, 'd', now).await, vec![Resolution::Pending]);
+  let resolutions = keymap.handle(&mut ui, 'x', now).await;
+  assert_eq!(resolutions, vec![Resolution::Unbound(vec!['d', 'x'])]);
+
+  assert_eq!(*received.borrow(), vec![(root, 2)]);
+}
+
+/// Check that ambiguous sequences are resolved on timeout.
+#[tokio::test]
+async fn ambiguous_sequence_timeout() {
+  let (mut ui, received, [root, ..]) = new_ui();
+  let mut keymap = Keymap::new(TIMEOUT);
+  keymap.bind(KeyContext::Global, vec!['g'], Message::new(1));
+  keymap.bind(KeyContext::Global, vec!['g', 'g'], Message::new(2));
+
+  let now = Instant::now();
+  assert_eq!(keymap.handle(&mut ui, 'g', now).await, vec![Resolution::Pending]);
+  assert_eq!(keymap.expire(&mut ui, now).await, None);
+
+  let later = now + TIMEOUT;
+  let resolution = keymap.expire(&mut ui, later).await.unwrap();
+  assert!(matches!(resolution, Resolution::Dispatched { .. }));
+  assert_eq!(keymap.deadline(), None);
+
+  // A timed out sequence is expired before handling the next key and
+  // its resolution is reported first.
+  assert_eq!(keymap.handle(&mut ui, 'g', later).await, vec![Resolution::Pending]);
+  let resolutions = keymap.handle(&mut ui, 'g', later + TIMEOUT).await;
+  let expected = Resolution::Dispatched {
+    widget: root,
+    response: Some(Message::new(2)),
+  };
+  assert_eq!(resolutions, vec![expected, Resolution::Pending]);
+
+  let resolutions = keymap.handle(&mut ui, 'g', later + TIMEOUT).await;
+  assert!(matches!(resolutions[..], [Resolution::Dispatched { .. }]));
+
+  assert_eq!(*received.borrow(), vec![(root, 1), (root, 1), (root, 2)]);
+}
+
+/// Check that a sequence that stops matching any binding dispatches the
+/// longest exact binding it passed and feeds the remaining keys anew.
+#[tokio::test]
+async fn mismatch_falls_back_to_longest_exact_binding() {
+  let (mut ui, received, [root, ..]) = new_ui();
+  let mut keymap = Keymap::new(TIMEOUT);
+  keymap.bind(KeyContext::Global, vec!['g'], Message::new(1));
+  keymap.bind(KeyContext::Global, vec!['g', 'g'], Message::new(2));
+  keymap.bind(KeyContext::Global, vec!['x'], Message::new(3));
+  keymap.bind(KeyContext::Global, vec!['a', 'b', 'c'], Message::new(4));
+  keymap.bind(KeyContext::Global, vec!['a'], Message::new(5));
+
+  let now = Instant::now();
+  assert_eq!(keymap.handle(&mut ui, 'g', now).await, vec![Resolution::Pending]);
+  let resolutions = keymap.handle(&mut ui, 'x', now).await;
+  let expected = vec![
+    Resolution::Dispatched {
+      widget: root,
+      response: Some(Message::new(2)),
+    },
+    Resolution::Dispatched {
+      widget: root,
+      response: Some(Message::new(4)),
+    },
+  ];
+  assert_eq!(resolutions, expected);
+  assert!(keymap.pending().is_empty());
+
+  assert_eq!(keymap.handle(&mut ui, 'a', now).await, vec![Resolution::Pending]);
+  assert_eq!(keymap.handle(&mut ui, 'b', now).await, vec![Resolution::Pending]);
+  let resolutions = keymap.handle(&mut ui, 'g', now).await;
+  let expected = vec![
+    Resolution::Dispatched {
+      widget: root,
+      response: Some(Message::new(6)),
+    },
+    Resolution::Unbound(vec!['b']),
+    Resolution::Pending,
+  ];
+  assert_eq!(resolutions, expected);
+  assert_eq!(keymap.pending(), &['g']);
+
+  assert_eq!(*received.borrow(), vec![(root, 1), (root, 3), (root, 5)]);
+}
+
+/// Check that a sequence timing out in one context resolves to an
+/// exact binding in an outer one.
+#[tokio::test]
+async fn timeout_falls_back_to_outer_context() {
+  let (mut ui, received, [root, _, w]) = new_ui();
+  ui.focus(w);
+  let mut keymap = Keymap::new(TIMEOUT);
+  keymap.bind(KeyContext::Widget(w), vec!['g', 'g'], Message::new(1));
+  keymap.bind(KeyContext::Global, vec!['g'], Message::new(2));
+
+  let now = Instant::now();
+  assert_eq!(keymap.handle(&mut ui, 'g', now).await, vec![Resolution::Pending]);
+
+  let resolution = keymap.expire(&mut ui, now + TIMEOUT).await.unwrap();
+  let expected = Resolution::Dispatched {
+    widget: root,
+    response: Some(Message::new(3)),
+  };
+  assert_eq!(resolution, expected);
+  assert_eq!(*received.borrow(), vec![(w, 2)]);
+}
+
+/// Check that bindings can be removed again.
+#[tokio::test]
+async fn unbind() {
+  let (mut ui, _, _) = new_ui();
+  let mut keymap = Keymap::new(TIMEOUT);
+  keymap.bind(KeyContext::Global, vec!['z'], Message::new(1));
+  keymap.bind(KeyContext::Global, vec!['z'], Message::new(2));
+
+  assert_eq!(keymap.unbind(KeyContext::Global, &['z']), Some(Message::new(2)));
+  assert_eq!(keymap.unbind(KeyContext::Global, &['z']), None);
+
+  let resolutions = keymap.handle(&mut ui, 'z', Instant::now()).await;
+  assert_eq!(resolutions, vec![Resolution::Unbound(vec!['z'])]);
+}
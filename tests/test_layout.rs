@@ -0,0 +1,280 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+mod common;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use gui::BBox;
+use gui::Cap;
+use gui::Constraint;
+use gui::Flow;
+use gui::Id;
+use gui::Layout;
+use gui::MutCap;
+use gui::Object;
+use gui::Renderable;
+use gui::Renderer;
+use gui::Size;
+use gui::Ui;
+
+use crate::common::Event;
+use crate::common::Message;
+use crate::common::TestWidget;
+use crate::common::TestWidgetDataBuilder;
+
+
+/// A renderer recording the bounding box of each rendered widget.
+#[derive(Debug, Default)]
+struct BBoxRecorder {
+  /// The content sizes of widgets, as reported when measuring.
+  sizes: RefCell<HashMap<Id, Size>>,
+  /// The widgets measured, in order.
+  measured: RefCell<Vec<Id>>,
+  bboxes: RefCell<Vec<(Id, BBox)>>,
+}
+
+impl BBoxRecorder {
+  fn bbox(&self, id: Id) -> Option<BBox> {
+    self
+      .bboxes
+      .borrow()
+      .iter()
+      .rev()
+      .find(|(x, _)| *x == id)
+      .map(|(_, bbox)| *bbox)
+  }
+}
+
+impl Renderer for BBoxRecorder {
+  fn renderable_area(&self) -> BBox {
+    BBox {
+      x: 0,
+      y: 0,
+      w: 20,
+      h: 10,
+    }
+  }
+
+  fn measure(&self, object: &dyn Renderable, _cap: &dyn Cap, available: Size) -> Size {
+    let widget = object.downcast_ref::<TestWidget>().unwrap();
+    self.measured.borrow_mut().push(widget.id());
+    self
+      .sizes
+      .borrow()
+      .get(&widget.id())
+      .copied()
+      .unwrap_or(available)
+  }
+
+  fn render(&self, object: &dyn Renderable, _cap: &dyn Cap, bbox: BBox) -> BBox {
+    let widget = object.downcast_ref::<TestWidget>().unwrap();
+    self.bboxes.borrow_mut().push((widget.id(), bbox));
+    bbox
+  }
+}
+
+
+/// Create a `Ui` with a root widget having the given number of
+/// children.
+fn new_ui(count: usize) -> (Ui<Event, Message>, Id, Vec<Id>) {
+  let (mut ui, root) = Ui::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let children = (0..count)
+    .map(|_| {
+      ui.add_ui_widget(
+        root,
+        || TestWidgetDataBuilder::new().build(),
+        |id, _cap| TestWidget::new(id),
+      )
+      .id()
+    })
+    .collect();
+  (ui, root, children)
+}
+
+fn bbox(x: u16, y: u16, w: u16, h: u16) -> BBox {
+  BBox { x, y, w, h }
+}
+
+
+/// Check that children of a widget without layout receive its entire
+/// inner bounding box.
+#[test]
+fn no_layout() {
+  let (ui, _, children) = new_ui(2);
+  let renderer = BBoxRecorder::default();
+  ui.render(&renderer);
+
+  assert_eq!(renderer.bbox(children[0]), Some(bbox(0, 0, 20, 10)));
+  assert_eq!(renderer.bbox(children[1]), Some(bbox(0, 0, 20, 10)));
+}
+
+/// Test laying out children in a row using fixed and weighted
+/// constraints.
+#[test]
+fn row_layout() {
+  let (mut ui, root, c) = new_ui(3);
+  let flow = Flow::row()
+    .child(c[0], Constraint::Fixed(5))
+    .child(c[1], Constraint::Fill)
+    .child(c[2], Constraint::Weight(2));
+  let previous = ui.set_layout(root, Some(Box::new(flow)));
+  assert!(previous.is_none());
+
+  let renderer = BBoxRecorder::default();
+  ui.render(&renderer);
+
+  assert_eq!(renderer.bbox(c[0]), Some(bbox(0, 0, 5, 10)));
+  assert_eq!(renderer.bbox(c[1]), Some(bbox(5, 0, 5, 10)));
+  assert_eq!(renderer.bbox(c[2]), Some(bbox(10, 0, 10, 10)));
+}
+
+/// Test laying out children in a column using minimum and maximum
+/// constraints.
+#[test]
+fn column_layout_min_max() {
+  let (mut ui, root, c) = new_ui(4);
+  let flow = Flow::column()
+    .child(c[0], Constraint::Max(1))
+    .child(c[1], Constraint::Min(4))
+    .child(c[2], Constraint::Fill);
+  let _ = ui.set_layout(root, Some(Box::new(flow)));
+
+  let renderer = BBoxRecorder::default();
+  ui.render(&renderer);
+
+  // The remaining six rows are split evenly between three flexible
+  // children, but the first one is capped at a single row.
+  assert_eq!(renderer.bbox(c[0]), Some(bbox(0, 0, 20, 1)));
+  assert_eq!(renderer.bbox(c[1]), Some(bbox(0, 1, 20, 7)));
+  assert_eq!(renderer.bbox(c[2]), Some(bbox(0, 8, 20, 2)));
+  // Children not part of the layout receive the entire area.
+  assert_eq!(renderer.bbox(c[3]), Some(bbox(0, 0, 20, 10)));
+}
+
+/// Check that hidden children do not take up space and that children
+/// not fitting are truncated or omitted.
+#[test]
+fn hidden_and_overflowing_children() {
+  let (mut ui, root, c) = new_ui(3);
+  let flow = Flow::row()
+    .child(c[0], Constraint::Fixed(15))
+    .child(c[1], Constraint::Fixed(10))
+    .child(c[2], Constraint::Fill);
+  let _ = ui.set_layout(root, Some(Box::new(flow)));
+
+  let renderer = BBoxRecorder::default();
+  ui.render(&renderer);
+
+  assert_eq!(renderer.bbox(c[0]), Some(bbox(0, 0, 15, 10)));
+  assert_eq!(renderer.bbox(c[1]), Some(bbox(15, 0, 5, 10)));
+  assert_eq!(renderer.bbox(c[2]), None);
+
+  ui.hide(c[0]);
+  assert!(ui.needs_render());
+  ui.render_dirty(&renderer);
+
+  assert_eq!(renderer.bbox(c[1]), Some(bbox(0, 0, 10, 10)));
+  assert_eq!(renderer.bbox(c[2]), Some(bbox(10, 0, 10, 10)));
+}
+
+
+/// Check that widgets are measured before being rendered and that their
+/// content size can be used by a layout.
+#[test]
+fn content_sized_children() {
+  let (mut ui, root, c) = new_ui(2);
+  let flow = Flow::row()
+    .child(c[0], Constraint::Content)
+    .child(c[1], Constraint::Fill);
+  let _ = ui.set_layout(root, Some(Box::new(flow)));
+
+  let renderer = BBoxRecorder::default();
+  let _ = renderer.sizes.borrow_mut().insert(c[0], Size { w: 4, h: 1 });
+  ui.render(&renderer);
+
+  // Children are measured before their parent.
+  assert_eq!(*renderer.measured.borrow(), vec![c[0], c[1], root]);
+  assert_eq!(ui.measured_size(c[0]), Some(Size { w: 4, h: 1 }));
+  assert_eq!(ui.measured_size(root), Some(Size { w: 20, h: 10 }));
+  assert_eq!(renderer.bbox(c[0]), Some(bbox(0, 0, 4, 10)));
+  assert_eq!(renderer.bbox(c[1]), Some(bbox(4, 0, 16, 10)));
+
+  // A change in size causes the layout to be applied again.
+  let _ = renderer.sizes.borrow_mut().insert(c[0], Size { w: 6, h: 1 });
+  ui.invalidate(c[0]);
+  ui.render_dirty(&renderer);
+
+  assert_eq!(renderer.bbox(c[0]), Some(bbox(0, 0, 6, 10)));
+  assert_eq!(renderer.bbox(c[1]), Some(bbox(6, 0, 14, 10)));
+
+  // Hidden widgets are not measured.
+  ui.hide(c[1]);
+  ui.render_dirty(&renderer);
+  assert_eq!(ui.measured_size(c[1]), None);
+}
+
+
+/// Check that widgets are measured with the space their parent's layout
+/// allots to them.
+#[test]
+fn measure_with_allotted_space() {
+  let (mut ui, root, c) = new_ui(3);
+  let grandchild = ui
+    .add_ui_widget(
+      c[0],
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let flow = Flow::row()
+    .child(c[0], Constraint::Fixed(5))
+    .child(c[1], Constraint::Content)
+    .child(c[2], Constraint::Min(3));
+  let _ = ui.set_layout(root, Some(Box::new(flow)));
+
+  let renderer = BBoxRecorder::default();
+  ui.render(&renderer);
+
+  assert_eq!(ui.measured_size(grandchild), Some(Size { w: 5, h: 10 }));
+  assert_eq!(ui.measured_size(c[0]), Some(Size { w: 5, h: 10 }));
+  // Content sized children may use the space not required by their
+  // siblings.
+  assert_eq!(ui.measured_size(c[1]), Some(Size { w: 12, h: 10 }));
+  assert_eq!(renderer.bbox(c[1]), Some(bbox(5, 0, 12, 10)));
+  assert_eq!(renderer.bbox(c[2]), Some(bbox(17, 0, 3, 10)));
+  assert_eq!(ui.measured_size(root), Some(Size { w: 20, h: 10 }));
+}
+
+/// A layout assigning a fixed bounding box to all children.
+#[derive(Debug)]
+struct FixedLayout(BBox);
+
+impl Layout for FixedLayout {
+  fn layout(&self, cap: &dyn Cap, widget: Id, _bbox: BBox) -> Vec<(Id, BBox)> {
+    cap.children(widget).map(|id| (*id, self.0)).collect()
+  }
+}
+
+
+/// Check that bounding boxes assigned by a layout are clipped to the
+/// parent's inner bounding box.
+#[test]
+fn child_bbox_clipping() {
+  let (mut ui, root, c) = new_ui(1);
+  let _ = ui.set_layout(root, Some(Box::new(FixedLayout(bbox(15, 5, 10, 10)))));
+
+  let renderer = BBoxRecorder::default();
+  ui.render(&renderer);
+  assert_eq!(renderer.bbox(c[0]), Some(bbox(15, 5, 5, 5)));
+
+  // A child entirely outside of its parent is not rendered at all.
+  let _ = ui.set_layout(root, Some(Box::new(FixedLayout(bbox(25, 0, 10, 10)))));
+  let renderer = BBoxRecorder::default();
+  ui.render(&renderer);
+  assert_eq!(renderer.bbox(c[0]), None);
+}
@@ -9,6 +9,9 @@ use gui::derive::Widget;
 use gui::Handleable;
 use gui::Id;
 use gui::MutCap;
+use gui::Query;
+use gui::QueryError;
+use gui::Request;
 use gui::Ui;
 
 use crate::common::Event;
@@ -21,7 +24,7 @@ use crate::common::TestWidgetDataBuilder;
 #[tokio::test]
 async fn no_handler() {
   let (mut ui, root) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
+    || Box::new(TestWidgetDataBuilder::new().build()),
     |id, _cap| Box::new(TestWidget::new(id)),
   );
 
@@ -37,18 +40,20 @@ fn increment_message(message: Message, _cap: &mut dyn MutCap<Event, Message>) ->
 #[tokio::test]
 async fn repeated_send_receive() {
   let (mut ui, root) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w1 = ui.add_ui_widget(
-    root,
-    || {
-      TestWidgetDataBuilder::new()
-        .react_handler(increment_message)
-        .build()
-    },
+    || Box::new(TestWidgetDataBuilder::new().build()),
     |id, _cap| Box::new(TestWidget::new(id)),
   );
+  let w1 = ui
+    .add_ui_widget(
+      root,
+      || {
+        TestWidgetDataBuilder::new()
+          .react_handler(increment_message)
+          .build()
+      },
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
 
   let result = ui.send(w1, Message::new(42)).await;
   assert_eq!(result, Some(Message::new(43)));
@@ -89,6 +94,13 @@ impl Handleable<Event, Message> for ForwardingWidget {
   ) -> Option<Message> {
     cap.call(self.next, message).await
   }
+
+  async fn answer(&self, request: &mut Request<'_>, cap: &mut dyn MutCap<Event, Message>) {
+    if let Some(query) = request.query::<Double>() {
+      let value = cap.query(self.next, Double(query.0 + 1)).await.unwrap();
+      let _ = request.respond::<Double>(value);
+    }
+  }
 }
 
 static mut FINAL_FORWARDED_VALUE: u64 = 0;
@@ -99,27 +111,24 @@ static mut FINAL_FORWARDED_VALUE: u64 = 0;
 async fn forward_message() {
   let (mut ui, root) = Ui::new(
     || {
-      TestWidgetDataBuilder::new()
+      let data = TestWidgetDataBuilder::new()
         .react_handler(|m, _| {
           unsafe {
             FINAL_FORWARDED_VALUE = m.value;
           }
           None
         })
-        .build()
+        .build();
+      Box::new(data)
     },
     |id, _cap| Box::new(TestWidget::new(id)),
   );
-  let w1 = ui.add_ui_widget(
-    root,
-    || Box::new(()),
-    |id, _cap| Box::new(ForwardingWidget::new(id, root)),
-  );
-  let w2 = ui.add_ui_widget(
-    root,
-    || Box::new(()),
-    |id, _cap| Box::new(ForwardingWidget::new(id, w1)),
-  );
+  let w1 = ui
+    .add_ui_widget(root, || (), |id, _cap| ForwardingWidget::new(id, root))
+    .id();
+  let w2 = ui
+    .add_ui_widget(root, || (), |id, _cap| ForwardingWidget::new(id, w1))
+    .id();
 
   let result = ui.send(w2, Message::new(1337)).await;
   assert_eq!(result, None);
@@ -131,27 +140,121 @@ async fn forward_message() {
 async fn call_message() {
   let (mut ui, root) = Ui::new(
     || {
-      TestWidgetDataBuilder::new()
+      let data = TestWidgetDataBuilder::new()
         .respond_handler(|m, _| {
           m.value *= 2;
           None
         })
-        .build()
+        .build();
+      Box::new(data)
     },
     |id, _cap| Box::new(TestWidget::new(id)),
   );
-  let w1 = ui.add_ui_widget(
-    root,
-    || Box::new(()),
-    |id, _cap| Box::new(ForwardingWidget::new(id, root)),
-  );
-  let w2 = ui.add_ui_widget(
-    root,
-    || Box::new(()),
-    |id, _cap| Box::new(ForwardingWidget::new(id, w1)),
-  );
+  let w1 = ui
+    .add_ui_widget(root, || (), |id, _cap| ForwardingWidget::new(id, root))
+    .id();
+  let w2 = ui
+    .add_ui_widget(root, || (), |id, _cap| ForwardingWidget::new(id, w1))
+    .id();
 
   let mut message = Message::new(1337);
   ui.call(w2, &mut message).await;
   assert_eq!(message.value, 2674);
 }
+
+
+/// A query asking a widget to double a value.
+#[derive(Debug)]
+struct Double(u64);
+
+impl Query for Double {
+  type Response = u64;
+}
+
+/// A query asking a widget for its name.
+#[derive(Debug)]
+struct Name;
+
+impl Query for Name {
+  type Response = String;
+}
+
+fn answer_double(request: &mut Request<'_>, _cap: &mut dyn MutCap<Event, Message>) {
+  let _ = request.answer::<Double, _>(|query| query.0 * 2);
+}
+
+/// Check that we can send a typed query and receive a typed response.
+#[tokio::test]
+async fn query_response() {
+  let (mut ui, root) = Ui::new(
+    || {
+      let data = TestWidgetDataBuilder::new()
+        .answer_handler(answer_double)
+        .build();
+      Box::new(data)
+    },
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+
+  let result = ui.query(root, Double(21)).await;
+  assert_eq!(result, Ok(42));
+}
+
+/// Verify that a query not answered by a widget is reported as an
+/// error.
+#[tokio::test]
+async fn query_unanswered() {
+  let (mut ui, root) = Ui::new(
+    || {
+      let data = TestWidgetDataBuilder::new()
+        .answer_handler(answer_double)
+        .build();
+      Box::new(data)
+    },
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let w1 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+
+  // `w1` does not answer any queries.
+  let result = ui.query(w1, Double(1)).await;
+  assert_eq!(
+    result,
+    Err(QueryError::Unanswered {
+      widget: w1,
+      query: std::any::type_name::<Double>(),
+    })
+  );
+
+  // `root` only knows how to answer `Double` queries.
+  let error = ui.query(root, Name).await.unwrap_err();
+  assert!(error.to_string().ends_with("did not answer query test_message::Name"), "{}", error);
+}
+
+/// Test that queries can be sent from within a handler.
+#[tokio::test]
+async fn forward_query() {
+  let (mut ui, root) = Ui::new(
+    || {
+      let data = TestWidgetDataBuilder::new()
+        .answer_handler(answer_double)
+        .build();
+      Box::new(data)
+    },
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let w1 = ui
+    .add_ui_widget(root, || (), |id, _cap| ForwardingWidget::new(id, root))
+    .id();
+  let w2 = ui
+    .add_ui_widget(root, || (), |id, _cap| ForwardingWidget::new(id, w1))
+    .id();
+
+  let result = ui.query(w2, Double(10)).await;
+  assert_eq!(result, Ok(24));
+}
@@ -0,0 +1,166 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use gui::Cap;
+use gui::Clock;
+use gui::Id;
+use gui::ManualClock;
+use gui::MutCap;
+use gui::RecordingRenderer;
+use gui::Ui;
+
+use crate::common::Event;
+use crate::common::Message;
+use crate::common::TestWidget;
+use crate::common::TestWidgetDataBuilder;
+
+
+type Observed = Rc<RefCell<Vec<(&'static str, Vec<Id>)>>>;
+
+/// Add a widget recording the notifications it receives under the
+/// given name.
+fn add_observer(
+  ui: &mut Ui<Event, Message>,
+  parent: Id,
+  name: &'static str,
+  observed: &Observed,
+) -> Id {
+  let observed = observed.clone();
+  ui.add_ui_widget(
+    parent,
+    move || {
+      TestWidgetDataBuilder::new()
+        .observe_handler(move |id, changed, cap| {
+          observed.borrow_mut().push((name, changed.to_vec()));
+          // Observers mutate their own data when notified.
+          let _ = cap.data_mut(id);
+        })
+        .build()
+    },
+    |id, _cap| TestWidget::new(id),
+  )
+  .id()
+}
+
+/// Create a `Ui` with a root widget that, on receipt of an
+/// `Event::Int(n)`, mutates the data of all its children starting at
+/// index `n`, twice, and that mutates its own data on receipt of a
+/// message.
+fn new_ui() -> (Ui<Event, Message>, Id) {
+  let (mut ui, root) = Ui::new(
+    || {
+      let data = TestWidgetDataBuilder::new()
+        .event_handler(|id, cap, event| {
+          let skip = event.unwrap_int() as usize;
+          let children = cap.children(id).skip(skip).copied().collect::<Vec<_>>();
+          for child in children {
+            let _ = cap.data_mut(child);
+            let _ = cap.data_mut(child);
+          }
+          None
+        })
+        .react_handler(|_message, cap| {
+          let _ = cap.data_mut(cap.root_id());
+          None
+        })
+        .build();
+      Box::new(data)
+    },
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  ui.focus(root);
+  (ui, root)
+}
+
+
+/// Check that observers are notified once per dispatch, with all
+/// changes batched.
+#[tokio::test]
+async fn batched_notification() {
+  let observed = Observed::default();
+  let (mut ui, root) = new_ui();
+  let w1 = add_observer(&mut ui, root, "w1", &observed);
+  let w2 = add_observer(&mut ui, root, "w2", &observed);
+  let observer = add_observer(&mut ui, root, "observer", &observed);
+  ui.watch(observer, w1);
+  ui.watch(observer, w2);
+  ui.watch(observer, w2);
+
+  ui.render(&RecordingRenderer::new(Default::default()));
+  assert!(!ui.needs_render());
+
+  // The event causes changes to all children, including the observer
+  // itself, which does not watch itself, though.
+  let _ = ui.handle(Event::Int(0)).await;
+  assert_eq!(*observed.borrow(), vec![("observer", vec![w1, w2])]);
+  assert!(ui.needs_render());
+  assert_eq!(ui.notify_observers().await, 0);
+
+  assert!(ui.unwatch(observer, w2));
+  assert!(!ui.unwatch(observer, w2));
+
+  observed.borrow_mut().clear();
+  let _ = ui.handle(Event::Int(0)).await;
+  assert_eq!(*observed.borrow(), vec![("observer", vec![w1])]);
+
+  // Untracked data access is not reported.
+  observed.borrow_mut().clear();
+  let _ = ui.data_mut_untracked(w1);
+  assert_eq!(ui.notify_observers().await, 0);
+
+  // Direct data access via the `Ui` is recorded as well, but observers
+  // are only notified on request.
+  let _ = ui.data_mut(w1);
+  assert!(observed.borrow().is_empty());
+  assert_eq!(ui.notify_observers().await, 1);
+  assert_eq!(*observed.borrow(), vec![("observer", vec![w1])]);
+}
+
+/// Check that changes caused by observers propagate, but that each
+/// observer is notified at most once per dispatch.
+#[tokio::test]
+async fn chained_notification() {
+  let observed = Observed::default();
+  let (mut ui, root) = new_ui();
+  let a = add_observer(&mut ui, root, "a", &observed);
+  let b = add_observer(&mut ui, root, "b", &observed);
+  let c = add_observer(&mut ui, root, "c", &observed);
+  // Observers mutate their own data when notified, meaning that the
+  // following forms a cycle.
+  ui.watch(b, a);
+  ui.watch(c, b);
+  ui.watch(a, c);
+
+  // Change the data of `c` only.
+  let _ = ui.handle(Event::Int(2)).await;
+  let expected = vec![("a", vec![c]), ("b", vec![a]), ("c", vec![b])];
+  assert_eq!(*observed.borrow(), expected);
+
+  // The change caused by the last observer is only delivered now.
+  observed.borrow_mut().clear();
+  assert_eq!(ui.notify_observers().await, 3);
+  assert_eq!(observed.borrow()[0], ("a", vec![c]));
+}
+
+/// Check that observers are notified after timers fired.
+#[tokio::test]
+async fn notification_after_timer() {
+  let observed = Observed::default();
+  let (mut ui, root) = new_ui();
+  let clock = ManualClock::default();
+  ui.set_clock(Box::new(clock.clone()));
+
+  let observer = add_observer(&mut ui, root, "observer", &observed);
+  ui.watch(observer, root);
+
+  let _ = ui.schedule(root, Duration::from_secs(1), Message::new(1));
+  clock.advance(Duration::from_secs(1));
+  assert_eq!(ui.fire_due(clock.now()).await, 1);
+  assert_eq!(*observed.borrow(), vec![("observer", vec![root])]);
+}
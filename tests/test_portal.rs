@@ -0,0 +1,200 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+mod common;
+
+use std::any::type_name;
+
+use gui::BBox;
+use gui::Cap;
+use gui::Constraint;
+use gui::Flow;
+use gui::Id;
+use gui::Layout;
+use gui::MutCap;
+use gui::Point;
+use gui::RecordingRenderer;
+use gui::Ui;
+
+use crate::common::Event;
+use crate::common::Message;
+use crate::common::TestWidget;
+use crate::common::TestWidgetDataBuilder;
+
+
+/// The area used by the renderer in all tests.
+const AREA: BBox = BBox {
+  x: 0,
+  y: 0,
+  w: 10,
+  h: 10,
+};
+
+
+/// A layout assigning a fixed bounding box to all children.
+#[derive(Debug)]
+struct FixedLayout(BBox);
+
+impl Layout for FixedLayout {
+  fn layout(&self, cap: &dyn Cap, widget: Id, _bbox: BBox) -> Vec<(Id, BBox)> {
+    cap.children(widget).map(|id| (*id, self.0)).collect()
+  }
+}
+
+
+fn bbox(x: u16, y: u16, w: u16, h: u16) -> BBox {
+  BBox { x, y, w, h }
+}
+
+fn point(x: u16, y: u16) -> Point {
+  Point { x, y }
+}
+
+/// Create a `Ui` with a root widget containing two children stacked on
+/// top of each other, the first of which has a portal as its child that
+/// extends into the area of the second one.
+fn new_ui() -> (Ui<Event, Message>, Id, Id, Id, Id) {
+  let (mut ui, root) = Ui::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let w1 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w2 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let portal = ui
+    .add_ui_widget(
+      w1,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+
+  let flow = Flow::column()
+    .child(w1, Constraint::Fixed(2))
+    .child(w2, Constraint::Fixed(8));
+  let _ = ui.set_layout(root, Some(Box::new(flow)));
+  let _ = ui.set_layout(w1, Some(Box::new(FixedLayout(bbox(1, 2, 5, 4)))));
+  assert!(!ui.set_portal(portal, true));
+  (ui, root, w1, w2, portal)
+}
+
+
+/// Check that portals are rendered after all other widgets, without
+/// being clipped to their parent.
+#[test]
+fn portal_rendering() {
+  let (mut ui, root, w1, w2, portal) = new_ui();
+  let renderer = RecordingRenderer::new(AREA);
+  ui.render(&renderer);
+
+  renderer.assert_render_order(&[root, w2, w1, portal]);
+  renderer.assert_bbox(w1, bbox(0, 0, 10, 2));
+  renderer.assert_bbox(portal, bbox(1, 2, 5, 4));
+  assert_eq!(ui.parent_id(portal), Some(w1));
+  assert!(ui.is_portal(portal));
+  assert!(!ui.is_portal(w1));
+
+  // Without a layout, a portal covers the entire renderable area.
+  let _ = ui.set_layout(w1, None);
+  ui.render(&renderer);
+  renderer.assert_bbox(portal, AREA);
+
+  assert!(ui.set_portal(portal, false));
+  ui.render(&renderer);
+  renderer.assert_render_order(&[root, w2, w1, portal]);
+  renderer.assert_bbox(portal, bbox(0, 0, 10, 2));
+}
+
+/// Check that portals nested in portals are rendered after those.
+#[test]
+fn nested_portals() {
+  let (mut ui, root, w1, w2, portal) = new_ui();
+  let nested = ui
+    .add_ui_widget(
+      portal,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let _ = ui.set_portal(nested, true);
+  let _ = ui.set_portal(w2, true);
+
+  let renderer = RecordingRenderer::new(AREA);
+  ui.render(&renderer);
+  renderer.assert_render_order(&[root, w1, w2, portal, nested]);
+  renderer.assert_bbox(nested, AREA);
+
+  let ty = type_name::<TestWidget>();
+  let expected = format!(
+    "{root} {ty}\n  {w1} {ty}\n    {portal} {ty} portal\n      {nested} {ty} portal\n  \
+     {w2} {ty} portal\n",
+  );
+  assert_eq!(ui.dump(), expected);
+}
+
+/// Check that only the necessary portals are rendered anew when
+/// rendering invalidated widgets.
+#[test]
+fn portal_render_dirty() {
+  let (mut ui, _root, w1, w2, portal) = new_ui();
+  let renderer = RecordingRenderer::new(AREA);
+  ui.render(&renderer);
+
+  // The portal overlaps the second widget and has to stay on top.
+  ui.invalidate(w2);
+  ui.render_dirty(&renderer);
+  renderer.assert_render_order(&[w2, portal]);
+
+  ui.invalidate(w1);
+  ui.render_dirty(&renderer);
+  renderer.assert_render_order(&[w1]);
+
+  ui.invalidate(portal);
+  ui.render_dirty(&renderer);
+  renderer.assert_render_order(&[portal]);
+
+  // Moving the portal causes it to be rendered at its new location.
+  let _ = ui.set_layout(w1, Some(Box::new(FixedLayout(bbox(2, 3, 5, 4)))));
+  ui.render_dirty(&renderer);
+  renderer.assert_render_order(&[w1, portal]);
+  renderer.assert_bbox(portal, bbox(2, 3, 5, 4));
+
+  ui.hide(portal);
+  ui.render_dirty(&renderer);
+  renderer.assert_not_rendered(portal);
+}
+
+/// Check that hit testing treats portals as top-most.
+#[test]
+fn hit_testing() {
+  let (mut ui, root, w1, w2, portal) = new_ui();
+  let renderer = RecordingRenderer::new(AREA);
+  assert_eq!(ui.hit_test(point(1, 3)), None);
+
+  ui.render(&renderer);
+  assert_eq!(ui.hit_test(point(0, 0)), Some(w1));
+  assert_eq!(ui.hit_test(point(1, 2)), Some(portal));
+  assert_eq!(ui.hit_test(point(5, 5)), Some(portal));
+  assert_eq!(ui.hit_test(point(6, 5)), Some(w2));
+  assert_eq!(ui.hit_test(point(9, 9)), Some(w2));
+  assert_eq!(ui.hit_test(point(10, 9)), None);
+
+  ui.hide(portal);
+  ui.render_dirty(&renderer);
+  assert_eq!(ui.hit_test(point(1, 2)), Some(w2));
+
+  ui.hide(w2);
+  ui.render_dirty(&renderer);
+  assert_eq!(ui.hit_test(point(1, 2)), Some(root));
+}
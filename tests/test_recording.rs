@@ -0,0 +1,133 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+mod common;
+
+use std::any::type_name;
+use std::any::TypeId;
+
+use gui::BBox;
+use gui::Constraint;
+use gui::DisplayItem;
+use gui::Flow;
+use gui::Id;
+use gui::MutCap;
+use gui::RecordingRenderer;
+use gui::RenderedWidget;
+use gui::Ui;
+
+use crate::common::Event;
+use crate::common::Message;
+use crate::common::TestWidget;
+use crate::common::TestWidgetDataBuilder;
+
+
+/// The area used by the renderer in all tests.
+const AREA: BBox = BBox {
+  x: 0,
+  y: 0,
+  w: 10,
+  h: 4,
+};
+
+
+/// Create a `Ui` with a root widget containing two children laid out
+/// in a column.
+fn new_ui() -> (Ui<Event, Message>, Id, Id, Id) {
+  let (mut ui, root) = Ui::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let top = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let bottom = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let flow = Flow::column()
+    .child(top, Constraint::Fixed(1))
+    .child(bottom, Constraint::Fill);
+  let _ = ui.set_layout(root, Some(Box::new(flow)));
+  (ui, root, top, bottom)
+}
+
+
+/// Check that all renderer invocations end up in the display list.
+#[test]
+fn display_list() {
+  let (ui, root, top, bottom) = new_ui();
+  let renderer = RecordingRenderer::new(AREA);
+  ui.render(&renderer);
+
+  let widget = |id, bbox| RenderedWidget {
+    type_id: TypeId::of::<TestWidget>(),
+    type_name: type_name::<TestWidget>(),
+    id,
+    bbox,
+  };
+  let root = widget(root, AREA);
+  let top = widget(top, BBox { h: 1, ..AREA });
+  let bottom = widget(bottom, BBox { y: 1, h: 3, ..AREA });
+  let expected = vec![
+    DisplayItem::PreRender,
+    DisplayItem::Render(root),
+    DisplayItem::Render(bottom),
+    DisplayItem::RenderDone(bottom),
+    DisplayItem::Render(top),
+    DisplayItem::RenderDone(top),
+    DisplayItem::RenderDone(root),
+    DisplayItem::PostRender,
+  ];
+  assert_eq!(renderer.display_list(), expected);
+  assert_eq!(renderer.rendered(), vec![root, bottom, top]);
+}
+
+/// Check that the assertion helpers work as expected.
+#[test]
+fn assertions() {
+  let (mut ui, root, top, bottom) = new_ui();
+  let renderer = RecordingRenderer::new(AREA);
+  ui.render(&renderer);
+
+  renderer.assert_render_order(&[root, bottom, top]);
+  renderer.assert_bbox(root, AREA);
+  renderer.assert_bbox(top, BBox { h: 1, ..AREA });
+
+  // The display list only covers the last render pass.
+  ui.hide(bottom);
+  ui.render(&renderer);
+  renderer.assert_render_order(&[root, top]);
+  renderer.assert_not_rendered(bottom);
+  assert_eq!(renderer.bbox(bottom), None);
+  assert_eq!(renderer.rendered_ids(), vec![root, top]);
+}
+
+/// Check that a wrong bounding box is reported.
+#[test]
+#[should_panic(expected = "unexpected bounding box")]
+fn assert_bbox_mismatch() {
+  let (ui, root, _, _) = new_ui();
+  let renderer = RecordingRenderer::new(AREA);
+  ui.render(&renderer);
+
+  renderer.assert_bbox(root, BBox::default());
+}
+
+/// Check that a wrong render order is reported.
+#[test]
+#[should_panic(expected = "unexpected render order")]
+fn assert_render_order_mismatch() {
+  let (ui, root, top, bottom) = new_ui();
+  let renderer = RecordingRenderer::new(AREA);
+  ui.render(&renderer);
+
+  renderer.assert_render_order(&[root, top, bottom]);
+}
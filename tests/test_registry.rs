@@ -0,0 +1,125 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+mod common;
+
+use std::any::TypeId;
+use std::cell::Cell;
+use std::rc::Rc;
+
+use gui::derive::Handleable;
+use gui::derive::Widget;
+use gui::BBox;
+use gui::Cap;
+use gui::Id;
+use gui::Object;
+use gui::Renderable;
+use gui::Renderer;
+use gui::RendererRegistry;
+use gui::Ui;
+
+use crate::common::Event;
+use crate::common::Message;
+use crate::common::TestWidget;
+use crate::common::TestWidgetDataBuilder;
+
+
+#[derive(Debug, Widget, Handleable)]
+#[gui(Event = Event, Message = Message)]
+struct Label {
+  id: Id,
+}
+
+
+/// A renderer dispatching through a `RendererRegistry`.
+#[derive(Debug)]
+struct RegistryRenderer {
+  registry: RendererRegistry,
+}
+
+impl Renderer for RegistryRenderer {
+  fn renderable_area(&self) -> BBox {
+    BBox {
+      x: 0,
+      y: 0,
+      w: 5,
+      h: 5,
+    }
+  }
+
+  fn render(&self, object: &dyn Renderable, cap: &dyn Cap, bbox: BBox) -> BBox {
+    self.registry.render(object, cap, bbox)
+  }
+}
+
+
+/// Create a `Ui` with a `TestWidget` as root and a `Label` as its
+/// child.
+fn new_ui() -> (Ui<Event, Message>, Id, Id) {
+  let (mut ui, root) = Ui::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let label = ui.add_ui_widget(root, || (), |id, _cap| Label { id }).id();
+  (ui, root, label)
+}
+
+
+/// Check that objects are dispatched to the function registered for
+/// their type.
+#[test]
+fn dispatch_by_type() {
+  let (ui, root, label) = new_ui();
+  let rendered = Rc::new(Cell::new(0));
+  let rendered1 = rendered.clone();
+  let rendered2 = rendered.clone();
+
+  let registry = RendererRegistry::new()
+    .on::<TestWidget, _>(move |widget, _cap, bbox| {
+      assert_eq!(widget.id(), root);
+      rendered1.set(rendered1.get() + 1);
+      BBox { w: 1, ..bbox }
+    })
+    .on::<Label, _>(move |widget, cap, bbox| {
+      assert_eq!(widget.id(), label);
+      assert_eq!(cap.parent_id(widget.id()), Some(root));
+      assert_eq!(bbox.w, 1);
+      rendered2.set(rendered2.get() + 10);
+      bbox
+    });
+  assert!(registry.is_registered(TypeId::of::<Label>()));
+  assert!(!registry.is_registered(TypeId::of::<u8>()));
+
+  let renderer = RegistryRenderer { registry };
+  ui.render(&renderer);
+  assert_eq!(rendered.get(), 11);
+}
+
+/// Check that the fallback is used for objects of unknown type.
+#[test]
+fn fallback() {
+  let (ui, _, _) = new_ui();
+  let fallback = Rc::new(Cell::new(0));
+  let fallback1 = fallback.clone();
+  let registry = RendererRegistry::new()
+    .on::<TestWidget, _>(|_widget, _cap, bbox| bbox)
+    .fallback(move |widget, _cap, bbox| {
+      assert!(widget.is::<Label>());
+      fallback1.set(fallback1.get() + 1);
+      bbox
+    });
+
+  let renderer = RegistryRenderer { registry };
+  ui.render(&renderer);
+  assert_eq!(fallback.get(), 1);
+}
+
+/// Check that the default fallback panics.
+#[test]
+#[should_panic(expected = "is unknown to the renderer")]
+fn default_fallback_panics() {
+  let (ui, _, _) = new_ui();
+  let registry = RendererRegistry::default().on::<TestWidget, _>(|_widget, _cap, bbox| bbox);
+  let renderer = RegistryRenderer { registry };
+  ui.render(&renderer);
+}
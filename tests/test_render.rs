@@ -10,6 +10,8 @@ use std::cell::Cell;
 
 use gui::BBox;
 use gui::Cap;
+use gui::Constraint;
+use gui::Flow;
 use gui::Id;
 use gui::MutCap;
 use gui::Object;
@@ -84,18 +86,18 @@ impl Renderer for CountingRenderer {
 fn render_is_called_for_each_widget() {
   let renderer = CountingRenderer::new();
   let (mut ui, root) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
+    || Box::new(TestWidgetDataBuilder::new().build()),
     |id, _cap| Box::new(TestWidget::new(id)),
   );
   let _ = ui.add_ui_widget(
     root,
     || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
+    |id, _cap| TestWidget::new(id),
   );
   let _ = ui.add_ui_widget(
     root,
     || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
+    |id, _cap| TestWidget::new(id),
   );
 
   ui.render(&renderer);
@@ -110,23 +112,27 @@ fn render_is_called_for_each_widget() {
 fn render_honors_visibility_flag() {
   let renderer = CountingRenderer::new();
   let (mut ui, root) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w1 = ui.add_ui_widget(
-    root,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w2 = ui.add_ui_widget(
-    root,
-    || TestWidgetDataBuilder::new().build(),
+    || Box::new(TestWidgetDataBuilder::new().build()),
     |id, _cap| Box::new(TestWidget::new(id)),
   );
+  let w1 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w2 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
   let _ = ui.add_ui_widget(
     w2,
     || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
+    |id, _cap| TestWidget::new(id),
   );
 
   ui.hide(w1);
@@ -210,18 +216,16 @@ impl Widget<Event, Message> for TestNoBBoxWidget {
 fn render_is_omitted_for_empty_bbox() {
   let renderer = CountingRenderer::new();
   let (mut ui, root) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
+    || Box::new(TestWidgetDataBuilder::new().build()),
     |id, _cap| Box::new(TestWidget::new(id)),
   );
-  let no_bbox = ui.add_ui_widget(
-    root,
-    || Box::new(()),
-    |id, _cap| Box::new(TestNoBBoxWidget { id }),
-  );
+  let no_bbox = ui
+    .add_ui_widget(root, || (), |id, _cap| TestNoBBoxWidget { id })
+    .id();
   let _ = ui.add_ui_widget(
     no_bbox,
     || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
+    |id, _cap| TestWidget::new(id),
   );
 
   ui.render(&renderer);
@@ -254,9 +258,9 @@ impl BBoxRenderer {
   fn check_bbox(&self, widget: &TestWidget, bbox: BBox) {
     let mut expected = self.renderable_area();
 
-    if widget.id() == unsafe { *CONTAINER.as_ref().unwrap() } {
+    if widget.id() == unsafe { CONTAINER.unwrap() } {
       expected.w -= 10;
-    } else if widget.id() == unsafe { *WIDGET.as_ref().unwrap() } {
+    } else if widget.id() == unsafe { WIDGET.unwrap() } {
       expected.w -= 10;
       expected.h -= 10;
     }
@@ -281,9 +285,9 @@ impl Renderer for BBoxRenderer {
     let widget = object.downcast_ref::<TestWidget>().unwrap();
     let () = self.check_bbox(widget, bbox);
 
-    if widget.id() == unsafe { *ROOT.as_ref().unwrap() } {
+    if widget.id() == unsafe { ROOT.unwrap() } {
       bbox.w -= 10
-    } else if widget.id() == unsafe { *CONTAINER.as_ref().unwrap() } {
+    } else if widget.id() == unsafe { CONTAINER.unwrap() } {
       bbox.h -= 10
     }
     bbox
@@ -300,19 +304,23 @@ impl Renderer for BBoxRenderer {
 fn bounding_box_is_properly_sized() {
   let renderer = BBoxRenderer::new();
   let (mut ui, root) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let cont = ui.add_ui_widget(
-    root,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let widget = ui.add_ui_widget(
-    cont,
-    || TestWidgetDataBuilder::new().build(),
+    || Box::new(TestWidgetDataBuilder::new().build()),
     |id, _cap| Box::new(TestWidget::new(id)),
   );
+  let cont = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let widget = ui
+    .add_ui_widget(
+      cont,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
 
   unsafe {
     ROOT = Some(root);
@@ -324,3 +332,222 @@ fn bounding_box_is_properly_sized() {
 
   assert_eq!(renderer.valid_bbox_count.get(), 6)
 }
+
+
+/// Check that only invalidated widgets and those covering them get
+/// re-rendered by `Ui::render_dirty`.
+#[test]
+fn render_dirty_renders_invalidated_widgets() {
+  let renderer = CountingRenderer::new();
+  let (mut ui, root) = Ui::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let w1 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w2 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  assert!(ui.needs_render());
+
+  // Nothing got rendered so far and so everything is rendered.
+  ui.render_dirty(&renderer);
+  assert_eq!(renderer.total_render_count.get(), 3);
+  assert!(!ui.needs_render());
+
+  // Without any invalidation, nothing should be rendered.
+  ui.render_dirty(&renderer);
+  assert_eq!(renderer.pre_render_count.get(), 1);
+  assert_eq!(renderer.total_render_count.get(), 3);
+
+  // `w1` has the highest z-index and is the only one to render.
+  ui.invalidate(w1);
+  assert!(ui.needs_render());
+  ui.render_dirty(&renderer);
+  assert_eq!(renderer.pre_render_count.get(), 2);
+  assert_eq!(renderer.post_render_count.get(), 2);
+  assert_eq!(renderer.total_render_count.get(), 4);
+  assert_eq!(renderer.total_render_done_count.get(), 4);
+
+  // `w2` is covered by `w1`, which has to be rendered again as well.
+  let _ = ui.data_mut(w2);
+  assert!(ui.needs_render());
+  ui.render_dirty(&renderer);
+  assert_eq!(renderer.total_render_count.get(), 6);
+
+  // Hiding a widget requires its parent to render again.
+  ui.hide(w1);
+  assert!(ui.needs_render());
+  ui.render_dirty(&renderer);
+  assert_eq!(renderer.total_render_count.get(), 8);
+  assert!(!ui.needs_render());
+}
+
+
+/// Verify that changes in visibility and focus invalidate widgets.
+#[test]
+fn visibility_and_focus_invalidate() {
+  let renderer = CountingRenderer::new();
+  let (mut ui, root) = Ui::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let w1 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w2 = ui
+    .add_ui_widget(
+      w1,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+
+  ui.hide(w1);
+  ui.render(&renderer);
+  assert!(!ui.needs_render());
+
+  // Invalidating a widget that is not displayed has no effect.
+  ui.invalidate(w2);
+  assert!(!ui.needs_render());
+
+  // Showing an already visible widget does not change anything.
+  ui.show(root);
+  assert!(!ui.needs_render());
+
+  ui.focus(w2);
+  assert!(ui.needs_render());
+  ui.render_dirty(&renderer);
+  assert_eq!(renderer.total_render_count.get(), 3);
+
+  ui.focus(w2);
+  assert!(!ui.needs_render());
+}
+
+
+/// Check that rendering a very deep widget tree does not overflow the
+/// stack.
+#[test]
+fn render_deep_widget_tree() {
+  const DEPTH: u64 = 100_000;
+
+  let renderer = CountingRenderer::new();
+  let (mut ui, root) = Ui::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let mut leaf = root;
+  for _ in 1..DEPTH {
+    leaf = ui
+      .add_ui_widget(
+        leaf,
+        || TestWidgetDataBuilder::new().build(),
+        |id, _cap| TestWidget::new(id),
+      )
+      .id();
+  }
+
+  ui.focus(leaf);
+  assert!(ui.is_displayed(leaf));
+
+  ui.render(&renderer);
+  assert_eq!(renderer.total_render_count.get(), DEPTH);
+  assert_eq!(renderer.total_render_done_count.get(), DEPTH);
+
+  ui.invalidate(leaf);
+  ui.render_dirty(&renderer);
+  assert_eq!(renderer.total_render_count.get(), DEPTH + 1);
+  assert_eq!(renderer.total_render_done_count.get(), DEPTH + 1);
+}
+
+
+/// A renderer reporting an inner bounding box twice the size of the
+/// one provided.
+#[derive(Debug)]
+struct GrowingRenderer(CountingRenderer);
+
+impl Renderer for GrowingRenderer {
+  fn renderable_area(&self) -> BBox {
+    self.0.renderable_area()
+  }
+
+  fn render(&self, object: &dyn Renderable, cap: &dyn Cap, bbox: BBox) -> BBox {
+    let bbox = self.0.render(object, cap, bbox);
+    BBox {
+      w: bbox.w * 2,
+      h: bbox.h * 2,
+      ..bbox
+    }
+  }
+
+  fn render_done(&self, object: &dyn Renderable, cap: &dyn Cap, bbox: BBox) {
+    self.0.render_done(object, cap, bbox)
+  }
+}
+
+
+/// Check that widgets outside of the renderable area are culled along
+/// with their children.
+#[test]
+fn render_culls_widgets_outside_renderable_area() {
+  let renderer = GrowingRenderer(CountingRenderer::new());
+  let (mut ui, root) = Ui::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let w1 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w2 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let _ = ui.add_ui_widget(
+    w2,
+    || TestWidgetDataBuilder::new().build(),
+    |id, _cap| TestWidget::new(id),
+  );
+  // The root's inner bounding box is 20x20, but only the upper 10x10
+  // are visible. `w2` is placed below that.
+  let flow = Flow::column()
+    .child(w1, Constraint::Fixed(10))
+    .child(w2, Constraint::Fixed(10));
+  let _ = ui.set_layout(root, Some(Box::new(flow)));
+  assert_eq!(ui.culled_count(), 0);
+
+  ui.render(&renderer);
+  assert_eq!(renderer.0.total_render_count.get(), 2);
+  assert_eq!(renderer.0.total_render_done_count.get(), 2);
+  assert_eq!(ui.culled_count(), 1);
+
+  ui.invalidate(w2);
+  ui.render_dirty(&renderer);
+  assert_eq!(renderer.0.total_render_count.get(), 2);
+  assert_eq!(ui.culled_count(), 1);
+
+  // Hidden widgets do not count as culled.
+  ui.hide(w2);
+  ui.render(&renderer);
+  assert_eq!(renderer.0.total_render_count.get(), 4);
+  assert_eq!(ui.culled_count(), 0);
+}
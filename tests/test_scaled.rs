@@ -0,0 +1,176 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+mod common;
+
+use std::cell::RefCell;
+
+use gui::BBox;
+use gui::Cap;
+use gui::Constraint;
+use gui::Coord;
+use gui::Flow;
+use gui::Id;
+use gui::MutCap;
+use gui::Renderable;
+use gui::Renderer;
+use gui::ScaledRenderer;
+use gui::Size;
+use gui::Ui;
+
+use crate::common::Event;
+use crate::common::Message;
+use crate::common::TestWidget;
+use crate::common::TestWidgetDataBuilder;
+
+
+/// A renderer working with device coordinates of type `T`, recording
+/// the bounding boxes it is asked to render widgets in.
+#[derive(Debug)]
+struct DeviceRenderer<T> {
+  area: BBox<T>,
+  rendered: RefCell<Vec<(Id, BBox<T>)>>,
+}
+
+impl<T> DeviceRenderer<T>
+where
+  T: Coord,
+{
+  fn new(area: BBox<T>) -> Self {
+    Self {
+      area,
+      rendered: RefCell::new(Vec::new()),
+    }
+  }
+}
+
+impl<T> Renderer<T> for DeviceRenderer<T>
+where
+  T: Coord,
+{
+  fn renderable_area(&self) -> BBox<T> {
+    self.area
+  }
+
+  fn render(&self, object: &dyn Renderable, _cap: &dyn Cap, bbox: BBox<T>) -> BBox<T> {
+    self.rendered.borrow_mut().push((object.id(), bbox));
+    bbox
+  }
+}
+
+
+/// Create a `Ui` with a root widget containing two children laid out
+/// in a row, each filling half of the available width.
+fn ui_with_row() -> (Ui<Event, Message>, Id, Id) {
+  let (mut ui, root) = Ui::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let left = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let right = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let flow = Flow::row()
+    .child(left, Constraint::Fill)
+    .child(right, Constraint::Fill);
+  let _ = ui.set_layout(ui.root_id(), Some(Box::new(flow)));
+  (ui, left, right)
+}
+
+
+/// Check that a display wider than `u16` can be rendered to.
+#[test]
+fn wide_display() {
+  let (ui, left, right) = ui_with_row();
+  let area = BBox::<u32> {
+    x: 0,
+    y: 0,
+    w: 100_000,
+    h: 600,
+  };
+  let device = DeviceRenderer::new(area);
+  let renderer = ScaledRenderer::new(&device, 2.0);
+  assert_eq!(
+    renderer.renderable_area(),
+    BBox {
+      x: 0,
+      y: 0,
+      w: 50_000,
+      h: 300,
+    }
+  );
+
+  ui.render(&renderer);
+
+  let rendered = device.rendered.borrow();
+  assert_eq!(rendered[0], (ui.root_id(), area));
+  assert!(rendered.contains(&(left, BBox { w: 50_000, ..area })));
+  assert!(rendered.contains(&(
+    right,
+    BBox {
+      x: 50_000,
+      w: 50_000,
+      ..area
+    }
+  )));
+}
+
+/// Check that fractional scale factors map logical coordinates to
+/// fractional device ones.
+#[test]
+fn fractional_scale() {
+  let (ui, left, right) = ui_with_row();
+  let area = BBox::<f64> {
+    x: 0.0,
+    y: 0.0,
+    w: 12.5,
+    h: 4.0,
+  };
+  let device = DeviceRenderer::new(area);
+  let renderer = ScaledRenderer::new(&device, 1.25);
+  assert_eq!(
+    renderer.renderable_area(),
+    BBox {
+      x: 0,
+      y: 0,
+      w: 10,
+      h: 3,
+    }
+  );
+  let size = renderer.measure(&TestWidget::new(left), &ui, Size { w: 3, h: 1 });
+  assert_eq!(size, Size { w: 3, h: 1 });
+
+  ui.render(&renderer);
+
+  let rendered = device.rendered.borrow();
+  let expected_left = BBox {
+    x: 0.0,
+    y: 0.0,
+    w: 6.25,
+    h: 3.75,
+  };
+  let expected_right = BBox {
+    x: 6.25,
+    ..expected_left
+  };
+  assert!(rendered.contains(&(left, expected_left)));
+  assert!(rendered.contains(&(right, expected_right)));
+}
+
+/// Check that a non-positive scale factor is rejected.
+#[test]
+#[should_panic(expected = "Scale factor must be finite and greater than zero")]
+fn invalid_scale() {
+  let device = DeviceRenderer::new(BBox::<u32>::default());
+  let _ = ScaledRenderer::new(&device, 0.0);
+}
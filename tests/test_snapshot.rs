@@ -0,0 +1,174 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+#![cfg(feature = "serde")]
+
+mod common;
+
+use gui::Cap;
+use gui::Id;
+use gui::MutCap;
+use gui::Point;
+use gui::RestoreError;
+use gui::Size;
+use gui::Snapshot;
+use gui::SnapshotRegistry;
+use gui::TypedId;
+use gui::Ui;
+use gui::Viewport;
+
+use serde_json::from_value;
+use serde_json::to_value;
+use serde_json::Value;
+
+use crate::common::Event;
+use crate::common::Message;
+use crate::common::TestWidget;
+use crate::common::TestWidgetDataBuilder;
+
+
+/// Create a `Ui` with a root widget, two panes with typed data, and a
+/// child inside the second pane.
+#[allow(clippy::type_complexity)]
+fn new_ui() -> (
+  Ui<Event, Message>,
+  TypedId<TestWidget, u64>,
+  TypedId<TestWidget, String>,
+  Id,
+) {
+  let (mut ui, root) = Ui::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let pane1 = ui.add_ui_widget(root, || 1u64, |id, _cap| TestWidget::new(id));
+  let pane2 = ui.add_ui_widget(root, || "foo".to_string(), |id, _cap| TestWidget::new(id));
+  let child = ui
+    .add_ui_widget(
+      pane2.id(),
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  (ui, pane1, pane2, child)
+}
+
+/// Create a registry for the data types used by the panes.
+fn registry() -> SnapshotRegistry<Value> {
+  SnapshotRegistry::new()
+    .on::<u64, _, _>(
+      |data| to_value(data).unwrap(),
+      |data, value| *data = from_value(value.clone()).unwrap(),
+    )
+    .on::<String, _, _>(
+      |data| to_value(data).unwrap(),
+      |data, value| *data = from_value(value.clone()).unwrap(),
+    )
+}
+
+
+/// Check that the state of a `Ui` survives a round trip through a
+/// serialized snapshot.
+#[test]
+fn snapshot_round_trip() {
+  let viewport = Viewport {
+    content: Size { w: 10, h: 20 },
+    offset: Point { x: 0, y: 5 },
+  };
+
+  let (mut ui, pane1, pane2, child) = new_ui();
+  ui.hide(pane1.id());
+  ui.focus(child);
+  let _ = ui.set_viewport(pane2.id(), Some(viewport));
+  *ui.get_mut(pane1) = 42;
+  ui.get_mut(pane2).push_str("bar");
+
+  let snapshot = Snapshot::capture(&ui, &registry());
+  assert_eq!(snapshot.widgets.len(), 4);
+  assert_eq!(snapshot.focused, Some(3));
+  assert_eq!(snapshot.widgets[0].parent, None);
+  assert_eq!(snapshot.widgets[0].data, None);
+  assert_eq!(snapshot.widgets[3].parent, Some(2));
+  assert_eq!(snapshot.widgets[3].data, None);
+
+  let json = serde_json::to_string(&snapshot).unwrap();
+  let snapshot = serde_json::from_str::<Snapshot<Value>>(&json).unwrap();
+
+  let (mut restored, pane1, pane2, child) = new_ui();
+  let () = snapshot.restore(&mut restored, &registry()).unwrap();
+  assert_eq!(restored.dump(), ui.dump());
+  assert!(restored.is_focused(child));
+  assert!(!restored.is_visible(pane1.id()));
+  assert_eq!(restored.viewport(pane2.id()), Some(viewport));
+  assert_eq!(*restored.get(pane1), 42);
+  assert_eq!(restored.get(pane2), "foobar");
+}
+
+/// Check that snapshots not matching a `Ui` are rejected without
+/// modifying it.
+#[test]
+fn snapshot_mismatch() {
+  let (mut ui, pane1, _pane2, _child) = new_ui();
+  ui.hide(pane1.id());
+  *ui.get_mut(pane1) = 42;
+  let _ = ui.add_ui_widget(
+    pane1.id(),
+    || TestWidgetDataBuilder::new().build(),
+    |id, _cap| TestWidget::new(id),
+  );
+  let snapshot = Snapshot::capture(&ui, &registry());
+
+  let (mut fresh, pane1, _pane2, _child) = new_ui();
+  let err = snapshot.restore(&mut fresh, &registry()).unwrap_err();
+  assert_eq!(err, RestoreError::UnknownWidget { path: vec![0, 0] });
+  assert_eq!(err.to_string(), "widget at path [0, 0] does not exist");
+  assert!(fresh.is_visible(pane1.id()));
+  assert_eq!(*fresh.get(pane1), 1);
+
+  let snapshot = Snapshot::capture(&fresh, &registry());
+  let err = snapshot
+    .restore(&mut fresh, &SnapshotRegistry::new())
+    .unwrap_err();
+  assert_eq!(err, RestoreError::UnknownData { path: vec![0] });
+
+  let mut snapshot = Snapshot::capture(&fresh, &registry());
+  snapshot.widgets[1].parent = Some(1);
+  let err = snapshot.restore(&mut fresh, &registry()).unwrap_err();
+  assert_eq!(err, RestoreError::Malformed { index: 1 });
+}
+
+/// Check that a snapshot of a very deep widget tree can be captured,
+/// serialized, and restored.
+#[test]
+fn snapshot_deep_widget_tree() {
+  const DEPTH: usize = 100_000;
+
+  let deep_ui = || {
+    let (mut ui, root) = Ui::new(
+      || Box::new(TestWidgetDataBuilder::new().build()),
+      |id, _cap| Box::new(TestWidget::new(id)),
+    );
+    let mut leaf = root;
+    for _ in 1..DEPTH {
+      leaf = ui
+        .add_ui_widget(leaf, || 1u64, |id, _cap| TestWidget::new(id))
+        .id();
+    }
+    (ui, leaf)
+  };
+
+  let (mut ui, leaf) = deep_ui();
+  ui.focus(leaf);
+  *ui.data_mut(leaf).downcast_mut::<u64>().unwrap() = 42;
+
+  let snapshot = Snapshot::capture(&ui, &registry());
+  assert_eq!(snapshot.widgets.len(), DEPTH);
+  assert_eq!(snapshot.focused, Some(DEPTH - 1));
+
+  let json = serde_json::to_string(&snapshot).unwrap();
+  let snapshot = serde_json::from_str::<Snapshot<Value>>(&json).unwrap();
+
+  let (mut restored, leaf) = deep_ui();
+  let () = snapshot.restore(&mut restored, &registry()).unwrap();
+  assert!(restored.is_focused(leaf));
+  assert_eq!(restored.data(leaf).downcast_ref::<u64>(), Some(&42));
+}
@@ -0,0 +1,147 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+mod common;
+
+use gui::BBox;
+use gui::Cap;
+use gui::CellStyle;
+use gui::Color;
+use gui::Emphasis;
+use gui::Id;
+use gui::Insets;
+use gui::MutCap;
+use gui::RecordingRenderer;
+use gui::Style;
+use gui::StyleKey;
+use gui::Theme;
+use gui::Ui;
+
+use crate::common::Event;
+use crate::common::Message;
+use crate::common::TestWidget;
+use crate::common::TestWidgetDataBuilder;
+
+
+/// A custom style property for the name of a font.
+struct Font;
+
+impl StyleKey for Font {
+  type Value = &'static str;
+}
+
+
+/// Create a `Ui` with a root widget, a child, and a grandchild.
+fn new_ui() -> (Ui<Event, Message>, [Id; 3]) {
+  let (mut ui, root) = Ui::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let child = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let grandchild = ui
+    .add_ui_widget(
+      child,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  (ui, [root, child, grandchild])
+}
+
+
+/// Check that custom style properties can be set, retrieved, and
+/// removed.
+#[test]
+fn custom_properties() {
+  let mut style = Style::new().with::<Font>("mono");
+  assert_eq!(style.get::<Font>(), Some(&"mono"));
+  assert_eq!(style.set::<Font>("serif"), Some("mono"));
+  assert_eq!(style.remove::<Font>(), Some("serif"));
+  assert_eq!(style.get::<Font>(), None);
+
+  let style = Style::new().fg(Color::Red).emphasis(Emphasis {
+    bold: true,
+    ..Default::default()
+  });
+  let expected = CellStyle {
+    fg: Color::Red,
+    bg: Color::Default,
+    emphasis: Emphasis {
+      bold: true,
+      ..Default::default()
+    },
+  };
+  assert_eq!(style.cell_style(), expected);
+}
+
+/// Check that unset style properties are inherited from parents.
+#[test]
+fn style_inheritance() {
+  let (mut ui, [root, child, grandchild]) = new_ui();
+  let style = ui.style(grandchild);
+  assert_eq!(style.fg, None);
+  assert_eq!(style.get::<Font>(), None);
+
+  let root_style = Style::new()
+    .fg(Color::Red)
+    .padding(Insets::uniform(1))
+    .with::<Font>("mono");
+  assert!(ui.set_style(root, Some(root_style)).is_none());
+  let _ = ui.set_style(child, Some(Style::new().bg(Color::Blue).with::<Font>("serif")));
+
+  let style = ui.style(grandchild);
+  assert_eq!(style.fg, Some(Color::Red));
+  assert_eq!(style.bg, Some(Color::Blue));
+  assert_eq!(style.padding, Some(Insets::uniform(1)));
+  assert_eq!(style.get::<Font>(), Some(&"serif"));
+
+  let style = ui.style(root);
+  assert_eq!(style.bg, None);
+  assert_eq!(style.get::<Font>(), Some(&"mono"));
+
+  let previous = ui.set_style(child, None).unwrap();
+  assert_eq!(previous.bg, Some(Color::Blue));
+  assert_eq!(ui.style(grandchild).bg, None);
+}
+
+/// Check that the theme provides defaults and can be swapped at
+/// runtime, causing everything to be rendered anew.
+#[test]
+fn theme_swapping() {
+  let (mut ui, [root, child, grandchild]) = new_ui();
+  let renderer = RecordingRenderer::new(BBox {
+    x: 0,
+    y: 0,
+    w: 10,
+    h: 10,
+  });
+  ui.render(&renderer);
+
+  let _ = ui.set_style(child, Some(Style::new().fg(Color::Green)));
+  let theme = Theme::new(Style::new().fg(Color::White).bg(Color::Black))
+    .with_widget_style::<TestWidget>(Style::new().bg(Color::Indexed(8)));
+  let _ = ui.set_theme(theme);
+  assert_eq!(ui.theme().base().fg, Some(Color::White));
+
+  let style = ui.style(root);
+  assert_eq!(style.fg, Some(Color::White));
+  assert_eq!(style.bg, Some(Color::Indexed(8)));
+  let style = ui.style(grandchild);
+  assert_eq!(style.fg, Some(Color::Green));
+  assert_eq!(style.bg, Some(Color::Indexed(8)));
+
+  assert!(ui.needs_render());
+  ui.render_dirty(&renderer);
+  renderer.assert_render_order(&[root, child, grandchild]);
+
+  let previous = ui.set_theme(Theme::default());
+  assert_eq!(previous.base().bg, Some(Color::Black));
+  assert_eq!(ui.style(root).fg, None);
+  assert_eq!(ui.style(grandchild).fg, Some(Color::Green));
+}
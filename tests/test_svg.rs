@@ -0,0 +1,134 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+mod common;
+
+use gui::BBox;
+use gui::CellBuffer;
+use gui::CellStyle;
+use gui::Constraint;
+use gui::Flow;
+use gui::Id;
+use gui::Insets;
+use gui::MutCap;
+use gui::Size;
+use gui::SvgRenderer;
+use gui::Ui;
+
+use crate::common::Event;
+use crate::common::Message;
+use crate::common::TestWidget;
+use crate::common::TestWidgetDataBuilder;
+
+
+/// Create a `Ui` with a root widget containing two children laid out
+/// in a row.
+fn new_ui() -> (Ui<Event, Message>, Id, Id, Id) {
+  let (mut ui, root) = Ui::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let left = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let right = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let flow = Flow::row()
+    .child(left, Constraint::Fixed(3))
+    .child(right, Constraint::Fill);
+  let _ = ui.set_layout(root, Some(Box::new(flow)));
+  (ui, root, left, right)
+}
+
+/// Create a `CellBuffer` that frames `TestWidget`s and insets them by
+/// one.
+fn new_buffer() -> CellBuffer {
+  CellBuffer::new(Size { w: 10, h: 4 }).on::<TestWidget, _>(|_widget, _cap, canvas| {
+    let () = canvas.frame('#', CellStyle::default());
+    canvas.bbox().inset(Insets::uniform(1))
+  })
+}
+
+
+/// Check that bounding boxes get recorded in rendering order, i.e.,
+/// with widgets higher in the z-order coming later.
+#[test]
+fn record_bboxes() {
+  let (ui, root, left, right) = new_ui();
+  let renderer = SvgRenderer::new(new_buffer());
+  ui.render(&renderer);
+
+  let bbox = |x, y, w, h| BBox { x, y, w, h };
+  let expected = vec![
+    (root, bbox(0, 0, 10, 4)),
+    (right, bbox(4, 1, 5, 2)),
+    (left, bbox(1, 1, 3, 2)),
+  ];
+  assert_eq!(renderer.bboxes(), expected);
+}
+
+/// Check the SVG and HTML output, with the focused widget raised to
+/// the top of the z-order and highlighted.
+#[test]
+fn export() {
+  let (mut ui, _, _, right) = new_ui();
+  ui.focus(right);
+
+  let renderer = SvgRenderer::new(new_buffer());
+  ui.render(&renderer);
+
+  let svg = renderer.to_svg();
+  let expected = r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 4" width="100" height="40">
+<style>text { font: 0.8px monospace; }</style>
+<g data-id="0" data-z="0">
+<title>0 test_svg::common::TestWidget bbox=(0, 0, 10, 4) inner=(1, 1, 8, 2)</title>
+<rect x="0" y="0" width="10" height="4" fill="#4e79a7" fill-opacity="0.3" stroke="#000000" stroke-width="0.1"/>
+<text x="0" y="0.8">0 TestWidget</text>
+</g>
+<g data-id="1" data-z="1">
+<title>1 test_svg::common::TestWidget bbox=(1, 1, 3, 2) inner=(2, 2, 1, 0)</title>
+<rect x="1" y="1" width="3" height="2" fill="#f28e2b" fill-opacity="0.3" stroke="#000000" stroke-width="0.1"/>
+<text x="1" y="1.8">1 TestWidget</text>
+</g>
+<g data-id="2" data-z="2" data-focused="true">
+<title>2 test_svg::common::TestWidget bbox=(4, 1, 5, 2) inner=(5, 2, 3, 0)</title>
+<rect x="4" y="1" width="5" height="2" fill="#f28e2b" fill-opacity="0.3" stroke="#e15759" stroke-width="0.3"/>
+<text x="4" y="1.8">2 TestWidget</text>
+</g>
+</svg>
+"##;
+  assert_eq!(svg, expected);
+
+  let html = renderer.to_html();
+  assert!(html.starts_with("<!DOCTYPE html>"));
+  assert!(html.contains(&svg));
+}
+
+/// Check that a partial render pass keeps the widgets that were not
+/// rendered anew and does not clear the wrapped renderer.
+#[test]
+fn render_dirty() {
+  let (mut ui, _, left, _) = new_ui();
+  let renderer = SvgRenderer::new(new_buffer());
+  ui.render(&renderer);
+
+  let bboxes = renderer.bboxes();
+  let svg = renderer.to_svg();
+  let contents = renderer.inner().to_string();
+
+  ui.invalidate(left);
+  ui.render_dirty(&renderer);
+  assert_eq!(renderer.bboxes(), bboxes);
+  assert_eq!(renderer.to_svg(), svg);
+  assert_eq!(renderer.inner().to_string(), contents);
+  assert_eq!(renderer.inner().cell(0, 0).unwrap().ch, '#');
+}
@@ -0,0 +1,247 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+mod common;
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+
+use gui::MutCap;
+use gui::Ui;
+
+use crate::common::Event;
+use crate::common::Message;
+use crate::common::TestWidget;
+use crate::common::TestWidgetDataBuilder;
+
+
+/// The shared state of a `Receiver` and its `Sender`.
+#[derive(Default)]
+struct Shared {
+  value: Option<Message>,
+  waker: Option<Waker>,
+}
+
+/// A minimal executor agnostic channel for a single message.
+struct Sender(Rc<RefCell<Shared>>);
+
+impl Sender {
+  fn send(self, message: Message) {
+    let mut shared = self.0.borrow_mut();
+    shared.value = Some(message);
+    if let Some(waker) = shared.waker.take() {
+      waker.wake()
+    }
+  }
+}
+
+struct Receiver(Rc<RefCell<Shared>>);
+
+impl Future for Receiver {
+  type Output = Message;
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let mut shared = self.0.borrow_mut();
+    match shared.value.take() {
+      Some(message) => Poll::Ready(message),
+      None => {
+        shared.waker = Some(cx.waker().clone());
+        Poll::Pending
+      },
+    }
+  }
+}
+
+fn channel() -> (Sender, Receiver) {
+  let shared = Rc::new(RefCell::new(Shared::default()));
+  (Sender(shared.clone()), Receiver(shared))
+}
+
+
+/// Check that running tasks without any being spawned returns
+/// immediately.
+#[tokio::test]
+async fn no_tasks() {
+  let (mut ui, _) = Ui::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+
+  assert!(!ui.has_tasks());
+  assert_eq!(ui.run_tasks().await, 0);
+}
+
+/// Test that the output of a task spawned from an event handler is
+/// delivered to the widget.
+#[tokio::test]
+async fn task_output_is_delivered() {
+  let received = Rc::new(RefCell::new(Vec::new()));
+  let received_clone = received.clone();
+
+  let (mut ui, root) = Ui::new(
+    move || {
+      let data = TestWidgetDataBuilder::new()
+        .event_handler(|id, cap, event| {
+          let value = event.unwrap_int();
+          cap.spawn(id, Box::pin(async move { Message::new(value * 2) }));
+          None
+        })
+        .react_handler(move |message, _cap| {
+          received_clone.borrow_mut().push(message.value);
+          None
+        })
+        .build();
+      Box::new(data)
+    },
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  ui.focus(root);
+
+  assert!(ui.handle(Event::Int(2)).await.is_none());
+  assert!(ui.handle(Event::Int(5)).await.is_none());
+  assert!(ui.has_tasks());
+  // The task must not have run yet.
+  assert!(received.borrow().is_empty());
+
+  assert_eq!(ui.run_tasks().await, 2);
+  assert!(!ui.has_tasks());
+
+  let mut values = received.borrow().clone();
+  values.sort_unstable();
+  assert_eq!(values, vec![4, 10]);
+}
+
+/// Check that a task waiting for some external event gets woken up and
+/// delivers its result.
+#[tokio::test]
+async fn pending_task_is_woken() {
+  let received = Rc::new(RefCell::new(None));
+  let received_clone = received.clone();
+
+  let (mut ui, root) = Ui::new(
+    move || {
+      let data = TestWidgetDataBuilder::new()
+        .react_handler(move |message, _cap| {
+          *received_clone.borrow_mut() = Some(message);
+          None
+        })
+        .build();
+      Box::new(data)
+    },
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+
+  let (sender, receiver) = channel();
+  MutCap::spawn(&mut ui, root, Box::pin(receiver));
+
+  let send = async move {
+    tokio::task::yield_now().await;
+    sender.send(Message::new(1337));
+  };
+
+  let (count, ()) = tokio::join!(ui.run_tasks(), send);
+  assert_eq!(count, 1);
+  assert_eq!(*received.borrow(), Some(Message::new(1337)));
+}
+
+/// Check that a task can spawn another task once its output got
+/// delivered.
+#[tokio::test]
+async fn task_spawning_task() {
+  let (mut ui, root) = Ui::new(
+    move || {
+      let data = TestWidgetDataBuilder::new()
+        .react_handler(|message, cap| {
+          if message.value > 0 {
+            let root = cap.root_id();
+            let message = Message::new(message.value - 1);
+            cap.spawn(root, Box::pin(async move { message }));
+          }
+          None
+        })
+        .build();
+      Box::new(data)
+    },
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+
+  MutCap::spawn(&mut ui, root, Box::pin(async { Message::new(3) }));
+
+  let mut total = 0;
+  while ui.has_tasks() {
+    total += ui.run_tasks().await;
+  }
+  assert_eq!(total, 4);
+}
+
+/// Check that tasks of hidden widgets and their descendants are
+/// dropped.
+#[tokio::test]
+async fn hiding_drops_tasks() {
+  let (mut ui, root) = Ui::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let w1 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w2 = ui
+    .add_ui_widget(
+      w1,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+
+  let (_sender, receiver) = channel();
+  MutCap::spawn(&mut ui, w2, Box::pin(receiver));
+  MutCap::spawn(&mut ui, root, Box::pin(async { Message::new(1) }));
+
+  ui.hide(w1);
+  assert!(ui.has_tasks());
+  assert_eq!(ui.run_tasks().await, 1);
+  assert!(!ui.has_tasks());
+}
+
+/// Check that tasks spawned for a widget that is not displayed are
+/// dropped right away.
+#[tokio::test]
+async fn hidden_widget_drops_spawned_tasks() {
+  let (mut ui, root) = Ui::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let w1 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w2 = ui
+    .add_ui_widget(
+      w1,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+
+  ui.hide(w1);
+  MutCap::spawn(&mut ui, w1, Box::pin(async { Message::new(1) }));
+  MutCap::spawn(&mut ui, w2, Box::pin(async { Message::new(2) }));
+  assert!(!ui.has_tasks());
+
+  ui.show(w1);
+  MutCap::spawn(&mut ui, w2, Box::pin(async { Message::new(3) }));
+  assert!(ui.has_tasks());
+  assert_eq!(ui.run_tasks().await, 1);
+}
@@ -0,0 +1,223 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+#![cfg(feature = "terminal")]
+
+mod common;
+
+use std::io::Error;
+use std::io::ErrorKind;
+use std::io::Result;
+use std::io::Write;
+
+use gui::derive::Handleable;
+use gui::derive::Widget;
+use gui::CellBuffer;
+use gui::CellStyle;
+use gui::Color;
+use gui::Constraint;
+use gui::Emphasis;
+use gui::Flow;
+use gui::Id;
+use gui::MutCap;
+use gui::Object;
+use gui::Size;
+use gui::Terminal;
+use gui::Ui;
+
+use crate::common::Event;
+use crate::common::Message;
+use crate::common::TestWidget;
+use crate::common::TestWidgetDataBuilder;
+
+
+/// A widget displaying the `String` stored as its data.
+#[derive(Debug, Widget, Handleable)]
+#[gui(Event = Event, Message = Message)]
+struct Label {
+  id: Id,
+}
+
+
+/// A writer failing all writes.
+#[derive(Debug)]
+struct FailingWriter;
+
+impl Write for FailingWriter {
+  fn write(&mut self, _buf: &[u8]) -> Result<usize> {
+    Err(Error::new(ErrorKind::Other, "broken pipe"))
+  }
+
+  fn flush(&mut self) -> Result<()> {
+    Ok(())
+  }
+}
+
+
+/// Create a `CellBuffer` drawing `Label`s as their text, in the given
+/// style.
+fn new_buffer(w: u16, h: u16, style: CellStyle) -> CellBuffer {
+  CellBuffer::new(Size { w, h }).on::<Label, _>(move |widget, cap, canvas| {
+    let text = cap.data(widget.id()).downcast_ref::<String>().unwrap();
+    let _ = canvas.text(0, 0, text, style);
+    canvas.bbox()
+  })
+}
+
+/// Create a `Ui` with a `Label` as the child of the root.
+fn new_ui(text: &str) -> (Ui<Event, Message>, Id) {
+  let (mut ui, root) = Ui::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let text = text.to_string();
+  let label = ui
+    .add_ui_widget(root, move || text, |id, _cap| Label { id })
+    .id();
+  (ui, label)
+}
+
+/// Retrieve and clear the output written to a terminal.
+fn take_output(terminal: &mut Terminal<Vec<u8>>) -> String {
+  String::from_utf8(terminal.get_mut().split_off(0)).unwrap()
+}
+
+
+/// Check that the first render writes all cells and subsequent ones
+/// only what changed.
+#[test]
+fn diff_updates() {
+  let (mut ui, label) = new_ui("ab");
+  let buffer = new_buffer(3, 2, CellStyle::default());
+  let mut terminal = Terminal::new(Vec::new(), buffer);
+
+  ui.render(&terminal);
+  let expected = "\x1b[1;1H\x1b[0mab \x1b[2;1H   \x1b[0m";
+  assert_eq!(take_output(&mut terminal), expected);
+
+  // Nothing changed, so nothing gets written.
+  ui.render(&terminal);
+  assert_eq!(take_output(&mut terminal), "");
+
+  *ui.data_mut(label).downcast_mut::<String>().unwrap() = "xb".to_string();
+  ui.render(&terminal);
+  assert_eq!(take_output(&mut terminal), "\x1b[1;1H\x1b[0mx\x1b[0m");
+
+  *ui.data_mut(label).downcast_mut::<String>().unwrap() = "ycd".to_string();
+  ui.render(&terminal);
+  assert_eq!(take_output(&mut terminal), "\x1b[1;1H\x1b[0mycd\x1b[0m");
+
+  let () = terminal.invalidate();
+  ui.render(&terminal);
+  let expected = "\x1b[1;1H\x1b[0mycd\x1b[2;1H   \x1b[0m";
+  assert_eq!(take_output(&mut terminal), expected);
+
+  let () = terminal.resize(Size { w: 1, h: 1 });
+  ui.render(&terminal);
+  let expected = "\x1b[2J\x1b[1;1H\x1b[0my\x1b[0m";
+  assert_eq!(take_output(&mut terminal), expected);
+  assert!(terminal.take_error().is_none());
+}
+
+/// Check that resizing the terminal clears the screen once and causes
+/// all cells to be written.
+#[test]
+fn resize() {
+  let (ui, _label) = new_ui("ab");
+  let buffer = new_buffer(3, 2, CellStyle::default());
+  let mut terminal = Terminal::new(Vec::new(), buffer);
+
+  ui.render(&terminal);
+  let _ = take_output(&mut terminal);
+
+  let () = terminal.resize(Size { w: 4, h: 1 });
+  ui.render(&terminal);
+  let expected = "\x1b[2J\x1b[1;1H\x1b[0mab  \x1b[0m";
+  assert_eq!(take_output(&mut terminal), expected);
+
+  ui.render(&terminal);
+  assert_eq!(take_output(&mut terminal), "");
+
+  // An invalidation writes all cells but leaves the screen alone.
+  let () = terminal.invalidate();
+  ui.render(&terminal);
+  assert_eq!(take_output(&mut terminal), "\x1b[1;1H\x1b[0mab  \x1b[0m");
+  assert!(terminal.take_error().is_none());
+}
+
+/// Check that a partial render pass keeps the cells of clean widgets
+/// and only writes what changed.
+#[test]
+fn render_dirty() {
+  let (mut ui, label1) = new_ui("ab");
+  let label2 = ui
+    .add_ui_widget(ui.root_id(), || "cd".to_string(), |id, _cap| Label { id })
+    .id();
+  let flow = Flow::column()
+    .child(label1, Constraint::Fixed(1))
+    .child(label2, Constraint::Fixed(1));
+  let _ = ui.set_layout(ui.root_id(), Some(Box::new(flow)));
+
+  let buffer = new_buffer(2, 2, CellStyle::default());
+  let mut terminal = Terminal::new(Vec::new(), buffer);
+  ui.render(&terminal);
+  let _ = take_output(&mut terminal);
+
+  *ui.data_mut(label2).downcast_mut::<String>().unwrap() = "xd".to_string();
+  ui.invalidate(label2);
+  ui.render_dirty(&terminal);
+  assert_eq!(take_output(&mut terminal), "\x1b[2;1H\x1b[0mx\x1b[0m");
+  assert_eq!(terminal.buffer().to_string(), "ab\nxd\n");
+}
+
+/// Check that colors and emphasis are emitted as SGR sequences.
+#[test]
+fn styles() {
+  let (ui, _) = new_ui("a");
+  let style = CellStyle {
+    fg: Color::Red,
+    bg: Color::Rgb(1, 2, 3),
+    emphasis: Emphasis {
+      bold: true,
+      underline: true,
+      ..Default::default()
+    },
+  };
+  let buffer = new_buffer(2, 1, style);
+  let terminal = Terminal::new(Vec::new(), buffer);
+  ui.render(&terminal);
+
+  let output = String::from_utf8(terminal.into_inner()).unwrap();
+  let expected = "\x1b[1;1H\x1b[0;1;4;31;48;2;1;2;3ma\x1b[0m \x1b[0m";
+  assert_eq!(output, expected);
+
+  let style = CellStyle {
+    fg: Color::Indexed(200),
+    bg: Color::White,
+    emphasis: Emphasis {
+      italic: true,
+      reverse: true,
+      ..Default::default()
+    },
+  };
+  let buffer = new_buffer(1, 1, style);
+  let terminal = Terminal::new(Vec::new(), buffer);
+  ui.render(&terminal);
+
+  let output = String::from_utf8(terminal.into_inner()).unwrap();
+  assert_eq!(output, "\x1b[1;1H\x1b[0;3;7;38;5;200;47ma\x1b[0m");
+}
+
+/// Check that write errors are reported and cause a full redraw.
+#[test]
+fn write_errors() {
+  let (ui, _) = new_ui("a");
+  let buffer = new_buffer(1, 1, CellStyle::default());
+  let terminal = Terminal::new(FailingWriter, buffer);
+
+  ui.render(&terminal);
+  ui.render(&terminal);
+  let error = terminal.take_error().unwrap();
+  assert_eq!(error.to_string(), "broken pipe");
+  assert!(terminal.take_error().is_none());
+}
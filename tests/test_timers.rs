@@ -0,0 +1,190 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use gui::Clock;
+use gui::Id;
+use gui::ManualClock;
+use gui::MutCap;
+use gui::Ui;
+
+use crate::common::Event;
+use crate::common::Message;
+use crate::common::TestWidget;
+use crate::common::TestWidgetDataBuilder;
+
+
+type Received = Rc<RefCell<Vec<u64>>>;
+
+/// Create a `Ui` with a root widget and two children, all of which
+/// record the messages they receive, along with a `ManualClock` driving
+/// it.
+fn new_ui() -> (Ui<Event, Message>, ManualClock, Received, [Id; 3]) {
+  let received = Received::default();
+  let new_data = |received: Received| {
+    TestWidgetDataBuilder::new()
+      .react_handler(move |message, _cap| {
+        received.borrow_mut().push(message.value);
+        None
+      })
+      .build()
+  };
+
+  let (mut ui, root) = Ui::new(
+    || Box::new(new_data(received.clone())),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let w1 = ui
+    .add_ui_widget(
+      root,
+      || new_data(received.clone()),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w2 = ui
+    .add_ui_widget(
+      w1,
+      || new_data(received.clone()),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+
+  let clock = ManualClock::default();
+  ui.set_clock(Box::new(clock.clone()));
+  (ui, clock, received, [root, w1, w2])
+}
+
+
+/// Check that a scheduled message is delivered once its deadline
+/// passed.
+#[tokio::test]
+async fn scheduled_message_delivery() {
+  let (mut ui, clock, received, [root, ..]) = new_ui();
+  assert_eq!(ui.next_deadline(), None);
+
+  let start = clock.now();
+  let _ = ui.schedule(root, Duration::from_secs(5), Message::new(42));
+  assert_eq!(ui.next_deadline(), Some(start + Duration::from_secs(5)));
+
+  clock.advance(Duration::from_secs(4));
+  assert_eq!(ui.fire_due(clock.now()).await, 0);
+  assert!(received.borrow().is_empty());
+
+  clock.advance(Duration::from_secs(1));
+  assert_eq!(ui.fire_due(clock.now()).await, 1);
+  assert_eq!(*received.borrow(), vec![42]);
+  assert_eq!(ui.next_deadline(), None);
+
+  // The timer fired already and should not do so again.
+  clock.advance(Duration::from_secs(10));
+  assert_eq!(ui.fire_due(clock.now()).await, 0);
+}
+
+/// Test that timers fire in the order of their deadlines.
+#[tokio::test]
+async fn timers_fire_in_deadline_order() {
+  let (mut ui, clock, received, [root, w1, w2]) = new_ui();
+
+  let _ = ui.schedule(w1, Duration::from_millis(30), Message::new(3));
+  let _ = ui.schedule(root, Duration::from_millis(10), Message::new(1));
+  let _ = ui.schedule(w2, Duration::from_millis(20), Message::new(2));
+
+  clock.advance(Duration::from_millis(30));
+  assert_eq!(ui.fire_due(clock.now()).await, 3);
+  assert_eq!(*received.borrow(), vec![1, 2, 3]);
+}
+
+/// Check that repeating timers fire repeatedly and coalesce missed
+/// expirations.
+#[tokio::test]
+async fn repeating_timer() {
+  let (mut ui, clock, received, [root, ..]) = new_ui();
+
+  let interval = Duration::from_millis(100);
+  let timer = ui.schedule_repeating(root, interval, Message::new(7));
+
+  for _ in 0..3 {
+    clock.advance(interval);
+    assert_eq!(ui.fire_due(clock.now()).await, 1);
+  }
+  assert_eq!(*received.borrow(), vec![7, 7, 7]);
+
+  // Missing several expirations results in a single message.
+  clock.advance(interval * 5);
+  assert_eq!(ui.fire_due(clock.now()).await, 1);
+  assert_eq!(ui.next_deadline(), Some(clock.now() + interval));
+
+  assert!(ui.cancel(timer));
+  assert!(!ui.cancel(timer));
+  assert_eq!(ui.next_deadline(), None);
+}
+
+/// Verify that hiding a widget cancels its timers as well as those of
+/// its descendants.
+#[tokio::test]
+async fn hiding_cancels_timers() {
+  let (mut ui, clock, received, [root, w1, w2]) = new_ui();
+
+  let _ = ui.schedule(root, Duration::from_secs(1), Message::new(1));
+  let t1 = ui.schedule(w1, Duration::from_secs(1), Message::new(2));
+  let t2 = ui.schedule_repeating(w2, Duration::from_secs(1), Message::new(3));
+
+  ui.hide(w1);
+  assert!(!ui.cancel(t1));
+  assert!(!ui.cancel(t2));
+
+  clock.advance(Duration::from_secs(1));
+  assert_eq!(ui.fire_due(clock.now()).await, 1);
+  assert_eq!(*received.borrow(), vec![1]);
+
+  // Timers for widgets that are not displayed are cancelled right
+  // away.
+  let t3 = ui.schedule(w2, Duration::from_secs(1), Message::new(4));
+  assert!(!ui.cancel(t3));
+  assert_eq!(ui.next_deadline(), None);
+}
+
+/// Check that repeating timers with a zero interval are rejected.
+#[test]
+#[should_panic(expected = "Timer interval must not be zero")]
+fn zero_interval() {
+  let (mut ui, _clock, _received, [root, ..]) = new_ui();
+  let _ = ui.schedule_repeating(root, Duration::ZERO, Message::new(1));
+}
+
+/// Check that a timer can be scheduled from a message handler.
+#[tokio::test]
+async fn schedule_from_handler() {
+  let clock = ManualClock::default();
+  let (mut ui, root) = Ui::new(
+    || {
+      let data = TestWidgetDataBuilder::new()
+        .react_handler(|message, cap| {
+          if message.value > 0 {
+            let root = cap.root_id();
+            let message = Message::new(message.value - 1);
+            let _ = cap.schedule(root, Duration::from_secs(1), message);
+          }
+          None
+        })
+        .build();
+      Box::new(data)
+    },
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  ui.set_clock(Box::new(clock.clone()));
+
+  let _ = ui.schedule(root, Duration::from_secs(1), Message::new(2));
+
+  let mut count = 0;
+  while let Some(deadline) = ui.next_deadline() {
+    clock.set(deadline);
+    count += ui.fire_due(clock.now()).await;
+  }
+  assert_eq!(count, 3);
+}
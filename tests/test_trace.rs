@@ -0,0 +1,217 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+mod common;
+
+use std::time::Duration;
+
+use gui::Clock;
+use gui::Id;
+use gui::ManualClock;
+use gui::MutCap;
+use gui::Recorder;
+use gui::ReplayError;
+use gui::TraceInput;
+use gui::Ui;
+
+use crate::common::Event;
+use crate::common::Message;
+use crate::common::TestWidget;
+use crate::common::TestWidgetDataBuilder;
+
+
+fn root_handler(widget: Id, cap: &mut dyn MutCap<Event, Message>, event: Event) -> Option<Event> {
+  match event {
+    Event::Key('z') => {
+      let _ = cap.add_widget(
+        widget,
+        Box::new(|| Box::new(TestWidgetDataBuilder::new().build())),
+        Box::new(|id, _cap| Box::new(TestWidget::new(id))),
+      );
+      None
+    },
+    Event::Key('f') => {
+      let last = *cap.children(widget).last().unwrap();
+      cap.focus(last);
+      None
+    },
+    Event::Key('t') => {
+      let _ = cap.schedule(widget, Duration::from_secs(1), Message::new(0));
+      None
+    },
+    Event::Key('s') => {
+      cap.spawn(widget, Box::pin(async { Message::new(1) }));
+      None
+    },
+    _ => Some(event),
+  }
+}
+
+fn root_reactor(message: Message, cap: &mut dyn MutCap<Event, Message>) -> Option<Message> {
+  let root = cap.root_id();
+  let child = *cap.children(root).nth(message.value as usize).unwrap();
+  cap.hide(child);
+  None
+}
+
+/// Create a `Ui` with a focused root widget that creates and focuses
+/// children on events and hides them on messages.
+fn new_ui() -> (Ui<Event, Message>, Id) {
+  let (mut ui, root) = Ui::new(
+    || {
+      let data = TestWidgetDataBuilder::new()
+        .event_handler(root_handler)
+        .react_handler(root_reactor)
+        .build();
+      Box::new(data)
+    },
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  ui.focus(root);
+  (ui, root)
+}
+
+
+/// Check the textual representation of the widget tree.
+#[tokio::test]
+async fn tree_dump() {
+  let (mut ui, root) = new_ui();
+  let _ = ui.handle(Event::Key('z')).await;
+  let _ = ui.handle(Event::Key('z')).await;
+  let _ = ui.send(root, Message::new(0)).await;
+
+  let expected = "\
+0 test_trace::common::TestWidget focused
+  1 test_trace::common::TestWidget hidden
+  2 test_trace::common::TestWidget
+";
+  assert_eq!(ui.dump(), expected);
+}
+
+/// Test that a recorded trace can be replayed on a fresh `Ui`.
+#[tokio::test]
+async fn record_and_replay() {
+  let (mut ui, root) = new_ui();
+  let mut recorder = Recorder::new(&ui);
+
+  let _ = recorder.handle(&mut ui, Event::Key('z')).await;
+  let _ = recorder.handle(&mut ui, Event::Key('z')).await;
+  let _ = recorder.handle(&mut ui, Event::Key('f')).await;
+  let _ = recorder.send(&mut ui, root, Message::new(1)).await;
+  let trace = recorder.finish(&ui);
+
+  assert_eq!(trace.entries.len(), 4);
+  assert_eq!(trace.entries[0].focused, Some(0));
+  assert_eq!(trace.entries[3].focused, Some(2));
+  assert_eq!(
+    trace.entries[3].input,
+    TraceInput::Message {
+      widget: 0,
+      message: Message::new(1),
+    }
+  );
+  assert_eq!(trace.dump, ui.dump());
+
+  let (mut replay_ui, _) = new_ui();
+  let result = trace.replay(&mut replay_ui).await;
+  assert_eq!(result, Ok(()));
+  assert_eq!(replay_ui.dump(), ui.dump());
+}
+
+/// Test that fired timers and completed tasks are recorded and replayed
+/// at the recorded times.
+#[tokio::test]
+async fn record_and_replay_timers_and_tasks() {
+  let (mut ui, _) = new_ui();
+  let clock = ManualClock::default();
+  ui.set_clock(Box::new(clock.clone()));
+  let mut recorder = Recorder::new(&ui);
+
+  let _ = recorder.handle(&mut ui, Event::Key('z')).await;
+  let _ = recorder.handle(&mut ui, Event::Key('z')).await;
+  let _ = recorder.handle(&mut ui, Event::Key('t')).await;
+  clock.advance(Duration::from_millis(500));
+  assert_eq!(recorder.fire_due(&mut ui, clock.now()).await, 0);
+  clock.advance(Duration::from_millis(500));
+  assert_eq!(recorder.fire_due(&mut ui, clock.now()).await, 1);
+  let _ = recorder.handle(&mut ui, Event::Key('s')).await;
+  assert_eq!(recorder.run_tasks(&mut ui).await, 1);
+  let mut trace = recorder.finish(&ui);
+
+  assert_eq!(trace.entries.len(), 7);
+  assert_eq!(trace.entries[3].time, Duration::from_millis(500));
+  assert_eq!(trace.entries[4].time, Duration::from_secs(1));
+  assert_eq!(trace.entries[4].input, TraceInput::Timers);
+  assert_eq!(
+    trace.entries[6].input,
+    TraceInput::Tasks {
+      completed: vec![(0, Message::new(1))],
+    }
+  );
+
+  let expected = "\
+0 test_trace::common::TestWidget focused
+  1 test_trace::common::TestWidget hidden
+  2 test_trace::common::TestWidget hidden
+";
+  assert_eq!(trace.dump, expected);
+
+  let (mut replay_ui, _) = new_ui();
+  assert_eq!(trace.replay(&mut replay_ui).await, Ok(()));
+  assert!(!replay_ui.has_tasks());
+
+  trace.entries[6].input = TraceInput::Tasks {
+    completed: vec![(42, Message::new(1))],
+  };
+  let (mut replay_ui, _) = new_ui();
+  let result = trace.replay(&mut replay_ui).await;
+  assert_eq!(result, Err(ReplayError::UnknownTask { entry: 6, task: 42 }));
+}
+
+/// Check that divergences during replay are detected.
+#[tokio::test]
+async fn replay_divergence() {
+  let (mut ui, _) = new_ui();
+  let mut recorder = Recorder::new(&ui);
+  let _ = recorder.handle(&mut ui, Event::Key('z')).await;
+  let _ = recorder.handle(&mut ui, Event::Key('f')).await;
+  let mut trace = recorder.finish(&ui);
+
+  // A `Ui` without the initial focus behaves differently.
+  let (mut replay_ui, root) = new_ui();
+  replay_ui.hide(root);
+  let result = trace.replay(&mut replay_ui).await;
+  assert_eq!(
+    result,
+    Err(ReplayError::FocusMismatch {
+      entry: 0,
+      expected: Some(0),
+      actual: None,
+    })
+  );
+
+  trace.dump.push_str("42 Foo\n");
+  let (mut replay_ui, _) = new_ui();
+  let error = trace.replay(&mut replay_ui).await.unwrap_err();
+  assert!(matches!(error, ReplayError::TreeMismatch { .. }), "{}", error);
+}
+
+/// Check that a trace can be serialized and deserialized.
+#[cfg(feature = "serde")]
+#[tokio::test]
+async fn trace_serialization() {
+  use gui::Trace;
+
+  let (mut ui, root) = new_ui();
+  let mut recorder = Recorder::new(&ui);
+  let _ = recorder.handle(&mut ui, Event::Key('z')).await;
+  let _ = recorder.send(&mut ui, root, Message::new(0)).await;
+  let trace = recorder.finish(&ui);
+
+  let json = serde_json::to_string(&trace).unwrap();
+  let deserialized = serde_json::from_str::<Trace<Event, Message>>(&json).unwrap();
+  assert_eq!(deserialized, trace);
+
+  let (mut replay_ui, _) = new_ui();
+  assert_eq!(deserialized.replay(&mut replay_ui).await, Ok(()));
+}
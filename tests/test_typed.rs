@@ -0,0 +1,130 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+mod common;
+
+use std::any::type_name;
+use std::cell::Cell;
+use std::rc::Rc;
+
+use gui::Cap;
+use gui::DataError;
+use gui::Id;
+use gui::MutCap;
+use gui::RecordingRenderer;
+use gui::TypedId;
+use gui::Ui;
+
+use crate::common::Event;
+use crate::common::Message;
+use crate::common::TestWidget;
+use crate::common::TestWidgetData;
+use crate::common::TestWidgetDataBuilder;
+
+
+/// Check that data of typed widgets can be accessed without
+/// downcasting.
+#[test]
+fn typed_data_access() {
+  let (mut ui, root) = Ui::<Event, Message>::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let widget = ui.add_ui_widget(root, || 42u64, |id, _cap| TestWidget::new(id));
+  let id = Id::from(widget);
+  assert_eq!(widget, id);
+  assert_eq!(ui.parent_id(widget.id()), Some(root));
+  assert_eq!(*ui.get(widget), 42);
+
+  ui.render(&RecordingRenderer::new(Default::default()));
+  assert!(!ui.needs_render());
+
+  *ui.get_mut(widget) += 1;
+  assert_eq!(*ui.get(widget), 43);
+  assert!(ui.needs_render());
+  assert_eq!(ui.try_data::<u64>(id), Ok(&43));
+}
+
+/// Check that typed data can be accessed via a `MutCap`.
+#[tokio::test]
+async fn typed_data_access_from_handler() {
+  let typed = Rc::new(Cell::new(None::<TypedId<TestWidget, String>>));
+  let typed_clone = typed.clone();
+  let (mut ui, root) = Ui::<Event, Message>::new(
+    || {
+      let data = TestWidgetDataBuilder::new()
+        .event_handler(move |_id, cap, _event| {
+          let widget = typed_clone.get().unwrap();
+          cap.get_mut(widget).push_str("bar");
+          assert_eq!(cap.try_data_mut::<String>(widget.id()).unwrap(), "foobar");
+          None
+        })
+        .build();
+      Box::new(data)
+    },
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let widget = ui.add_ui_widget(root, || "foo".to_string(), |id, _cap| TestWidget::new(id));
+  typed.set(Some(widget));
+  ui.focus(root);
+
+  assert_eq!(ui.handle(Event::Empty).await, None);
+  assert_eq!(ui.get(widget), "foobar");
+}
+
+/// Check that accessing data using the wrong type is reported as an
+/// error.
+#[test]
+fn data_type_mismatch() {
+  let (mut ui, root) = Ui::<Event, Message>::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let widget = ui.add_ui_widget(root, || 42u64, |id, _cap| TestWidget::new(id));
+
+  let err = ui.try_data::<String>(widget.id()).unwrap_err();
+  let expected = DataError::TypeMismatch {
+    widget: widget.id(),
+    expected: type_name::<String>(),
+    actual: Some("u64"),
+  };
+  assert_eq!(err, expected);
+  assert_eq!(
+    err.to_string(),
+    format!(
+      "data of widget {} are of type u64 and not {}",
+      widget,
+      type_name::<String>()
+    )
+  );
+
+  // The data type of widgets not added in typed form is unknown.
+  let err = ui.try_data_mut::<u64>(root).unwrap_err();
+  assert_eq!(
+    err,
+    DataError::TypeMismatch {
+      widget: root,
+      expected: "u64",
+      actual: None,
+    }
+  );
+  assert_eq!(err.to_string(), format!("data of widget {} are not of type u64", root));
+  assert!(ui.try_data::<TestWidgetData>(root).is_ok());
+}
+
+/// Check that using a `TypedId` handed out by a different `Ui` panics.
+#[test]
+#[should_panic]
+fn foreign_typed_id() {
+  let (mut ui1, root1) = Ui::<Event, Message>::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let widget = ui1.add_ui_widget(root1, || 42u64, |id, _cap| TestWidget::new(id));
+
+  let (ui2, _) = Ui::<Event, Message>::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let _ = ui2.get(widget);
+}
@@ -30,43 +30,55 @@ use crate::common::TestWidgetDataBuilder;
 #[test]
 fn correct_ids() {
   let (mut ui, root) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w1 = ui.add_ui_widget(
-    root,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w2 = ui.add_ui_widget(
-    root,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let w1 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w2 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
   // And a container.
-  let c1 = ui.add_ui_widget(
-    root,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
+  let c1 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
   // And a widget to the container.
-  let w3 = ui.add_ui_widget(
-    c1,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
+  let w3 = ui
+    .add_ui_widget(
+      c1,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
   // And another container for deeper nesting.
-  let c2 = ui.add_ui_widget(
-    c1,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
+  let c2 = ui
+    .add_ui_widget(
+      c1,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
   // And the last widget.
-  let w4 = ui.add_ui_widget(
-    c2,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
+  let w4 = ui
+    .add_ui_widget(
+      c2,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
 
   assert_eq!(ui.parent_id(root), None);
   assert_eq!(ui.parent_id(w1).unwrap(), root);
@@ -79,7 +91,7 @@ fn correct_ids() {
 #[test]
 fn debug_format() {
   let (ui, _) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
+    || Box::new(TestWidgetDataBuilder::new().build()),
     |id, _cap| Box::new(TestWidget::new(id)),
   );
 
@@ -100,34 +112,44 @@ fn debug_format() {
 #[test]
 fn creation_order_is_child_order() {
   let (mut ui, root) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w1 = ui.add_ui_widget(
-    root,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w11 = ui.add_ui_widget(
-    w1,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w12 = ui.add_ui_widget(
-    w1,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w2 = ui.add_ui_widget(
-    root,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w21 = ui.add_ui_widget(
-    w2,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let w1 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w11 = ui
+    .add_ui_widget(
+      w1,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w12 = ui
+    .add_ui_widget(
+      w1,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w2 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w21 = ui
+    .add_ui_widget(
+      w2,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
 
   let mut it = ui.children(root);
   assert_eq!(*it.next().unwrap(), w1);
@@ -153,14 +175,14 @@ fn creation_order_is_child_order() {
 #[should_panic(expected = "Created widget does not have provided Id")]
 async fn incorrect_widget_id() {
   let (mut ui, root) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
+    || Box::new(TestWidgetDataBuilder::new().build()),
     |id, _cap| Box::new(TestWidget::new(id)),
   );
   let _ = ui.add_ui_widget(
     root,
     || TestWidgetDataBuilder::new().build(),
     // Initialize the widget with the ID of the root widget.
-    |_id, _cap| Box::new(TestWidget::new(root)),
+    |_id, _cap| TestWidget::new(root),
   );
 }
 
@@ -169,17 +191,19 @@ async fn incorrect_widget_id() {
 #[should_panic(expected = "The given Id belongs to a different Ui")]
 async fn share_ids_between_ui_objects() {
   let (mut ui1, root) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let widget = ui1.add_ui_widget(
-    root,
-    || TestWidgetDataBuilder::new().build(),
+    || Box::new(TestWidgetDataBuilder::new().build()),
     |id, _cap| Box::new(TestWidget::new(id)),
   );
+  let widget = ui1
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
 
   let (mut ui2, _) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
+    || Box::new(TestWidgetDataBuilder::new().build()),
     |id, _cap| Box::new(TestWidget::new(id)),
   );
 
@@ -193,24 +217,30 @@ async fn share_ids_between_ui_objects() {
 #[test]
 fn visibility_fun() {
   let (mut ui, root) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w1 = ui.add_ui_widget(
-    root,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w2 = ui.add_ui_widget(
-    root,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w3 = ui.add_ui_widget(
-    w2,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let w1 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w2 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w3 = ui
+    .add_ui_widget(
+      w2,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
 
   assert!(ui.is_visible(root));
   assert!(ui.is_visible(w1));
@@ -265,7 +295,7 @@ fn visibility_fun() {
 #[test]
 fn no_initial_focus() {
   let (mut ui, root) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
+    || Box::new(TestWidgetDataBuilder::new().build()),
     |id, _cap| Box::new(TestWidget::new(id)),
   );
 
@@ -275,7 +305,7 @@ fn no_initial_focus() {
   let _ = ui.add_ui_widget(
     root,
     || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
+    |id, _cap| TestWidget::new(id),
   );
   assert!(ui.focused().is_none());
 }
@@ -283,14 +313,16 @@ fn no_initial_focus() {
 #[test]
 fn focus_widget() {
   let (mut ui, root) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let widget = ui.add_ui_widget(
-    root,
-    || TestWidgetDataBuilder::new().build(),
+    || Box::new(TestWidgetDataBuilder::new().build()),
     |id, _cap| Box::new(TestWidget::new(id)),
   );
+  let widget = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
 
   ui.focus(widget);
   assert!(ui.is_focused(widget));
@@ -299,14 +331,16 @@ fn focus_widget() {
 #[test]
 fn focus_makes_widget_visible() {
   let (mut ui, root) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let widget = ui.add_ui_widget(
-    root,
-    || TestWidgetDataBuilder::new().build(),
+    || Box::new(TestWidgetDataBuilder::new().build()),
     |id, _cap| Box::new(TestWidget::new(id)),
   );
+  let widget = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
 
   ui.hide(root);
   ui.hide(widget);
@@ -323,39 +357,51 @@ fn focus_makes_widget_visible() {
 #[test]
 fn focus_changes_child_order() {
   let (mut ui, root) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w1 = ui.add_ui_widget(
-    root,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w11 = ui.add_ui_widget(
-    w1,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w2 = ui.add_ui_widget(
-    root,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w21 = ui.add_ui_widget(
-    w2,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w22 = ui.add_ui_widget(
-    w2,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w23 = ui.add_ui_widget(
-    w2,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let w1 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w11 = ui
+    .add_ui_widget(
+      w1,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w2 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w21 = ui
+    .add_ui_widget(
+      w2,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w22 = ui
+    .add_ui_widget(
+      w2,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w23 = ui
+    .add_ui_widget(
+      w2,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
 
   ui.focus(w1);
   {
@@ -397,24 +443,30 @@ fn focus_changes_child_order() {
 #[test]
 fn hide_and_show_preserve_order() {
   let (mut ui, root) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w1 = ui.add_ui_widget(
-    root,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w2 = ui.add_ui_widget(
-    root,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
-  let w3 = ui.add_ui_widget(
-    root,
-    || TestWidgetDataBuilder::new().build(),
-    |id, _cap| Box::new(TestWidget::new(id)),
-  );
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let w1 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w2 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
+  let w3 = ui
+    .add_ui_widget(
+      root,
+      || TestWidgetDataBuilder::new().build(),
+      |id, _cap| TestWidget::new(id),
+    )
+    .id();
 
   let before = ui.children(root).cloned().collect::<Vec<_>>();
 
@@ -471,9 +523,10 @@ impl CreatingWidget {
     let child = cap.add_widget(
       id,
       Box::new(|| {
-        TestWidgetDataBuilder::new()
+        let data = TestWidgetDataBuilder::new()
           .event_handler(counting_handler)
-          .build()
+          .build();
+        Box::new(data)
       }),
       Box::new(|id, _cap| Box::new(TestWidget::new(id))),
     );
@@ -521,6 +574,7 @@ async fn recursive_widget_creation() {
 }
 
 
+#[allow(dead_code)]
 #[derive(Debug)]
 struct Moveable {}
 
@@ -542,14 +596,10 @@ impl MovingWidget {
 #[test]
 fn moving_widget_creation() {
   let (mut ui, root) = Ui::new(
-    || TestWidgetDataBuilder::new().build(),
+    || Box::new(TestWidgetDataBuilder::new().build()),
     |id, _cap| Box::new(TestWidget::new(id)),
   );
-  let _ = ui.add_ui_widget(
-    root,
-    || Box::new(()),
-    |id, _cap| Box::new(MovingWidget::new(id)),
-  );
+  let _ = ui.add_ui_widget(root, || (), |id, _cap| MovingWidget::new(id));
 }
 
 
@@ -573,9 +623,10 @@ fn create_handler(widget: Id, cap: &mut dyn MutCap<Event, Message>, event: Event
 async fn event_based_widget_creation() {
   let (mut ui, root) = Ui::new(
     || {
-      TestWidgetDataBuilder::new()
+      let data = TestWidgetDataBuilder::new()
         .event_handler(create_handler)
-        .build()
+        .build();
+      Box::new(data)
     },
     |id, _cap| Box::new(TestWidget::new(id)),
   );
@@ -610,9 +661,10 @@ fn recursive_operations_handler(
 async fn recursive_widget_operations() {
   let (mut ui, root) = Ui::new(
     || {
-      TestWidgetDataBuilder::new()
+      let data = TestWidgetDataBuilder::new()
         .event_handler(recursive_operations_handler)
-        .build()
+        .build();
+      Box::new(data)
     },
     |id, _cap| Box::new(TestWidget::new(id)),
   );
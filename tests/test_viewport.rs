@@ -0,0 +1,174 @@
+// Copyright (C) 2024 Daniel Mueller (deso@posteo.net)
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+mod common;
+
+use std::cell::RefCell;
+
+use gui::BBox;
+use gui::Cap;
+use gui::Constraint;
+use gui::Flow;
+use gui::Id;
+use gui::MutCap;
+use gui::Object;
+use gui::Point;
+use gui::Renderable;
+use gui::Renderer;
+use gui::Size;
+use gui::Ui;
+use gui::Viewport;
+
+use crate::common::Event;
+use crate::common::Message;
+use crate::common::TestWidget;
+use crate::common::TestWidgetDataBuilder;
+
+
+/// A renderer recording the bounding box of each rendered widget.
+#[derive(Debug, Default)]
+struct BBoxRecorder {
+  bboxes: RefCell<Vec<(Id, BBox)>>,
+}
+
+impl BBoxRecorder {
+  fn rendered(&self) -> Vec<(Id, BBox)> {
+    self.bboxes.borrow_mut().drain(..).collect()
+  }
+}
+
+impl Renderer for BBoxRecorder {
+  fn renderable_area(&self) -> BBox {
+    BBox {
+      x: 0,
+      y: 0,
+      w: 20,
+      h: 10,
+    }
+  }
+
+  fn render(&self, object: &dyn Renderable, _cap: &dyn Cap, bbox: BBox) -> BBox {
+    let widget = object.downcast_ref::<TestWidget>().unwrap();
+    self.bboxes.borrow_mut().push((widget.id(), bbox));
+    bbox
+  }
+}
+
+
+fn bbox(x: u16, y: u16, w: u16, h: u16) -> BBox {
+  BBox { x, y, w, h }
+}
+
+fn viewport(x: u16, y: u16) -> Viewport {
+  Viewport {
+    content: Size { w: 20, h: 30 },
+    offset: Point { x, y },
+  }
+}
+
+/// Create a `Ui` with a root widget having a viewport and three
+/// children, each ten units high, stacked on top of each other.
+fn new_ui() -> (Ui<Event, Message>, Id, Vec<Id>) {
+  let (mut ui, root) = Ui::new(
+    || Box::new(TestWidgetDataBuilder::new().build()),
+    |id, _cap| Box::new(TestWidget::new(id)),
+  );
+  let children = (0..3)
+    .map(|_| {
+      ui.add_ui_widget(
+        root,
+        || TestWidgetDataBuilder::new().build(),
+        |id, _cap| TestWidget::new(id),
+      )
+      .id()
+    })
+    .collect::<Vec<_>>();
+
+  let flow = children.iter().fold(Flow::column(), |flow, child| {
+    flow.child(*child, Constraint::Fixed(10))
+  });
+  let _ = ui.set_layout(root, Some(Box::new(flow)));
+  let previous = ui.set_viewport(root, Some(viewport(0, 5)));
+  assert_eq!(previous, None);
+  (ui, root, children)
+}
+
+
+/// Check that children of a widget with a viewport are translated and
+/// clipped.
+#[test]
+fn scrolled_children() {
+  let (ui, root, c) = new_ui();
+  let renderer = BBoxRecorder::default();
+  ui.render(&renderer);
+
+  assert_eq!(
+    renderer.rendered(),
+    vec![
+      (root, bbox(0, 0, 20, 10)),
+      (c[1], bbox(0, 5, 20, 5)),
+      (c[0], bbox(0, 0, 20, 5)),
+    ]
+  );
+  assert_eq!(ui.viewport(root), Some(viewport(0, 5)));
+  // The last child is scrolled out of view entirely.
+  assert_eq!(ui.culled_count(), 1);
+
+  let unclipped = ui.unclipped_bbox(c[0]).unwrap();
+  assert_eq!(
+    unclipped,
+    BBox {
+      x: 0,
+      y: -5,
+      w: 20,
+      h: 10,
+    }
+  );
+  assert_eq!(ui.unclipped_bbox(c[2]).unwrap().y, 15);
+}
+
+/// Test that widgets can be scrolled into view.
+#[test]
+fn scroll_into_view() {
+  let (mut ui, root, c) = new_ui();
+  let renderer = BBoxRecorder::default();
+  ui.render(&renderer);
+  let _ = renderer.rendered();
+
+  ui.scroll_into_view(c[2]);
+  assert_eq!(ui.viewport(root), Some(viewport(0, 20)));
+  assert!(ui.needs_render());
+
+  // Scrolling again before rendering should not change anything.
+  ui.scroll_into_view(c[2]);
+  assert_eq!(ui.viewport(root), Some(viewport(0, 20)));
+
+  ui.render_dirty(&renderer);
+  assert_eq!(
+    renderer.rendered(),
+    vec![(root, bbox(0, 0, 20, 10)), (c[2], bbox(0, 0, 20, 10))]
+  );
+
+  // A widget already displayed does not cause any scrolling.
+  ui.scroll_into_view(c[2]);
+  assert!(!ui.needs_render());
+
+  ui.scroll_into_view(c[1]);
+  assert_eq!(ui.viewport(root), Some(viewport(0, 10)));
+  ui.scroll_into_view(c[0]);
+  assert_eq!(ui.viewport(root), Some(viewport(0, 0)));
+}
+
+/// Check that scrolling is limited to the content area.
+#[test]
+fn scroll_into_view_clamps_offset() {
+  let (mut ui, root, c) = new_ui();
+  let _ = ui.set_viewport(root, Some(viewport(5, 25)));
+  let renderer = BBoxRecorder::default();
+  ui.render(&renderer);
+
+  // The widget is partly displayed already, but the offset is out of
+  // range horizontally.
+  ui.scroll_into_view(c[2]);
+  assert_eq!(ui.viewport(root), Some(viewport(0, 20)));
+}